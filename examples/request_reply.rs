@@ -102,7 +102,7 @@ fn responder(context: Context) {
                 .build()
                 .expect("could not build message");
 
-            let _ = replier.publish(reply_msg);
+            let _ = replier.publish(&reply_msg);
         } else {
             println!("Got message without reply to address")
         }
@@ -44,7 +44,7 @@ fn main() {
                 .build()
                 .expect("could not build message")
         };
-        session.publish(message).expect("message to be sent");
+        session.publish(&message).expect("message to be sent");
         sleep(Duration::new(1, 0));
     }
 
@@ -1,22 +1,32 @@
-extern crate bindgen;
 use std::sync::Arc;
-use std::{env, io::Write, path::PathBuf};
+use std::{
+    env,
+    io::Write,
+    path::{Path, PathBuf},
+};
 use ureq::Agent;
 
-#[cfg(target_os = "windows")]
-const SOLCLIENT_GZ_PATH: &str = "solclient_Win_vs2015_7.26.1.8.tar.gz";
+/// Version of the bundled C library, keep in sync with the tarball names.
+const SOLCLIENT_VERSION: &str = "7.26.1.8";
 
-#[cfg(target_os = "macos")]
-const SOLCLIENT_GZ_PATH: &str = "solclient_Darwin-universal2_opt_7.26.1.8.tar.gz";
+/// Tarball of the C library for the target. Build scripts are compiled for the host, so the
+/// target is read from the environment set by cargo rather than with `cfg`.
+fn solclient_gz_path() -> &'static str {
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    let target_env = env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
 
-#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
-const SOLCLIENT_GZ_PATH: &str = "solclient_Linux26-x86_64_opt_7.26.1.8.tar.gz";
-
-#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
-const SOLCLIENT_GZ_PATH: &str = "solclient_Linux-aarch64_opt_7.26.1.8.tar.gz";
-
-#[cfg(all(target_os = "linux", target_arch = "x86_64", target_env = "musl"))]
-const SOLCLIENT_GZ_PATH: &str = "solclient_Linux_musl-x86_64_opt_7.26.1.8.tar.gz";
+    // musl has to be matched before the generic linux x86_64 tarball
+    match (os.as_str(), arch.as_str(), target_env.as_str()) {
+        ("macos", _, _) => "solclient_Darwin-universal2_opt_7.26.1.8.tar.gz",
+        ("linux", "x86_64", "musl") => "solclient_Linux_musl-x86_64_opt_7.26.1.8.tar.gz",
+        ("linux", "x86_64", _) => "solclient_Linux26-x86_64_opt_7.26.1.8.tar.gz",
+        ("linux", "aarch64", _) => "solclient_Linux-aarch64_opt_7.26.1.8.tar.gz",
+        _ => panic!(
+            "no solclient tarball for target {os} {arch} {target_env}, set SOLCLIENT_LIB_PATH"
+        ),
+    }
+}
 
 fn build_ureq_agent() -> Agent {
     rustls::crypto::ring::default_provider()
@@ -33,15 +43,110 @@ fn build_ureq_agent() -> Agent {
 
     ureq::builder().tls_config(Arc::new(tls_config)).build()
 }
-fn download_and_unpack(url: &str, tarball_path: PathBuf, tarball_unpack_path: PathBuf) {
+/// SHA-256 of the release tarballs at the default url, by file name, one entry for every
+/// tarball of `solclient_gz_path`. Tarballs from other urls are verified with
+/// `SOLCLIENT_TARBALL_SHA256`. A tarball without a hash fails the build unless
+/// `SOLCLIENT_TARBALL_SKIP_VERIFY` is set.
+const PINNED_SHA256: &[(&str, &str)] = &[];
+
+fn download(url: &str) -> Vec<u8> {
+    let gz_path = solclient_gz_path();
     let mut content = Vec::new();
-    build_ureq_agent()
+    let result = build_ureq_agent()
         .get(url)
         .call()
-        .unwrap()
-        .into_reader()
-        .read_to_end(&mut content)
-        .unwrap();
+        .map_err(|e| e.to_string())
+        .and_then(|response| {
+            response
+                .into_reader()
+                .read_to_end(&mut content)
+                .map_err(|e| e.to_string())
+        });
+
+    if let Err(e) = result {
+        panic!(
+            "failed to download solclient: {e}\n\
+             When building offline, either set SOLCLIENT_LIB_PATH to an unpacked solclient lib \
+             directory or put {gz_path} in the directory set by \
+             SOLCLIENT_TARBALL_CACHE_DIR."
+        );
+    }
+
+    content
+}
+
+/// Reads the tarball from `SOLCLIENT_TARBALL_CACHE_DIR` if it is there, otherwise downloads it
+/// and stores it in the cache directory. Either way the tarball is verified first, so a broken
+/// download never ends up in the cache.
+fn fetch_tarball(url: &str, expected_sha256: Option<&str>) -> Vec<u8> {
+    let cached_path = env::var_os("SOLCLIENT_TARBALL_CACHE_DIR")
+        .map(|dir| PathBuf::from(dir).join(solclient_gz_path()));
+
+    if let Some(path) = cached_path.as_ref().filter(|p| p.is_file()) {
+        eprintln!("Using cached solclient {}", path.display());
+        let content = std::fs::read(path).unwrap();
+        verify_sha256(&content, expected_sha256, Some(path));
+        return content;
+    }
+
+    eprintln!("Solclient not found. Downloading from {}", url);
+    let content = download(url);
+    verify_sha256(&content, expected_sha256, None);
+
+    if let Some(path) = cached_path {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::fs::write(path, &content).unwrap();
+    }
+
+    content
+}
+
+/// `cached_path` is the cache file the tarball was read from, if any.
+fn verify_sha256(content: &[u8], expected: Option<&str>, cached_path: Option<&Path>) {
+    let gz_path = solclient_gz_path();
+    let digest = ring::digest::digest(&ring::digest::SHA256, content);
+    let actual: String = digest.as_ref().iter().map(|b| format!("{b:02x}")).collect();
+
+    let Some(expected) = expected else {
+        if env::var_os("SOLCLIENT_TARBALL_SKIP_VERIFY").is_some() {
+            println!(
+                "cargo:warning=solclient tarball {gz_path} is not verified, \
+                 set SOLCLIENT_TARBALL_SHA256={actual} to pin it"
+            );
+            return;
+        }
+        panic!(
+            "solclient tarball {gz_path} has no pinned hash. Set \
+             SOLCLIENT_TARBALL_SHA256={actual} after checking it, or set \
+             SOLCLIENT_TARBALL_SKIP_VERIFY=1 to use it unverified."
+        );
+    };
+
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        let remove_hint = cached_path
+            .map(|p| {
+                format!(
+                    " Remove the cached tarball {} to download it again.",
+                    p.display()
+                )
+            })
+            .unwrap_or_default();
+        panic!(
+            "solclient tarball {gz_path} does not match its pinned hash. \
+             expected sha256 {expected} found {actual}.{remove_hint}"
+        );
+    }
+}
+
+fn download_and_unpack(
+    url: &str,
+    expected_sha256: Option<&str>,
+    tarball_path: PathBuf,
+    tarball_unpack_path: PathBuf,
+) {
+    let content = fetch_tarball(url, expected_sha256);
 
     let mut file_gz = std::fs::File::create(tarball_path.clone()).unwrap();
     file_gz.write_all(&content).unwrap();
@@ -63,59 +168,100 @@ fn download_and_unpack(url: &str, tarball_path: PathBuf, tarball_unpack_path: Pa
         .for_each(|x| println!("> {}", x.display()));
 }
 
+/// Generates the bindings into `OUT_DIR` instead of using the pregenerated ones. Needs libclang.
+#[cfg(feature = "bindgen")]
+fn generate_bindings(include_dir: &Path, out_dir: &Path) {
+    println!("cargo:rerun-if-changed=wrapper.h");
+    println!("cargo:rerun-if-env-changed=SOLCLIENT_INCLUDE_PATH");
+
+    // keep in sync with scripts/create-binding.sh
+    bindgen::Builder::default()
+        .header("wrapper.h")
+        .clang_arg(format!("-I{}", include_dir.display()))
+        .generate_comments(false)
+        .derive_default(true)
+        .allowlist_function("^solClient_.*")
+        .allowlist_var("^SOLCLIENT_.*")
+        .parse_callbacks(Box::new(bindgen::CargoCallbacks))
+        .generate()
+        .expect("failed to generate the solclient bindings, the bindgen feature requires libclang")
+        .write_to_file(out_dir.join("solace_binding.rs"))
+        .expect("failed to write the solclient bindings");
+}
+
+#[cfg(not(feature = "bindgen"))]
+fn generate_bindings(_include_dir: &Path, _out_dir: &Path) {}
+
 fn main() {
-    cfg_if::cfg_if! {
-        if #[cfg(target_os = "windows")] {
-            panic!("Windows currently not supported");
-        }
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    if target_os == "windows" {
+        panic!("Windows currently not supported");
     }
 
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
     // do nothing if we are just building the docs
     if std::env::var("DOCS_RS").is_ok() {
+        // the docs are built from the pregenerated bindings
+        if cfg!(feature = "bindgen") {
+            std::fs::copy("src/solace_binding.rs", out_dir.join("solace_binding.rs")).unwrap();
+        }
         return;
     }
 
-    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
-
-    let solclient_folder_name = "solclient-7.26.1.8";
-    let solclient_folder_path = out_dir.join(solclient_folder_name);
-
-    let solclient_tarball_default_url = format!(
-        "https://github.com/asimsedhain/solace-rs/releases/download/0.0.0.0/{SOLCLIENT_GZ_PATH}"
-    );
+    let solclient_folder_name = format!("solclient-{SOLCLIENT_VERSION}");
+    let solclient_folder_path = out_dir.join(&solclient_folder_name);
 
     let lib_dir = if env::var("SOLCLIENT_LIB_PATH").is_ok() {
         PathBuf::from(env::var("SOLCLIENT_LIB_PATH").unwrap())
     } else {
+        let gz_path = solclient_gz_path();
+        let solclient_tarball_default_url =
+            format!("https://github.com/asimsedhain/solace-rs/releases/download/0.0.0.0/{gz_path}");
         let solclient_tarball_url =
-            env::var("SOLCLIENT_TARBALL_URL").unwrap_or(solclient_tarball_default_url);
+            env::var("SOLCLIENT_TARBALL_URL").unwrap_or(solclient_tarball_default_url.clone());
 
         let solclient_tarball_path = out_dir.join(format!("{solclient_folder_name}.tar.gz"));
 
+        // the pinned hashes only apply to the tarballs at the default url
+        let expected_sha256 = env::var("SOLCLIENT_TARBALL_SHA256").ok().or_else(|| {
+            PINNED_SHA256
+                .iter()
+                .find(|(name, _)| {
+                    *name == gz_path && solclient_tarball_url == solclient_tarball_default_url
+                })
+                .map(|(_, sha256)| sha256.to_string())
+        });
+
         if !solclient_folder_path.is_dir() {
-            eprintln!(
-                "Solclient not found. Downloading from {}",
-                solclient_tarball_url
-            );
             download_and_unpack(
                 &solclient_tarball_url,
+                expected_sha256.as_deref(),
                 solclient_tarball_path,
                 solclient_folder_path.clone(),
             );
         }
 
+        println!("cargo:rustc-env=SOLCLIENT_BUNDLED_VERSION={SOLCLIENT_VERSION}");
         solclient_folder_path.join("lib")
     };
 
+    // the headers are only needed to generate the bindings
+    if cfg!(feature = "bindgen") {
+        let include_dir = match env::var("SOLCLIENT_INCLUDE_PATH") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => lib_dir.with_file_name("include"),
+        };
+        generate_bindings(&include_dir, &out_dir);
+    }
+
     println!(
         "cargo:rustc-link-search=native={}",
         lib_dir.as_path().display()
     );
 
-    cfg_if::cfg_if! {
-        if #[cfg(target_os = "macos")] {
-            println!("cargo:rustc-link-lib=dylib=gssapi_krb5");
-        }
+    if target_os == "macos" {
+        println!("cargo:rustc-link-lib=dylib=gssapi_krb5");
     }
 
     println!("cargo:rustc-link-lib=static=crypto");
@@ -3,4 +3,16 @@
 #![allow(non_snake_case)]
 #![allow(dead_code)]
 
+#[cfg(not(any(feature = "pregenerated-bindings", feature = "bindgen")))]
+compile_error!("either the `pregenerated-bindings` or the `bindgen` feature has to be enabled");
+
+// bindgen takes precedence so that enabling it is enough to regenerate the bindings
+#[cfg(not(feature = "bindgen"))]
 include!("solace_binding.rs");
+
+#[cfg(feature = "bindgen")]
+include!(concat!(env!("OUT_DIR"), "/solace_binding.rs"));
+
+/// Version of the bundled solclient tarball the crate was linked against, `None` when built
+/// against `SOLCLIENT_LIB_PATH`.
+pub const SOLCLIENT_BUNDLED_VERSION: Option<&str> = option_env!("SOLCLIENT_BUNDLED_VERSION");
@@ -1,17 +1,56 @@
+pub mod builder;
+
+pub use builder::{CacheSessionBuilder, CacheSessionBuilderError};
+
 use std::{
     ffi::CString,
-    ops::{Deref, DerefMut},
+    mem::ManuallyDrop,
     ptr,
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use solace_rs_sys as ffi;
 use tracing::warn;
 
 use crate::{
-    message::InboundMessage, session::SessionEvent, util::get_last_error_info, Session,
-    SessionError, SolClientReturnCode,
+    message::{InboundMessage, OutboundMessage},
+    session::{SessionEvent, SubscribeOutcome},
+    Session, SessionError, SolClientReturnCode,
 };
 
+/// How live data on the requested topic is handled while the cache request is outstanding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheRequestMode {
+    /// Only the cached messages are delivered. No subscription is added for the topic, which is
+    /// useful for loading snapshots.
+    CachedOnly,
+    /// Live data is queued until the cached messages are delivered
+    /// (`SOLCLIENT_CACHEREQUEST_FLAGS_LIVEDATA_QUEUE`).
+    CachedFirstThenLive,
+    /// Live data is delivered as soon as it arrives, interleaved with the cached messages.
+    LiveDataFlowThru,
+    /// The request completes as soon as live data arrives, cached messages are only delivered if
+    /// they arrive first.
+    LiveDataFulfill,
+}
+
+impl CacheRequestMode {
+    fn flags(self) -> u32 {
+        match self {
+            Self::CachedOnly => {
+                ffi::SOLCLIENT_CACHEREQUEST_FLAGS_NO_SUBSCRIBE
+                    | ffi::SOLCLIENT_CACHEREQUEST_FLAGS_LIVEDATA_FLOWTHRU
+            }
+            Self::CachedFirstThenLive => ffi::SOLCLIENT_CACHEREQUEST_FLAGS_LIVEDATA_QUEUE,
+            Self::LiveDataFlowThru => ffi::SOLCLIENT_CACHEREQUEST_FLAGS_LIVEDATA_FLOWTHRU,
+            Self::LiveDataFulfill => ffi::SOLCLIENT_CACHEREQUEST_FLAGS_LIVEDATA_FULFILL,
+        }
+    }
+}
+
+// shared by all cache sessions since they can deliver to the same message callback
+static NEXT_CACHE_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
 pub struct CacheSession<
     'session,
     M: FnMut(InboundMessage) + Send + 'session,
@@ -23,119 +62,97 @@ pub struct CacheSession<
     pub(crate) session: Session<'session, M, E>,
 }
 
+// not `Sync` for the same reason as `Session`
 unsafe impl<M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send> Send
     for CacheSession<'_, M, E>
 {
 }
-unsafe impl<M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send> Sync
+
+impl<M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send> Drop
     for CacheSession<'_, M, E>
 {
+    fn drop(&mut self) {
+        self.destroy();
+    }
 }
 
-impl<'session, M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send> Deref
-    for CacheSession<'session, M, E>
+impl<'session, M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send>
+    CacheSession<'session, M, E>
 {
-    type Target = Session<'session, M, E>;
-
-    fn deref(&self) -> &Self::Target {
+    /// The session the cache session was created on.
+    pub fn session(&self) -> &Session<'session, M, E> {
         &self.session
     }
-}
 
-impl<M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send> Drop
-    for CacheSession<'_, M, E>
-{
-    fn drop(&mut self) {
-        let session_free_result =
-            unsafe { ffi::solClient_cacheSession_destroy(&mut self._cache_session_pt) };
-        let rc = SolClientReturnCode::from_raw(session_free_result);
+    /// Pointer to the C cache session, to call functions of the C library that are not wrapped
+    /// yet.
+    ///
+    /// # Safety
+    /// The pointer is only valid while the cache session is borrowed. It must not be destroyed.
+    pub unsafe fn as_raw(&self) -> ffi::solClient_opaqueCacheSession_pt {
+        self._cache_session_pt
+    }
 
-        if !rc.is_ok() {
-            warn!("cache session was not dropped properly. {rc}");
-        }
+    /// Publishes on the underlying session, see [`Session::publish`].
+    pub fn publish(&self, message: &OutboundMessage) -> Result<(), SessionError> {
+        self.session.publish(message)
     }
-}
 
-impl<M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send> DerefMut
-    for CacheSession<'_, M, E>
-{
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.session
+    /// Subscribes on the underlying session, see [`Session::subscribe`].
+    pub fn subscribe<T>(&self, topic: T) -> Result<SubscribeOutcome, SessionError>
+    where
+        T: Into<Vec<u8>>,
+    {
+        self.session.subscribe(topic)
     }
-}
 
-impl<'session, M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send>
-    CacheSession<'session, M, E>
-{
-    pub(crate) fn new<N>(
-        session: Session<'session, M, E>,
-        cache_name: N,
-        max_message: Option<u64>,
-        max_age: Option<u64>,
-        timeout_ms: Option<u64>,
-    ) -> Result<Self, SessionError>
+    /// Unsubscribes on the underlying session, see [`Session::unsubscribe`].
+    pub fn unsubscribe<T>(&self, topic: T) -> Result<(), SessionError>
     where
-        N: Into<Vec<u8>>,
+        T: Into<Vec<u8>>,
     {
-        let c_cache_name = CString::new(cache_name)?;
-        let c_max_message = CString::new(max_message.unwrap_or(1).to_string())?;
-        let c_max_age = CString::new(max_age.unwrap_or(0).to_string())?;
-        let c_timeout_ms = CString::new(timeout_ms.unwrap_or(10000).to_string())?;
-
-        // Note: Needs to live long enough for the values to be copied
-        let mut cache_session_props = [
-            ffi::SOLCLIENT_CACHESESSION_PROP_CACHE_NAME.as_ptr() as *const i8,
-            c_cache_name.as_ptr(),
-            ffi::SOLCLIENT_CACHESESSION_PROP_DEFAULT_MAX_MSGS.as_ptr() as *const i8,
-            c_max_message.as_ptr(),
-            ffi::SOLCLIENT_CACHESESSION_PROP_MAX_AGE.as_ptr() as *const i8,
-            c_max_age.as_ptr(),
-            ffi::SOLCLIENT_CACHESESSION_PROP_REQUESTREPLY_TIMEOUT_MS.as_ptr() as *const i8,
-            c_timeout_ms.as_ptr(),
-            ptr::null(),
-        ];
-
-        let mut cache_session_pt: ffi::solClient_opaqueCacheSession_pt = ptr::null_mut();
-
-        let cache_create_raw_result = unsafe {
-            ffi::solClient_session_createCacheSession(
-                cache_session_props.as_mut_ptr(),
-                session._session_ptr,
-                &mut cache_session_pt,
-            )
-        };
+        self.session.unsubscribe(topic)
+    }
 
-        let rc = SolClientReturnCode::from_raw(cache_create_raw_result);
+    /// Destroys the cache session and disconnects the underlying session.
+    pub fn disconnect(self) -> Result<(), SessionError> {
+        let mut this = ManuallyDrop::new(self);
+        this.destroy();
+        // the cache session is destroyed, only the session is left to be dropped
+        let session = unsafe { ptr::read(&this.session) };
+        session.disconnect()
+    }
+
+    fn destroy(&mut self) {
+        let rc = unsafe { ffi::solClient_cacheSession_destroy(&mut self._cache_session_pt) };
+        let rc = SolClientReturnCode::from_raw(rc);
 
         if !rc.is_ok() {
-            let subcode = get_last_error_info();
-            return Err(SessionError::InitializationFailure(rc, subcode));
+            warn!(
+                session_id = self.session.state.id(),
+                client_name = self.session.state.client_name(),
+                "cache session was not dropped properly. {rc}"
+            );
         }
+    }
 
-        Ok(CacheSession {
-            session,
-            _cache_session_pt: cache_session_pt,
-        })
+    /// Generates a process unique cache request id. Cached messages carry the id of the request
+    /// they answer, see [`InboundMessage::cache_info`].
+    pub fn next_request_id(&self) -> u64 {
+        NEXT_CACHE_REQUEST_ID.fetch_add(1, Ordering::Relaxed)
     }
 
     pub fn blocking_cache_request<T>(
         &self,
         topic: T,
         request_id: u64,
-        subscribe: bool,
+        mode: CacheRequestMode,
     ) -> Result<(), SessionError>
     where
         T: Into<Vec<u8>>,
     {
         let c_topic = CString::new(topic)?;
 
-        let flags = if subscribe {
-            ffi::SOLCLIENT_CACHEREQUEST_FLAGS_LIVEDATA_FLOWTHRU
-                & ffi::SOLCLIENT_CACHEREQUEST_FLAGS_NO_SUBSCRIBE
-        } else {
-            ffi::SOLCLIENT_CACHEREQUEST_FLAGS_LIVEDATA_FLOWTHRU
-        };
-
         let rc = unsafe {
             ffi::solClient_cacheSession_sendCacheRequest(
                 self._cache_session_pt,
@@ -143,16 +160,13 @@ impl<'session, M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send>
                 request_id,
                 None,
                 ptr::null_mut(),
-                flags,
+                mode.flags(),
                 0,
             )
         };
 
         let rc = SolClientReturnCode::from_raw(rc);
-        if !rc.is_ok() {
-            let subcode = get_last_error_info();
-            return Err(SessionError::CacheRequestFailure(rc, subcode));
-        }
+        rc.to_result(SessionError::CacheRequestFailure)?;
 
         Ok(())
     }
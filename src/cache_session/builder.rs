@@ -0,0 +1,136 @@
+use solace_rs_sys as ffi;
+use std::{
+    ffi::{CString, NulError},
+    ptr,
+};
+
+use crate::{
+    message::InboundMessage, props::PropList, session::SessionEvent, Session, SolClientReturnCode,
+    SolClientSubCode,
+};
+
+use super::CacheSession;
+
+#[derive(thiserror::Error, Debug)]
+pub enum CacheSessionBuilderError {
+    #[error("cache session failed to initialize. SolClient return code: {0} subcode: {1}")]
+    InitializationFailure(SolClientReturnCode, SolClientSubCode),
+    #[error("{0} arg contains interior nul byte")]
+    InvalidArgs(String, #[source] NulError),
+    #[error("{0} arg need to be set")]
+    MissingRequiredArgs(String),
+}
+
+type Result<T> = std::result::Result<T, CacheSessionBuilderError>;
+
+fn to_c_string<T: Into<Vec<u8>>>(field: &str, value: T) -> Result<CString> {
+    CString::new(value).map_err(|e| CacheSessionBuilderError::InvalidArgs(field.to_owned(), e))
+}
+
+/// `CacheSessionBuilder` creates a [`CacheSession`] on top of an existing session.
+///
+/// For more detailed documentation on all the configuration field, refer to [the official library documentation](https://docs.solace.com/API-Developer-Online-Ref-Documentation/c/group___cache_session_props.html).
+pub struct CacheSessionBuilder<'session, M, E>
+where
+    M: FnMut(InboundMessage) + Send + 'session,
+    E: FnMut(SessionEvent) + Send + 'session,
+{
+    session: Session<'session, M, E>,
+
+    // Note: required params
+    cache_name: Option<Vec<u8>>,
+
+    // Note: optional params
+    max_messages: Option<u64>,
+    max_age: Option<u64>,
+    timeout_ms: Option<u64>,
+}
+
+impl<'session, M, E> CacheSessionBuilder<'session, M, E>
+where
+    M: FnMut(InboundMessage) + Send + 'session,
+    E: FnMut(SessionEvent) + Send + 'session,
+{
+    pub(crate) fn new(session: Session<'session, M, E>) -> Self {
+        Self {
+            session,
+            cache_name: None,
+            max_messages: None,
+            max_age: None,
+            timeout_ms: None,
+        }
+    }
+
+    pub fn cache_name<N: Into<Vec<u8>>>(mut self, cache_name: N) -> Self {
+        self.cache_name = Some(cache_name.into());
+        self
+    }
+
+    /// Maximum number of messages per topic to retrieve. 0 retrieves all messages.
+    pub fn max_messages(mut self, max_messages: u64) -> Self {
+        self.max_messages = Some(max_messages);
+        self
+    }
+
+    /// Maximum age in seconds of the messages to retrieve. 0 retrieves messages of any age.
+    pub fn max_age(mut self, max_age: u64) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    pub fn build(self) -> Result<CacheSession<'session, M, E>> {
+        let Some(cache_name) = self.cache_name else {
+            return Err(CacheSessionBuilderError::MissingRequiredArgs(
+                "cache_name".to_owned(),
+            ));
+        };
+
+        let c_cache_name = to_c_string("cache_name", cache_name)?;
+        let c_max_messages = self
+            .max_messages
+            .map(|x| to_c_string("max_messages", x.to_string()))
+            .transpose()?;
+        let c_max_age = self
+            .max_age
+            .map(|x| to_c_string("max_age", x.to_string()))
+            .transpose()?;
+        let c_timeout_ms = self
+            .timeout_ms
+            .map(|x| to_c_string("timeout_ms", x.to_string()))
+            .transpose()?;
+
+        // Note: Needs to live long enough for the values to be copied
+        let mut cache_session_props = PropList::new();
+        cache_session_props.push(ffi::SOLCLIENT_CACHESESSION_PROP_CACHE_NAME, &c_cache_name);
+        cache_session_props.push_opt(ffi::SOLCLIENT_CACHESESSION_PROP_MAX_MSGS, &c_max_messages);
+        cache_session_props.push_opt(ffi::SOLCLIENT_CACHESESSION_PROP_MAX_AGE, &c_max_age);
+        cache_session_props.push_opt(
+            ffi::SOLCLIENT_CACHESESSION_PROP_REQUESTREPLY_TIMEOUT_MS,
+            &c_timeout_ms,
+        );
+
+        let mut cache_session_pt: ffi::solClient_opaqueCacheSession_pt = ptr::null_mut();
+
+        let cache_create_raw_result = unsafe {
+            ffi::solClient_session_createCacheSession(
+                cache_session_props.as_mut_ptr(),
+                self.session._session_ptr,
+                &mut cache_session_pt,
+            )
+        };
+
+        let rc = SolClientReturnCode::from_raw(cache_create_raw_result);
+
+        rc.to_result(CacheSessionBuilderError::InitializationFailure)?;
+
+        Ok(CacheSession {
+            session: self.session,
+            _cache_session_pt: cache_session_pt,
+        })
+    }
+}
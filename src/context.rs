@@ -1,14 +1,22 @@
+pub mod builder;
+mod stats;
+
+pub use builder::{ContextBuilder, GlobalProps};
+pub use stats::ContextStats;
+pub(crate) use stats::{CallbackKind, ContextCounters};
+
 use crate::session::builder::SessionBuilder;
 use crate::session::builder::SessionBuilderError;
-use crate::util::get_last_error_info;
+use crate::session::SessionConfig;
 use crate::Session;
 use crate::{ContextError, SolClientReturnCode, SolaceLogLevel};
 use solace_rs_sys as ffi;
+use std::collections::HashMap;
+use std::ffi::c_char;
 use std::mem;
 use std::ptr;
-use std::sync::Mutex;
-use std::sync::OnceLock;
-use tracing::warn;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use tracing::{info, warn};
 
 use crate::message::InboundMessage;
 use crate::session::SessionEvent;
@@ -18,9 +26,19 @@ type Result<T> = std::result::Result<T, ContextError>;
 pub(super) struct RawContext {
     // This pointer must never be allowed to leave the struct
     pub(crate) ctx: ffi::solClient_opaqueContext_pt,
+    // sessions created on the context, by session id
+    sessions: HashMap<u64, LiveSession>,
+}
+
+struct LiveSession {
+    client_name: String,
+    // unregistered before the session is destroyed
+    session_ptr: ffi::solClient_opaqueSession_pt,
 }
 
-static SOLACE_GLOBAL_INIT: OnceLock<i32> = OnceLock::new();
+// only set once the library initialized, so that a failed initialization is retried by the next
+// context instead of failing every context of the process
+static SOLACE_GLOBAL_INIT: Mutex<bool> = Mutex::new(false);
 
 impl RawContext {
     /// .
@@ -32,17 +50,33 @@ impl RawContext {
     ///
     /// # Safety
     /// Context initializes global variables so it is not safe to have multiple solace contexts.
+    /// `global_props` and `context_props` must be null terminated lists of key value pairs.
     /// .
-    pub unsafe fn new(log_level: SolaceLogLevel) -> Result<Self> {
-        let rc = SOLACE_GLOBAL_INIT
-            .get_or_init(|| ffi::solClient_initialize(log_level as u32, ptr::null_mut()));
+    pub unsafe fn new(
+        log_level: SolaceLogLevel,
+        global_props: Option<&mut [*const c_char]>,
+        context_props: &mut [*const c_char],
+    ) -> Result<Self> {
+        let mut global_init = SOLACE_GLOBAL_INIT
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if *global_init {
+            // global props can only be applied by the first initialization
+            if global_props.is_some() {
+                return Err(ContextError::AlreadyInitialized);
+            }
+        } else {
+            let global_props = global_props.map_or(ptr::null_mut(), |p| p.as_mut_ptr());
+            let rc = SolClientReturnCode::from_raw(ffi::solClient_initialize(
+                log_level as u32,
+                global_props,
+            ));
 
-        let rc = SolClientReturnCode::from_raw(*rc);
-
-        if !rc.is_ok() {
-            let subcode = get_last_error_info();
-            return Err(ContextError::InitializationFailed(rc, subcode));
+            rc.to_result(ContextError::InitializationFailed)?;
+            *global_init = true;
+            info!("initialized {}", crate::solclient_version());
         }
+        drop(global_init);
         let mut ctx: ffi::solClient_opaqueContext_pt = ptr::null_mut();
         let mut context_func: ffi::solClient_context_createFuncInfo_t =
             ffi::solClient_context_createFuncInfo {
@@ -53,16 +87,11 @@ impl RawContext {
                 },
             };
 
-        // enable context thread
-        let mut conext_props: [*const i8; 3] = [
-            solace_rs_sys::SOLCLIENT_CONTEXT_PROP_CREATE_THREAD.as_ptr() as *const i8,
-            solace_rs_sys::SOLCLIENT_PROP_ENABLE_VAL.as_ptr() as *const i8,
-            ptr::null(),
-        ];
+        debug_assert!(context_props.last().is_some_and(|p| p.is_null()));
 
         let solace_context_raw_rc = unsafe {
             ffi::solClient_context_create(
-                conext_props.as_mut_ptr(),
+                context_props.as_mut_ptr(),
                 &mut ctx,
                 &mut context_func,
                 mem::size_of::<ffi::solClient_context_createRegisterFdFuncInfo>(),
@@ -71,16 +100,18 @@ impl RawContext {
 
         let rc = SolClientReturnCode::from_raw(solace_context_raw_rc);
 
-        if !rc.is_ok() {
-            let subcode = get_last_error_info();
-            return Err(ContextError::InitializationFailed(rc, subcode));
-        }
-        Ok(Self { ctx })
+        rc.to_result(ContextError::InitializationFailed)?;
+        Ok(Self {
+            ctx,
+            sessions: HashMap::new(),
+        })
     }
 }
 
+// every session holds a handle to the context, so it is only destroyed after its sessions
 impl Drop for RawContext {
     fn drop(&mut self) {
+        debug_assert!(self.sessions.is_empty());
         let return_code = unsafe { ffi::solClient_context_destroy(&mut self.ctx) };
         if return_code != ffi::solClient_returnCode_SOLCLIENT_OK {
             warn!("Solace context did not drop properly");
@@ -101,16 +132,140 @@ unsafe impl Send for RawContext {}
 /// context.
 ///
 ///
+///
+/// The C context is only destroyed once every session created from it is dropped, even if all
+/// handles were dropped before.
 #[derive(Clone)]
 pub struct Context {
     pub(super) raw: Arc<Mutex<RawContext>>,
+    counters: Arc<ContextCounters>,
+    // shared by the handles of the application, `None` for the handles held by sessions
+    handle: Option<Arc<()>>,
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        let Some(handle) = &self.handle else {
+            return;
+        };
+        if Arc::strong_count(handle) > 1 {
+            return;
+        }
+
+        let raw = self.lock_raw();
+        if !raw.sessions.is_empty() {
+            let client_names: Vec<_> = raw.sessions.values().map(|s| &s.client_name).collect();
+            info!(
+                ?client_names,
+                "last context handle dropped, the context is destroyed once its sessions are dropped"
+            );
+        }
+    }
 }
 
 impl Context {
     pub fn new(log_level: SolaceLogLevel) -> std::result::Result<Self, ContextError> {
-        Ok(Self {
-            raw: Arc::new(Mutex::new(unsafe { RawContext::new(log_level) }?)),
-        })
+        ContextBuilder::new(log_level).build()
+    }
+
+    /// Builder for a context with custom context thread options.
+    pub fn builder(log_level: SolaceLogLevel) -> ContextBuilder {
+        ContextBuilder::new(log_level)
+    }
+
+    /// Creates a context with process wide properties, see [`GlobalProps`].
+    pub fn new_with_props(
+        log_level: SolaceLogLevel,
+        global_props: GlobalProps,
+    ) -> std::result::Result<Self, ContextError> {
+        ContextBuilder::new(log_level)
+            .global_props(global_props)
+            .build()
+    }
+
+    pub(crate) fn from_raw(raw: RawContext) -> Self {
+        Self {
+            raw: Arc::new(Mutex::new(raw)),
+            counters: Arc::new(ContextCounters::default()),
+            handle: Some(Arc::new(())),
+        }
+    }
+
+    /// Handle kept by a session, which does not count as a handle of the application.
+    pub(crate) fn session_handle(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            counters: self.counters.clone(),
+            handle: None,
+        }
+    }
+
+    pub(crate) fn register_session(
+        &self,
+        session_id: u64,
+        client_name: &str,
+        session_ptr: ffi::solClient_opaqueSession_pt,
+    ) {
+        let session = LiveSession {
+            client_name: client_name.to_owned(),
+            session_ptr,
+        };
+        self.lock_raw().sessions.insert(session_id, session);
+    }
+
+    /// Locks the C context. A panic while it was locked leaves it consistent, since the lock only
+    /// guards calls into the C library and the session registry, so poisoning is ignored.
+    pub(crate) fn lock_raw(&self) -> MutexGuard<'_, RawContext> {
+        self.raw.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn counters(&self) -> Arc<ContextCounters> {
+        self.counters.clone()
+    }
+
+    pub(crate) fn unregister_session(&self, session_id: u64) {
+        self.lock_raw().sessions.remove(&session_id);
+    }
+
+    /// Pointer to the C context, to call functions of the C library that are not wrapped yet.
+    ///
+    /// # Safety
+    /// The pointer is only valid while the context is borrowed. It must not be destroyed, and
+    /// timers or file descriptors registered on it must be removed before the context is dropped.
+    pub unsafe fn as_raw(&self) -> ffi::solClient_opaqueContext_pt {
+        self.lock_raw().ctx
+    }
+
+    /// Client names of the sessions created from the context that are not dropped yet.
+    pub fn live_sessions(&self) -> Vec<String> {
+        let raw = self.lock_raw();
+        raw.sessions
+            .values()
+            .map(|s| s.client_name.clone())
+            .collect()
+    }
+
+    /// Counters of the callbacks dispatched on the context thread, see [`ContextStats`].
+    pub fn stats(&self) -> ContextStats {
+        let raw = self.lock_raw();
+        // the sessions are unregistered before they are destroyed, which needs the lock
+        let rx_messages = raw
+            .sessions
+            .values()
+            .filter_map(|s| {
+                let mut value: u64 = 0;
+                let rc = unsafe {
+                    ffi::solClient_session_getRxStat(
+                        s.session_ptr,
+                        ffi::solClient_stats_rx_SOLCLIENT_STATS_RX_MSGS,
+                        &mut value,
+                    )
+                };
+                SolClientReturnCode::from_raw(rc).is_ok().then_some(value)
+            })
+            .sum();
+
+        self.counters.stats(raw.sessions.len(), rx_messages)
     }
 
     pub fn session_builder<Host, Vpn, Username, Password, OnMessage, OnEvent>(
@@ -119,6 +274,14 @@ impl Context {
         SessionBuilder::new(self.clone())
     }
 
+    /// Builder with the properties of the config, see [`SessionBuilder::config`].
+    pub fn session_builder_from_config<OnMessage, OnEvent>(
+        &self,
+        config: &SessionConfig,
+    ) -> SessionBuilder<Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>, OnMessage, OnEvent> {
+        SessionBuilder::<Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>, _, _>::new(self.clone()).config(config)
+    }
+
     pub fn session<'session, Host, Vpn, Username, Password, OnMessage, OnEvent>(
         &self,
         host_name: Host,
@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+use std::ffi::CString;
+
+use solace_rs_sys as ffi;
+
+use super::{Context, RawContext};
+use crate::props::PropList;
+use crate::{ContextError, SolaceLogLevel};
+
+type Result<T> = std::result::Result<T, ContextError>;
+
+fn to_c_string<T: Into<Vec<u8>>>(field: &str, value: T) -> Result<CString> {
+    CString::new(value).map_err(|e| ContextError::InvalidArgs(field.to_owned(), e))
+}
+
+/// Process wide properties of the C library. They are applied when the library is initialized,
+/// which happens once with the first context created in the process. If the initialization
+/// fails, the next context tries again and can pass other props.
+///
+/// For more detailed documentation on all the configuration field, refer to [the official library documentation](https://docs.solace.com/API-Developer-Online-Ref-Documentation/c/group___global_props.html).
+#[derive(Debug, Clone, Default)]
+pub struct GlobalProps {
+    // by pool, validated when the props are converted
+    dbquanta_sizes: BTreeMap<usize, u64>,
+    max_pool_mem: Option<u64>,
+    gss_krb_lib: Option<Vec<u8>>,
+    ssl_lib: Option<Vec<u8>>,
+    crypto_lib: Option<Vec<u8>>,
+    ibm_codeset: Option<Vec<u8>>,
+}
+
+impl GlobalProps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Size of the data blocks in the given message pool, `pool` ranges from 0 to 4. Building the
+    /// context fails with [`ContextError::InvalidRange`] for other pools.
+    pub fn dbquanta_size(mut self, pool: usize, size: u64) -> Self {
+        self.dbquanta_sizes.insert(pool, size);
+        self
+    }
+
+    /// Maximum memory in bytes the message pool can hold on to.
+    pub fn max_pool_mem(mut self, max_pool_mem: u64) -> Self {
+        self.max_pool_mem = Some(max_pool_mem);
+        self
+    }
+
+    /// Path of the GSS Kerberos library.
+    pub fn gss_krb_lib<Lib: Into<Vec<u8>>>(mut self, gss_krb_lib: Lib) -> Self {
+        self.gss_krb_lib = Some(gss_krb_lib.into());
+        self
+    }
+
+    /// Path of the SSL library.
+    pub fn ssl_lib<Lib: Into<Vec<u8>>>(mut self, ssl_lib: Lib) -> Self {
+        self.ssl_lib = Some(ssl_lib.into());
+        self
+    }
+
+    /// Path of the crypto library.
+    pub fn crypto_lib<Lib: Into<Vec<u8>>>(mut self, crypto_lib: Lib) -> Self {
+        self.crypto_lib = Some(crypto_lib.into());
+        self
+    }
+
+    pub fn ibm_codeset<Codeset: Into<Vec<u8>>>(mut self, ibm_codeset: Codeset) -> Self {
+        self.ibm_codeset = Some(ibm_codeset.into());
+        self
+    }
+
+    fn to_c_props(&self) -> Result<Vec<(&'static [u8], CString)>> {
+        const DBQUANTA_KEYS: [&[u8]; 5] = [
+            ffi::SOLCLIENT_GLOBAL_PROP_DBQUANTASIZE_0,
+            ffi::SOLCLIENT_GLOBAL_PROP_DBQUANTASIZE_1,
+            ffi::SOLCLIENT_GLOBAL_PROP_DBQUANTASIZE_2,
+            ffi::SOLCLIENT_GLOBAL_PROP_DBQUANTASIZE_3,
+            ffi::SOLCLIENT_GLOBAL_PROP_DBQUANTASIZE_4,
+        ];
+
+        let mut props = Vec::new();
+
+        for (&pool, size) in &self.dbquanta_sizes {
+            let key = DBQUANTA_KEYS.get(pool).ok_or_else(|| {
+                ContextError::InvalidRange(
+                    "dbquanta_size pool".to_owned(),
+                    format!("0 to {}", DBQUANTA_KEYS.len() - 1),
+                    pool.to_string(),
+                )
+            })?;
+            props.push((*key, to_c_string("dbquanta_size", size.to_string())?));
+        }
+
+        if let Some(x) = self.max_pool_mem {
+            props.push((
+                &ffi::SOLCLIENT_GLOBAL_PROP_MAXPOOLMEM[..],
+                to_c_string("max_pool_mem", x.to_string())?,
+            ));
+        }
+
+        let libs = [
+            (
+                &ffi::SOLCLIENT_GLOBAL_PROP_GSS_KRB_LIB[..],
+                "gss_krb_lib",
+                &self.gss_krb_lib,
+            ),
+            (
+                &ffi::SOLCLIENT_GLOBAL_PROP_SSL_LIB[..],
+                "ssl_lib",
+                &self.ssl_lib,
+            ),
+            (
+                &ffi::SOLCLIENT_GLOBAL_PROP_CRYPTO_LIB[..],
+                "crypto_lib",
+                &self.crypto_lib,
+            ),
+            (
+                &ffi::SOLCLIENT_GLOBAL_PROP_IBM_CODESET[..],
+                "ibm_codeset",
+                &self.ibm_codeset,
+            ),
+        ];
+
+        for (key, field, value) in libs {
+            if let Some(value) = value {
+                props.push((key, to_c_string(field, value.clone())?));
+            }
+        }
+
+        Ok(props)
+    }
+}
+
+/// `ContextBuilder` allows configuring the context thread that dispatches the callbacks of all
+/// sessions created from the context.
+///
+/// The C library does not expose the thread priority, use the operating system tools for that.
+///
+/// For more detailed documentation on all the configuration field, refer to [the official library documentation](https://docs.solace.com/API-Developer-Online-Ref-Documentation/c/group___context_props.html).
+pub struct ContextBuilder {
+    log_level: SolaceLogLevel,
+    thread_affinity: Option<u64>,
+    thread_affinity_cpus: Option<Vec<usize>>,
+    time_res_ms: Option<u64>,
+    global_props: Option<GlobalProps>,
+}
+
+impl ContextBuilder {
+    pub(crate) fn new(log_level: SolaceLogLevel) -> Self {
+        Self {
+            log_level,
+            thread_affinity: None,
+            thread_affinity_cpus: None,
+            time_res_ms: None,
+            global_props: None,
+        }
+    }
+
+    /// CPU mask the context thread is pinned to, bit `n` allows CPU `n`. 0 disables pinning.
+    pub fn thread_affinity(mut self, cpu_mask: u64) -> Self {
+        self.thread_affinity = Some(cpu_mask);
+        self.thread_affinity_cpus = None;
+        self
+    }
+
+    /// Pins the context thread to the given CPUs. Only CPUs 0 to 63 can be addressed.
+    pub fn thread_affinity_cpus(mut self, cpus: &[usize]) -> Self {
+        self.thread_affinity_cpus = Some(cpus.to_vec());
+        self.thread_affinity = None;
+        self
+    }
+
+    /// Resolution of the timers run by the context thread.
+    pub fn time_res_ms(mut self, time_res_ms: u64) -> Self {
+        self.time_res_ms = Some(time_res_ms);
+        self
+    }
+
+    /// Process wide properties. Building fails with [`ContextError::AlreadyInitialized`] if the
+    /// library was already initialized by another context.
+    pub fn global_props(mut self, global_props: GlobalProps) -> Self {
+        self.global_props = Some(global_props);
+        self
+    }
+
+    pub fn build(self) -> Result<Context> {
+        let thread_affinity = match self.thread_affinity_cpus {
+            Some(cpus) => {
+                let mut mask = 0u64;
+                for cpu in cpus {
+                    if cpu >= 64 {
+                        return Err(ContextError::InvalidRange(
+                            "thread_affinity_cpus".to_owned(),
+                            "< 64".to_owned(),
+                            cpu.to_string(),
+                        ));
+                    }
+                    mask |= 1 << cpu;
+                }
+                Some(mask)
+            }
+            None => self.thread_affinity,
+        };
+
+        let time_res_ms = match self.time_res_ms {
+            Some(x) if x < 1 => {
+                return Err(ContextError::InvalidRange(
+                    "time_res_ms".to_owned(),
+                    ">= 1".to_owned(),
+                    x.to_string(),
+                ));
+            }
+            x => x,
+        };
+
+        // numbers never contain a nul byte
+        let thread_affinity = thread_affinity.map(|x| CString::new(x.to_string()).unwrap());
+        let time_res_ms = time_res_ms.map(|x| CString::new(x.to_string()).unwrap());
+
+        // Note: Needs to live long enough for the values to be copied
+        // the context thread is always enabled
+        let mut props = PropList::new();
+        props.push_static(
+            ffi::SOLCLIENT_CONTEXT_PROP_CREATE_THREAD,
+            ffi::SOLCLIENT_PROP_ENABLE_VAL,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_CONTEXT_PROP_THREAD_AFFINITY,
+            &thread_affinity,
+        );
+        props.push_opt(ffi::SOLCLIENT_CONTEXT_PROP_TIME_RES_MS, &time_res_ms);
+
+        let global_props = self
+            .global_props
+            .as_ref()
+            .map(GlobalProps::to_c_props)
+            .transpose()?;
+        let mut raw_global_props = global_props.as_ref().map(|global_props| {
+            let mut raw = PropList::new();
+            for (key, value) in global_props {
+                raw.push(key, value);
+            }
+            raw
+        });
+
+        let raw = unsafe {
+            RawContext::new(
+                self.log_level,
+                raw_global_props.as_mut().map(PropList::as_mut_slice),
+                props.as_mut_slice(),
+            )
+        }?;
+
+        Ok(Context::from_raw(raw))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_only_include_set_global_props() {
+        let props = GlobalProps::new()
+            .dbquanta_size(2, 4096)
+            .ssl_lib("/opt/ssl/libssl.so")
+            .to_c_props()
+            .unwrap();
+
+        assert_eq!(2, props.len());
+        assert_eq!(&ffi::SOLCLIENT_GLOBAL_PROP_DBQUANTASIZE_2[..], props[0].0);
+        assert_eq!("4096", props[0].1.to_str().unwrap());
+        assert_eq!(&ffi::SOLCLIENT_GLOBAL_PROP_SSL_LIB[..], props[1].0);
+        assert_eq!("/opt/ssl/libssl.so", props[1].1.to_str().unwrap());
+    }
+
+    #[test]
+    fn it_should_reject_unknown_dbquanta_pool() {
+        let result = GlobalProps::new()
+            .dbquanta_size(2, 4096)
+            .dbquanta_size(5, 1)
+            .to_c_props();
+
+        assert!(matches!(
+            result,
+            Err(ContextError::InvalidRange(field, _, pool)) if field == "dbquanta_size pool" && pool == "5"
+        ));
+    }
+
+    #[test]
+    fn it_should_name_global_prop_with_nul_byte() {
+        let result = GlobalProps::new().gss_krb_lib("lib\0").to_c_props();
+
+        assert!(
+            matches!(result, Err(ContextError::InvalidArgs(field, _)) if field == "gss_krb_lib")
+        );
+    }
+}
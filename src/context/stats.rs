@@ -0,0 +1,97 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Counters of the context thread returned by [`Context::stats`](super::Context::stats).
+///
+/// The callback counters and times are monotonic over the lifetime of the context. Comparing
+/// the growth of `callback_time` with the wall clock time between two samples gives the share of
+/// the context thread spent in callbacks: when it approaches the whole interval, the context
+/// thread is the bottleneck and the sessions should be spread over more contexts.
+///
+/// The C library dispatches a single message per callback and does not expose the iterations of
+/// its event loop, so they are not part of the stats.
+#[derive(Debug, Clone, Default)]
+pub struct ContextStats {
+    /// Sessions of the context that are not dropped yet.
+    pub sessions: usize,
+    /// Messages received by the live sessions, read from the stats of the C library. Sessions
+    /// that were dropped no longer count.
+    pub rx_messages: u64,
+    /// `on_message` callbacks dispatched on the context thread.
+    pub message_callbacks: u64,
+    /// Session event callbacks dispatched on the context thread.
+    pub event_callbacks: u64,
+    /// Timer callbacks dispatched on the context thread, such as the ack timeout timer.
+    pub timer_callbacks: u64,
+    /// Total time spent in callbacks.
+    pub callback_time: Duration,
+    /// Longest single callback.
+    pub max_callback_time: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CallbackKind {
+    Message,
+    Event,
+    Timer,
+}
+
+/// Shared by the sessions of a context, which record every callback they dispatch.
+#[derive(Debug, Default)]
+pub(crate) struct ContextCounters {
+    message_callbacks: AtomicU64,
+    event_callbacks: AtomicU64,
+    timer_callbacks: AtomicU64,
+    callback_nanos: AtomicU64,
+    max_callback_nanos: AtomicU64,
+}
+
+impl ContextCounters {
+    pub(crate) fn record(&self, kind: CallbackKind, elapsed: Duration) {
+        let counter = match kind {
+            CallbackKind::Message => &self.message_callbacks,
+            CallbackKind::Event => &self.event_callbacks,
+            CallbackKind::Timer => &self.timer_callbacks,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.callback_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.max_callback_nanos.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self, sessions: usize, rx_messages: u64) -> ContextStats {
+        ContextStats {
+            sessions,
+            rx_messages,
+            message_callbacks: self.message_callbacks.load(Ordering::Relaxed),
+            event_callbacks: self.event_callbacks.load(Ordering::Relaxed),
+            timer_callbacks: self.timer_callbacks.load(Ordering::Relaxed),
+            callback_time: Duration::from_nanos(self.callback_nanos.load(Ordering::Relaxed)),
+            max_callback_time: Duration::from_nanos(
+                self.max_callback_nanos.load(Ordering::Relaxed),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_count_callbacks_by_kind() {
+        let counters = ContextCounters::default();
+        counters.record(CallbackKind::Message, Duration::from_millis(2));
+        counters.record(CallbackKind::Message, Duration::from_millis(5));
+        counters.record(CallbackKind::Event, Duration::from_millis(1));
+        counters.record(CallbackKind::Timer, Duration::from_micros(10));
+
+        let stats = counters.stats(1, 2);
+        assert_eq!(2, stats.message_callbacks);
+        assert_eq!(1, stats.event_callbacks);
+        assert_eq!(1, stats.timer_callbacks);
+        assert_eq!(Duration::from_micros(8010), stats.callback_time);
+        assert_eq!(Duration::from_millis(5), stats.max_callback_time);
+    }
+}
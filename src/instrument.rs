@@ -0,0 +1,47 @@
+//! Helpers to fill in the fields of the spans created with the `trace-instrumentation` feature.
+//! Without the feature they do nothing, so call sites do not need to be feature gated.
+
+use crate::message::OutboundMessage;
+use crate::SolClientReturnCode;
+
+#[cfg(feature = "trace-instrumentation")]
+pub(crate) fn record_message(message: &OutboundMessage) {
+    use crate::message::MessageRead;
+
+    let span = tracing::Span::current();
+    if span.is_disabled() {
+        return;
+    }
+
+    if let Ok(Some(destination)) = message.get_destination() {
+        span.record("destination", destination.dest.to_string_lossy().as_ref());
+    }
+    if let Ok(delivery_mode) = message.get_delivery_mode() {
+        span.record("delivery_mode", tracing::field::debug(delivery_mode));
+    }
+    if let Ok(payload_size) = message.payload_len() {
+        span.record("payload_size", payload_size);
+    }
+}
+
+#[cfg(not(feature = "trace-instrumentation"))]
+#[inline(always)]
+pub(crate) fn record_message(_message: &OutboundMessage) {}
+
+#[cfg(feature = "trace-instrumentation")]
+pub(crate) fn record_topic(topic: &std::ffi::CStr) {
+    tracing::Span::current().record("topic", topic.to_string_lossy().as_ref());
+}
+
+#[cfg(not(feature = "trace-instrumentation"))]
+#[inline(always)]
+pub(crate) fn record_topic(_topic: &std::ffi::CStr) {}
+
+#[cfg(feature = "trace-instrumentation")]
+pub(crate) fn record_rc(rc: &SolClientReturnCode) {
+    tracing::Span::current().record("rc", tracing::field::debug(rc));
+}
+
+#[cfg(not(feature = "trace-instrumentation"))]
+#[inline(always)]
+pub(crate) fn record_rc(_rc: &SolClientReturnCode) {}
@@ -1,16 +1,26 @@
 pub mod cache_session;
 pub mod context;
+pub(crate) mod instrument;
 pub mod message;
+pub(crate) mod props;
+pub mod selector;
 pub mod session;
 pub(crate) mod util;
+pub mod version;
 
+use cache_session::CacheSessionBuilderError;
 use enum_primitive::*;
+use message::outbound::MessageBuilderError;
+use message::{CodecError, MessageError};
+use selector::SelectorError;
+use session::{ReconnectError, SessionBuilderError};
 use solace_rs_sys as ffi;
 use std::fmt::{self, Display};
 use thiserror::Error;
 
 pub use crate::context::Context;
 pub use crate::session::Session;
+pub use crate::version::{solclient_version, SolClientVersion};
 
 // Generic error
 #[derive(Debug, Clone)]
@@ -36,7 +46,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(PartialEq, Eq)]
+    #[derive(PartialEq, Eq, Clone, Copy)]
     #[repr(i32)]
     pub enum SolClientReturnCode {
         Ok=ffi::solClient_returnCode_SOLCLIENT_OK,
@@ -89,12 +99,40 @@ impl SolClientReturnCode {
         }
     }
 
-    pub fn is_ok(&self) -> bool {
-        *self == Self::Ok
+    pub fn is_ok(self) -> bool {
+        self == Self::Ok
+    }
+
+    pub fn is_would_block(self) -> bool {
+        self == Self::WouldBlock
+    }
+
+    pub fn is_not_found(self) -> bool {
+        self == Self::NotFound
+    }
+
+    /// `Ok(())` if the call succeeded, otherwise the error built from the return code.
+    pub fn ok_or_else<E, F: FnOnce(Self) -> E>(self, err: F) -> Result<(), E> {
+        if self.is_ok() {
+            return Ok(());
+        }
+        Err(err(self))
+    }
+
+    /// Like [`Self::ok_or_else`], also passing the subcode of the failed call, so that error
+    /// variants can be used directly, e.g. `rc.to_result(SessionError::PublishError)?`.
+    ///
+    /// The subcode is read from the last error of the current thread, so this has to be called
+    /// right after the failed call.
+    pub fn to_result<E, F: FnOnce(Self, SolClientSubCode) -> E>(self, err: F) -> Result<(), E> {
+        if self.is_ok() {
+            return Ok(());
+        }
+        Err(err(self, util::get_last_error_info()))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SolClientSubCode {
     pub subcode: u32,
     pub error_string: String,
@@ -108,8 +146,14 @@ impl Display for SolClientSubCode {
 
 #[derive(Error, Debug)]
 pub enum ContextError {
-    #[error("context thread failed to initialize. SolClient return code: {0:?}")]
+    #[error("context thread failed to initialize. SolClient return code: {0:?} subcode: {1}")]
     InitializationFailed(SolClientReturnCode, SolClientSubCode),
+    #[error("{0} valid range is {1} found {2}")]
+    InvalidRange(String, String, String),
+    #[error("{0} arg contains interior nul byte")]
+    InvalidArgs(String, #[source] std::ffi::NulError),
+    #[error("global props can only be set before the first context is created")]
+    AlreadyInitialized,
 }
 
 #[derive(Error, Debug)]
@@ -122,14 +166,71 @@ pub enum SessionError {
     DisconnectError(SolClientReturnCode, SolClientSubCode),
     #[error("session failed to initialize. SolClient return code: {0} subcode: {1}")]
     InitializationFailure(SolClientReturnCode, SolClientSubCode),
-    #[error("session failed to subscribe on topic. SolClient return code: {0} subcode: {1}")]
+    #[error("session failed to subscribe on topic {0}. SolClient return code: {1} subcode: {2}")]
     SubscriptionFailure(String, SolClientReturnCode, SolClientSubCode),
-    #[error("session failed to unsubscribe on topic. SolClient return code: {0} subcode: {1}")]
+    #[error("session failed to unsubscribe on topic {0}. SolClient return code: {1} subcode: {2}")]
     UnsubscriptionFailure(String, SolClientReturnCode, SolClientSubCode),
-    #[error("cache request failed")]
+    #[error("cache request failed. SolClient return code: {0} subcode: {1}")]
     CacheRequestFailure(SolClientReturnCode, SolClientSubCode),
-    #[error("could not publish message. SolClient return code: {0}")]
+    #[error("could not publish message. SolClient return code: {0} subcode: {1}")]
     PublishError(SolClientReturnCode, SolClientSubCode),
-    #[error("could not send request. SolClient return code: {0}")]
+    #[error("could not send request. SolClient return code: {0} subcode: {1}")]
     RequestError(SolClientReturnCode, SolClientSubCode),
+    #[error("session failed to get property {0}. SolClient return code: {1} subcode: {2}")]
+    PropertyError(String, SolClientReturnCode, SolClientSubCode),
+    #[error("session failed to modify property {0}. SolClient return code: {1} subcode: {2}")]
+    ModifyPropertyError(String, SolClientReturnCode, SolClientSubCode),
+    #[error("session property {0} could not be converted")]
+    PropertyConversionError(String),
+    #[error("session failed to get stats. SolClient return code: {0} subcode: {1}")]
+    StatsError(SolClientReturnCode, SolClientSubCode),
+    #[error("broker rejected subscription on topic {0}. response code: {1} info: {2} {3}")]
+    SubscriptionRejected(String, u32, String, SolClientSubCode),
+    #[error("subscription on topic {0} was not confirmed in time")]
+    SubscriptionConfirmTimeout(String),
+    #[error("broker rejected unsubscribe on topic {0}. response code: {1} info: {2} {3}")]
+    UnsubscriptionRejected(String, u32, String, SolClientSubCode),
+    #[error("unsubscribe on topic {0} was not confirmed in time")]
+    UnsubscriptionConfirmTimeout(String),
+    #[error("session failed to build message")]
+    MessageBuildError(#[from] MessageBuilderError),
+    #[error("session failed to build cache session")]
+    CacheSessionBuildError(#[from] CacheSessionBuilderError),
+    #[error("session is closed")]
+    SessionClosed,
+    #[error("broker rejected message. response code: {0} info: {1} {2}")]
+    MessageRejected(u32, String, SolClientSubCode),
+    #[error("message was not acknowledged in time")]
+    PublishConfirmTimeout,
+    #[error("broker did not acknowledge or reject the message within the ack timeout")]
+    AckTimedOut,
+    #[error("payload codec failed")]
+    CodecError(#[from] CodecError),
+    #[error("failed to update message")]
+    MessageError(#[from] MessageError),
+    #[error("could not bind reply queue. SolClient return code: {0} subcode: {1}")]
+    ReplyQueueError(SolClientReturnCode, SolClientSubCode),
+    #[error("reply was not received in time")]
+    RequestTimeout,
+}
+
+/// Any error returned by this crate, for applications that want to use a single error type.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Context(#[from] ContextError),
+    #[error(transparent)]
+    Session(#[from] SessionError),
+    #[error(transparent)]
+    SessionBuilder(#[from] SessionBuilderError),
+    #[error(transparent)]
+    CacheSessionBuilder(#[from] CacheSessionBuilderError),
+    #[error(transparent)]
+    Reconnect(#[from] ReconnectError),
+    #[error(transparent)]
+    Message(#[from] MessageError),
+    #[error(transparent)]
+    MessageBuilder(#[from] MessageBuilderError),
+    #[error(transparent)]
+    Selector(#[from] SelectorError),
 }
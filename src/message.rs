@@ -1,26 +1,46 @@
+pub mod codec;
+pub mod dedup;
 pub mod destination;
 pub mod inbound;
+pub mod latency;
 pub mod outbound;
+pub mod owned;
+pub mod sdt;
 
 use crate::SolClientReturnCode;
+pub use codec::{CodecError, PayloadCodec};
+pub use dedup::{DedupKey, DedupWindow};
 pub use destination::{DestinationType, MessageDestination};
 use enum_primitive::*;
-pub use inbound::InboundMessage;
-pub use outbound::{OutboundMessage, OutboundMessageBuilder};
+pub use inbound::{CacheInfo, InboundMessage, Payload};
+pub use latency::LatencyStamp;
+pub use outbound::{OutboundMessage, OutboundMessageBuilder, Validation};
+pub use owned::OwnedMessage;
+pub use sdt::{SdtMap, SdtValue};
 use solace_rs_sys as ffi;
-use std::ffi::CStr;
+use std::ffi::{c_void, CStr, CString, NulError};
 use std::mem;
 use std::mem::size_of;
 use std::ptr;
 use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
+// timestamps before the UNIX epoch are not expected from the broker, but should not panic either
+pub(crate) fn millis_to_system_time(ms: i64) -> SystemTime {
+    let offset = Duration::from_millis(ms.unsigned_abs());
+    if ms >= 0 {
+        SystemTime::UNIX_EPOCH + offset
+    } else {
+        SystemTime::UNIX_EPOCH - offset
+    }
+}
+
 // the below assertions makes sure that u32 can always be converted into usize safely.
 #[allow(dead_code)]
 const ASSERT_USIZE_IS_AT_LEAST_U32: () = assert!(size_of::<u32>() <= size_of::<usize>());
 
 enum_from_primitive! {
-    #[derive(Debug, PartialEq, Eq)]
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
     #[repr(u32)]
     pub enum DeliveryMode {
         Direct=ffi::SOLCLIENT_DELIVERY_MODE_DIRECT,
@@ -30,7 +50,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(Debug, PartialEq, Eq)]
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
     #[repr(u32)]
     pub enum ClassOfService {
         One=ffi::SOLCLIENT_COS_1,
@@ -40,7 +60,7 @@ enum_from_primitive! {
 }
 
 enum_from_primitive! {
-    #[derive(Debug, PartialEq, Eq)]
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
     #[repr(i32)]
     pub enum CacheStatus {
         InvalidMessage=ffi::solClient_cacheStatus_SOLCLIENT_CACHE_INVALID_MESSAGE,
@@ -66,11 +86,22 @@ pub enum MessageError {
     FieldError(&'static str, SolClientReturnCode),
     #[error("failed to convert field from solace")]
     FieldConvertionError(&'static str),
+    #[error("failed to duplicate message. SolClient return code: {0}")]
+    DuplicationError(SolClientReturnCode),
+    #[error("{0} contains interior nul byte")]
+    InvalidArgs(&'static str, #[source] NulError),
 }
 
 type Result<T> = std::result::Result<T, MessageError>;
 
-pub trait Message<'a> {
+/// Previous name of [`MessageRead`], kept so that existing imports keep working.
+#[deprecated(note = "use MessageRead")]
+pub use self::MessageRead as Message;
+
+/// User property key used by Solace JMS for the `JMSXGroupID` header.
+pub const JMSX_GROUP_ID: &str = "JMSXGroupID";
+
+pub trait MessageRead<'a> {
     /// .
     ///
     /// # Safety
@@ -110,6 +141,35 @@ pub trait Message<'a> {
         Ok(Some(safe_slice))
     }
 
+    /// Size of the binary attachment in bytes, 0 if the message has no payload.
+    fn payload_len(&'a self) -> Result<usize> {
+        Ok(self.get_payload()?.map_or(0, <[u8]>::len))
+    }
+
+    /// Size in bytes of the data carried by the message: binary attachment, XML part and user
+    /// data. The C library does not expose the encoded size, so headers are not included.
+    fn total_size(&'a self) -> Result<usize> {
+        let mut xml_buffer = ptr::null_mut();
+        let mut xml_len: u32 = 0;
+
+        let rc = unsafe {
+            ffi::solClient_msg_getXmlPtr(self.get_raw_message_ptr(), &mut xml_buffer, &mut xml_len)
+        };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        let xml_len: usize = match rc {
+            // the compile time check ASSERT_USIZE_IS_AT_LEAST_U32 guarantees that this conversion
+            // is possible
+            SolClientReturnCode::Ok => xml_len.try_into().unwrap(),
+            SolClientReturnCode::NotFound => 0,
+            _ => return Err(MessageError::FieldError("xml", rc)),
+        };
+
+        let user_data_len = self.get_user_data()?.map_or(0, <[u8]>::len);
+
+        Ok(self.payload_len()? + xml_len + user_data_len)
+    }
+
     fn get_application_message_id(&'a self) -> Option<&'a str> {
         let mut buffer = ptr::null();
 
@@ -146,15 +206,28 @@ pub trait Message<'a> {
         c_str.to_str().ok()
     }
 
+    fn get_delivery_mode(&'a self) -> Result<DeliveryMode> {
+        let mut mode: u32 = 0;
+        let rc =
+            unsafe { ffi::solClient_msg_getDeliveryMode(self.get_raw_message_ptr(), &mut mode) };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        rc.ok_or_else(|rc| MessageError::FieldError("delivery_mode", rc))?;
+
+        let Some(mode) = DeliveryMode::from_u32(mode) else {
+            return Err(MessageError::FieldConvertionError("delivery_mode"));
+        };
+
+        Ok(mode)
+    }
+
     fn get_class_of_service(&'a self) -> Result<ClassOfService> {
         let mut cos: u32 = 0;
         let rc =
             unsafe { ffi::solClient_msg_getClassOfService(self.get_raw_message_ptr(), &mut cos) };
 
         let rc = SolClientReturnCode::from_raw(rc);
-        if !rc.is_ok() {
-            return Err(MessageError::FieldError("ClassOfService", rc));
-        }
+        rc.ok_or_else(|rc| MessageError::FieldError("ClassOfService", rc))?;
 
         let Some(cos) = ClassOfService::from_u32(cos) else {
             return Err(MessageError::FieldConvertionError("ClassOfService"));
@@ -185,6 +258,124 @@ pub trait Message<'a> {
         Ok(Some(str))
     }
 
+    /// `JMSType` header. Solace JMS carries it in the application message type field.
+    fn get_jms_type(&'a self) -> Option<&'a str> {
+        self.get_application_msg_type()
+    }
+
+    /// `JMSCorrelationID` header. Solace JMS carries it in the correlation id field.
+    fn get_jms_correlation_id(&'a self) -> Result<Option<&'a str>> {
+        self.get_correlation_id()
+    }
+
+    /// `JMSXGroupID` header, carried as a user property.
+    fn get_group_id(&'a self) -> Result<Option<&'a str>> {
+        self.get_user_property(JMSX_GROUP_ID)
+    }
+
+    fn get_content_type(&'a self) -> Result<Option<&'a str>> {
+        let mut buffer = ptr::null();
+
+        let rc = unsafe {
+            ffi::solClient_msg_getHttpContentType(self.get_raw_message_ptr(), &mut buffer)
+        };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        match rc {
+            SolClientReturnCode::Ok => (),
+            SolClientReturnCode::NotFound => return Ok(None),
+            _ => return Err(MessageError::FieldError("content_type", rc)),
+        }
+
+        let c_str = unsafe { CStr::from_ptr(buffer) };
+
+        let str = c_str
+            .to_str()
+            .map_err(|_| MessageError::FieldConvertionError("content_type"))?;
+
+        Ok(Some(str))
+    }
+
+    fn get_content_encoding(&'a self) -> Result<Option<&'a str>> {
+        let mut buffer = ptr::null();
+
+        let rc = unsafe {
+            ffi::solClient_msg_getHttpContentEncoding(self.get_raw_message_ptr(), &mut buffer)
+        };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        match rc {
+            SolClientReturnCode::Ok => (),
+            SolClientReturnCode::NotFound => return Ok(None),
+            _ => return Err(MessageError::FieldError("content_encoding", rc)),
+        }
+
+        let c_str = unsafe { CStr::from_ptr(buffer) };
+
+        let str = c_str
+            .to_str()
+            .map_err(|_| MessageError::FieldConvertionError("content_encoding"))?;
+
+        Ok(Some(str))
+    }
+
+    /// Looks up a string value in the user property map of the message.
+    fn get_user_property(&'a self, key: &str) -> Result<Option<&'a str>> {
+        let c_key =
+            CString::new(key).map_err(|_| MessageError::FieldConvertionError("user_property"))?;
+
+        let mut map = ptr::null_mut();
+        let rc =
+            unsafe { ffi::solClient_msg_getUserPropertyMap(self.get_raw_message_ptr(), &mut map) };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        match rc {
+            SolClientReturnCode::Ok => (),
+            SolClientReturnCode::NotFound => return Ok(None),
+            _ => return Err(MessageError::FieldError("user_property", rc)),
+        }
+
+        let mut buffer = ptr::null();
+        let rc = unsafe { ffi::solClient_container_getStringPtr(map, &mut buffer, c_key.as_ptr()) };
+
+        // the string points into the message buffer, so it stays valid after the map is closed
+        unsafe { ffi::solClient_container_closeMapStream(&mut map) };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        match rc {
+            SolClientReturnCode::Ok => (),
+            SolClientReturnCode::NotFound => return Ok(None),
+            _ => return Err(MessageError::FieldError("user_property", rc)),
+        }
+
+        let c_str = unsafe { CStr::from_ptr(buffer) };
+
+        let str = c_str
+            .to_str()
+            .map_err(|_| MessageError::FieldConvertionError("user_property"))?;
+
+        Ok(Some(str))
+    }
+
+    /// Reads the whole user property map of the message with the type of every value.
+    fn get_user_properties(&'a self) -> Result<Option<SdtMap>> {
+        let mut map = ptr::null_mut();
+        let rc =
+            unsafe { ffi::solClient_msg_getUserPropertyMap(self.get_raw_message_ptr(), &mut map) };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        match rc {
+            SolClientReturnCode::Ok => (),
+            SolClientReturnCode::NotFound => return Ok(None),
+            _ => return Err(MessageError::FieldError("user_property", rc)),
+        }
+
+        let properties = unsafe { sdt::read_map(map) };
+        unsafe { ffi::solClient_container_closeMapStream(&mut map) };
+
+        properties.map(Some)
+    }
+
     fn is_eliding_eligible(&'a self) -> bool {
         let unsafe_result =
             unsafe { ffi::solClient_msg_isElidingEligible(self.get_raw_message_ptr()) };
@@ -192,6 +383,34 @@ pub trait Message<'a> {
         unsafe_result != 0
     }
 
+    fn is_ack_immediately(&'a self) -> bool {
+        let unsafe_result =
+            unsafe { ffi::solClient_msg_isAckImmediately(self.get_raw_message_ptr()) };
+
+        unsafe_result != 0
+    }
+
+    fn is_dmq_eligible(&'a self) -> bool {
+        let unsafe_result = unsafe { ffi::solClient_msg_isDMQEligible(self.get_raw_message_ptr()) };
+
+        unsafe_result != 0
+    }
+
+    /// `None` if the message does not expire.
+    fn get_time_to_live(&'a self) -> Result<Option<Duration>> {
+        let mut ttl: i64 = 0;
+        let rc = unsafe { ffi::solClient_msg_getTimeToLive(self.get_raw_message_ptr(), &mut ttl) };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        rc.ok_or_else(|rc| MessageError::FieldError("time_to_live", rc))?;
+
+        if ttl <= 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(Duration::from_millis(ttl as u64)))
+    }
+
     fn get_expiration(&'a self) -> i64 {
         let mut exp: i64 = 0;
         unsafe { ffi::solClient_msg_getExpiration(self.get_raw_message_ptr(), &mut exp) };
@@ -205,9 +424,7 @@ pub trait Message<'a> {
             unsafe { ffi::solClient_msg_getPriority(self.get_raw_message_ptr(), &mut priority) };
 
         let rc = SolClientReturnCode::from_raw(rc);
-        if !rc.is_ok() {
-            return Err(MessageError::FieldError("priority", rc));
-        }
+        rc.ok_or_else(|rc| MessageError::FieldError("priority", rc))?;
 
         if priority == -1 {
             return Ok(None);
@@ -281,7 +498,19 @@ pub trait Message<'a> {
         res != 0
     }
 
+    /// Sender timestamp set by the publisher. `chrono::DateTime<Utc>` and `time::OffsetDateTime`
+    /// both implement `From<SystemTime>`, so it converts with `.into()` without a feature of this
+    /// crate.
     fn get_sender_timestamp(&'a self) -> Result<Option<SystemTime>> {
+        Ok(self
+            .get_sender_timestamp_millis()?
+            .map(millis_to_system_time))
+    }
+
+    /// Sender timestamp in milliseconds since the UNIX epoch, the precision provided by the C
+    /// library. Convenient for converting into other time types without going through
+    /// `SystemTime`.
+    fn get_sender_timestamp_millis(&'a self) -> Result<Option<i64>> {
         let mut ts: i64 = 0;
         let rc =
             unsafe { ffi::solClient_msg_getSenderTimestamp(self.get_raw_message_ptr(), &mut ts) };
@@ -290,9 +519,7 @@ pub trait Message<'a> {
 
         match rc {
             SolClientReturnCode::NotFound => Ok(None),
-            SolClientReturnCode::Ok => Ok(Some(
-                SystemTime::UNIX_EPOCH + Duration::from_millis(ts.try_into().unwrap()),
-            )),
+            SolClientReturnCode::Ok => Ok(Some(ts)),
             _ => Err(MessageError::FieldError("sender_timestamp", rc)),
         }
     }
@@ -325,3 +552,133 @@ pub trait Message<'a> {
         Ok(Some(safe_slice))
     }
 }
+
+/// Setters for messages owned by the application, such as an [`OutboundMessage`] or a received
+/// message turned into one with [`InboundMessage::into_outbound`]. The values are copied into the
+/// message.
+pub trait MessageWrite: for<'a> MessageRead<'a> {
+    fn set_payload(&mut self, payload: &[u8]) -> Result<()> {
+        let rc = unsafe {
+            ffi::solClient_msg_setBinaryAttachment(
+                self.get_raw_message_ptr(),
+                payload.as_ptr() as *const c_void,
+                payload.len() as u32,
+            )
+        };
+        check_set("payload", rc)
+    }
+
+    fn set_destination(&mut self, destination: &MessageDestination) -> Result<()> {
+        let mut destination = ffi::solClient_destination {
+            destType: destination.dest_type.to_i32(),
+            dest: destination.dest.as_ptr(),
+        };
+        let rc = unsafe {
+            ffi::solClient_msg_setDestination(
+                self.get_raw_message_ptr(),
+                &mut destination,
+                size_of::<ffi::solClient_destination>(),
+            )
+        };
+        check_set("destination", rc)
+    }
+
+    fn set_reply_to(&mut self, reply_to: &MessageDestination) -> Result<()> {
+        let reply_to = ffi::solClient_destination {
+            destType: reply_to.dest_type.to_i32(),
+            dest: reply_to.dest.as_ptr(),
+        };
+        let rc = unsafe {
+            ffi::solClient_msg_setReplyTo(
+                self.get_raw_message_ptr(),
+                &reply_to,
+                size_of::<ffi::solClient_destination>(),
+            )
+        };
+        check_set("reply_to", rc)
+    }
+
+    fn set_delivery_mode(&mut self, mode: DeliveryMode) -> Result<()> {
+        let rc =
+            unsafe { ffi::solClient_msg_setDeliveryMode(self.get_raw_message_ptr(), mode as u32) };
+        check_set("delivery_mode", rc)
+    }
+
+    fn set_class_of_service(&mut self, cos: ClassOfService) -> Result<()> {
+        let rc =
+            unsafe { ffi::solClient_msg_setClassOfService(self.get_raw_message_ptr(), cos.into()) };
+        check_set("class_of_service", rc)
+    }
+
+    fn set_correlation_id(&mut self, id: &str) -> Result<()> {
+        let id = to_c_string("correlation_id", id)?;
+        let rc =
+            unsafe { ffi::solClient_msg_setCorrelationId(self.get_raw_message_ptr(), id.as_ptr()) };
+        check_set("correlation_id", rc)
+    }
+
+    fn set_application_message_id(&mut self, id: &str) -> Result<()> {
+        let id = to_c_string("application_message_id", id)?;
+        let rc = unsafe {
+            ffi::solClient_msg_setApplicationMessageId(self.get_raw_message_ptr(), id.as_ptr())
+        };
+        check_set("application_message_id", rc)
+    }
+
+    fn set_content_type(&mut self, content_type: &str) -> Result<()> {
+        let content_type = to_c_string("content_type", content_type)?;
+        let rc = unsafe {
+            ffi::solClient_msg_setHttpContentType(self.get_raw_message_ptr(), content_type.as_ptr())
+        };
+        check_set("content_type", rc)
+    }
+
+    fn set_content_encoding(&mut self, content_encoding: &str) -> Result<()> {
+        let content_encoding = to_c_string("content_encoding", content_encoding)?;
+        let rc = unsafe {
+            ffi::solClient_msg_setHttpContentEncoding(
+                self.get_raw_message_ptr(),
+                content_encoding.as_ptr(),
+            )
+        };
+        check_set("content_encoding", rc)
+    }
+
+    fn set_priority(&mut self, priority: u8) -> Result<()> {
+        let rc =
+            unsafe { ffi::solClient_msg_setPriority(self.get_raw_message_ptr(), priority.into()) };
+        check_set("priority", rc)
+    }
+
+    /// A zero time to live removes the expiry.
+    fn set_time_to_live(&mut self, ttl: Duration) -> Result<()> {
+        let ttl_ms = i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX);
+        let rc = unsafe { ffi::solClient_msg_setTimeToLive(self.get_raw_message_ptr(), ttl_ms) };
+        check_set("time_to_live", rc)
+    }
+
+    fn set_dmq_eligible(&mut self, dmq_eligible: bool) -> Result<()> {
+        let rc = unsafe {
+            ffi::solClient_msg_setDMQEligible(self.get_raw_message_ptr(), dmq_eligible.into())
+        };
+        check_set("dmq_eligible", rc)
+    }
+
+    fn set_eliding_eligible(&mut self, eliding_eligible: bool) -> Result<()> {
+        let rc = unsafe {
+            ffi::solClient_msg_setElidingEligible(
+                self.get_raw_message_ptr(),
+                eliding_eligible.into(),
+            )
+        };
+        check_set("eliding_eligible", rc)
+    }
+}
+
+fn to_c_string(field: &'static str, value: &str) -> Result<CString> {
+    CString::new(value).map_err(|e| MessageError::InvalidArgs(field, e))
+}
+
+fn check_set(field: &'static str, rc: ffi::solClient_returnCode_t) -> Result<()> {
+    SolClientReturnCode::from_raw(rc).ok_or_else(|rc| MessageError::FieldError(field, rc))
+}
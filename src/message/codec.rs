@@ -0,0 +1,99 @@
+use std::error::Error;
+use std::ffi::{c_void, CString};
+
+use solace_rs_sys as ffi;
+use thiserror::Error;
+
+use super::{MessageError, MessageRead};
+use crate::SolClientReturnCode;
+
+pub type BoxError = Box<dyn Error + Send + Sync>;
+
+#[derive(Error, Debug)]
+pub enum CodecError {
+    #[error("failed to encode payload with {0}")]
+    Encode(String, #[source] BoxError),
+    #[error("failed to decode payload with {0}")]
+    Decode(String, #[source] BoxError),
+    #[error("failed to read message")]
+    Message(#[from] MessageError),
+    #[error("failed to set {0}. SolClient return code: {1}")]
+    FieldError(&'static str, SolClientReturnCode),
+}
+
+type Result<T> = std::result::Result<T, CodecError>;
+
+/// Application level payload transformation, such as compression, set with
+/// [`SessionBuilder::codec`](crate::session::SessionBuilder::codec).
+///
+/// Published payloads are encoded and marked with [`PayloadCodec::encoding`] as their content
+/// encoding. Received messages with that content encoding are decoded before they are passed to
+/// the `on_message` callback. Messages from publishers without the codec are passed as is.
+pub trait PayloadCodec: Send + Sync {
+    /// Content encoding identifying the codec, for example `zstd`.
+    fn encoding(&self) -> &str;
+
+    fn encode(&self, payload: &[u8]) -> std::result::Result<Vec<u8>, BoxError>;
+
+    fn decode(&self, payload: &[u8]) -> std::result::Result<Vec<u8>, BoxError>;
+}
+
+/// Encodes the payload of the message in place. Messages that already have a content encoding
+/// are left untouched, which also keeps a message published twice from being encoded twice.
+pub(crate) fn encode<'a, M: MessageRead<'a>>(
+    codec: &dyn PayloadCodec,
+    message: &'a M,
+) -> Result<()> {
+    if message.get_content_encoding()?.is_some() {
+        return Ok(());
+    }
+
+    let payload = message.get_payload()?.unwrap_or_default();
+    let encoded = codec
+        .encode(payload)
+        .map_err(|e| CodecError::Encode(codec.encoding().to_owned(), e))?;
+
+    // an encoding with a nul byte can never be matched by the receiving side
+    let encoding = CString::new(codec.encoding())
+        .map_err(|e| CodecError::Encode(codec.encoding().to_owned(), Box::new(e) as BoxError))?;
+
+    unsafe { set_payload(message.get_raw_message_ptr(), &encoded)? };
+    let rc = unsafe {
+        ffi::solClient_msg_setHttpContentEncoding(message.get_raw_message_ptr(), encoding.as_ptr())
+    };
+    SolClientReturnCode::from_raw(rc)
+        .ok_or_else(|rc| CodecError::FieldError("content_encoding", rc))
+}
+
+/// Decodes the payload of the message in place if it was encoded with the codec, and removes the
+/// content encoding.
+pub(crate) fn decode<'a, M: MessageRead<'a>>(
+    codec: &dyn PayloadCodec,
+    message: &'a M,
+) -> Result<()> {
+    if message.get_content_encoding()? != Some(codec.encoding()) {
+        return Ok(());
+    }
+
+    let payload = message.get_payload()?.unwrap_or_default();
+    let decoded = codec
+        .decode(payload)
+        .map_err(|e| CodecError::Decode(codec.encoding().to_owned(), e))?;
+
+    unsafe { set_payload(message.get_raw_message_ptr(), &decoded)? };
+    let rc = unsafe { ffi::solClient_msg_deleteHttpContentEncoding(message.get_raw_message_ptr()) };
+    SolClientReturnCode::from_raw(rc)
+        .ok_or_else(|rc| CodecError::FieldError("content_encoding", rc))
+}
+
+/// # Safety
+/// `msg_ptr` must be a valid message. The payload is copied, so no slice into the previous
+/// payload may be used afterwards.
+unsafe fn set_payload(msg_ptr: ffi::solClient_opaqueMsg_pt, payload: &[u8]) -> Result<()> {
+    let rc = ffi::solClient_msg_setBinaryAttachment(
+        msg_ptr,
+        payload.as_ptr() as *const c_void,
+        payload.len() as u32,
+    );
+    SolClientReturnCode::from_raw(rc).ok_or_else(|rc| CodecError::FieldError("payload", rc))
+}
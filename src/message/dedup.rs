@@ -0,0 +1,135 @@
+use std::collections::{HashMap, VecDeque};
+use std::num::NonZeroUsize;
+
+use super::{InboundMessage, MessageRead, Result};
+
+/// Identity of a message used by [`DedupWindow`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DedupKey {
+    /// Replication group message id, set by the broker on guaranteed messages.
+    ReplicationGroupMessageId(String),
+    /// Sender id and sequence number, set by the publisher.
+    Sequence {
+        sender_id: String,
+        sequence_number: i64,
+    },
+}
+
+impl DedupKey {
+    /// Prefers the replication group message id, which survives broker failover, and falls back
+    /// to the sender id and sequence number. `None` if the message carries neither.
+    pub fn from_message(message: &InboundMessage) -> Result<Option<Self>> {
+        if let Some(rgmid) = message.get_replication_group_message_id()? {
+            return Ok(Some(Self::ReplicationGroupMessageId(rgmid)));
+        }
+
+        let (Some(sender_id), Some(sequence_number)) =
+            (message.get_sender_id()?, message.get_sequence_number()?)
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self::Sequence {
+            sender_id: sender_id.to_owned(),
+            sequence_number,
+        }))
+    }
+}
+
+/// Remembers the most recently seen messages to drop duplicates, for example messages
+/// redelivered after a reconnect that were already processed.
+///
+/// Once the window is full, the least recently seen key is forgotten.
+#[derive(Debug)]
+pub struct DedupWindow {
+    capacity: usize,
+    // the generation of the last time a key was seen, older entries in `order` are stale
+    seen: HashMap<DedupKey, u64>,
+    order: VecDeque<(DedupKey, u64)>,
+    generation: u64,
+}
+
+impl DedupWindow {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            capacity: capacity.get(),
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            generation: 0,
+        }
+    }
+
+    /// Records the key and returns whether it was already in the window.
+    pub fn check(&mut self, key: DedupKey) -> bool {
+        self.generation += 1;
+        let duplicate = self.seen.insert(key.clone(), self.generation).is_some();
+        self.order.push_back((key, self.generation));
+
+        while self.seen.len() > self.capacity {
+            let Some((key, generation)) = self.order.pop_front() else {
+                break;
+            };
+            if self.seen.get(&key) == Some(&generation) {
+                self.seen.remove(&key);
+            }
+        }
+        // drops stale entries so that keys seen repeatedly do not grow the queue
+        if self.order.len() > self.capacity * 2 {
+            let seen = &self.seen;
+            self.order
+                .retain(|(key, generation)| seen.get(key) == Some(generation));
+        }
+
+        duplicate
+    }
+
+    /// Records the message and returns whether it is a duplicate. Messages without a
+    /// [`DedupKey`] are never duplicates.
+    pub fn is_duplicate(&mut self, message: &InboundMessage) -> Result<bool> {
+        Ok(DedupKey::from_message(message)?.is_some_and(|key| self.check(key)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(sequence_number: i64) -> DedupKey {
+        DedupKey::Sequence {
+            sender_id: "sender".to_owned(),
+            sequence_number,
+        }
+    }
+
+    #[test]
+    fn it_should_detect_duplicates_within_the_window() {
+        let mut window = DedupWindow::new(NonZeroUsize::new(2).unwrap());
+
+        assert!(!window.check(key(1)));
+        assert!(!window.check(key(2)));
+        assert!(window.check(key(1)));
+
+        // 2 is the least recently seen
+        assert!(!window.check(key(3)));
+        assert!(window.check(key(1)));
+        assert!(!window.check(key(2)));
+        assert_eq!(2, window.len());
+    }
+
+    #[test]
+    fn it_should_not_grow_with_repeated_keys() {
+        let mut window = DedupWindow::new(NonZeroUsize::new(2).unwrap());
+        for _ in 0..100 {
+            window.check(key(1));
+        }
+        assert!(window.order.len() <= 4);
+    }
+}
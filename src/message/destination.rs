@@ -5,7 +5,7 @@ use std::convert::From;
 use std::ffi::{CStr, CString};
 
 enum_from_primitive! {
-    #[derive(Debug, PartialEq, Eq, Default)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
     #[repr(i32)]
     pub enum DestinationType {
         #[default]
@@ -18,10 +18,23 @@ enum_from_primitive! {
 }
 
 impl DestinationType {
+    pub fn is_topic(&self) -> bool {
+        matches!(self, Self::Topic | Self::TopicTemp)
+    }
+
+    pub fn is_queue(&self) -> bool {
+        matches!(self, Self::Queue | Self::QueueTemp)
+    }
+
+    /// Temporary topics and queues only live as long as the session or flow that created them.
+    pub fn is_temporary(&self) -> bool {
+        matches!(self, Self::TopicTemp | Self::QueueTemp)
+    }
+
     pub fn to_i32(&self) -> i32 {
         match self {
             Self::Null => ffi::solClient_destinationType_SOLCLIENT_NULL_DESTINATION,
-            Self::Topic => ffi::solClient_destinationType_SOLCLIENT_TOPIC_TEMP_DESTINATION,
+            Self::Topic => ffi::solClient_destinationType_SOLCLIENT_TOPIC_DESTINATION,
             Self::Queue => ffi::solClient_destinationType_SOLCLIENT_QUEUE_DESTINATION,
             Self::TopicTemp => ffi::solClient_destinationType_SOLCLIENT_TOPIC_TEMP_DESTINATION,
             Self::QueueTemp => ffi::solClient_destinationType_SOLCLIENT_QUEUE_TEMP_DESTINATION,
@@ -34,7 +47,7 @@ impl DestinationType {
 // then pass that as value to the message builder
 // but then also be able to get a MessageDestination from a message.
 // Right now, it seems the best way to do that is with by copying the meessage destination field.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MessageDestination {
     pub dest_type: DestinationType,
     pub dest: CString,
@@ -62,3 +75,40 @@ impl From<ffi::solClient_destination> for MessageDestination {
         MessageDestination { dest_type, dest }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESTINATION_TYPES: [DestinationType; 5] = [
+        DestinationType::Null,
+        DestinationType::Topic,
+        DestinationType::Queue,
+        DestinationType::TopicTemp,
+        DestinationType::QueueTemp,
+    ];
+
+    #[test]
+    fn it_should_convert_destination_types_to_their_own_value() {
+        for dest_type in DESTINATION_TYPES {
+            assert_eq!(dest_type as i32, dest_type.to_i32());
+            assert_eq!(
+                Some(dest_type),
+                DestinationType::from_i32(dest_type.to_i32())
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_round_trip_raw_destinations() {
+        for dest_type in DESTINATION_TYPES {
+            let destination = MessageDestination::new(dest_type, "a/b").unwrap();
+            let raw = ffi::solClient_destination {
+                destType: destination.dest_type.to_i32(),
+                dest: destination.dest.as_ptr(),
+            };
+
+            assert_eq!(destination, MessageDestination::from(raw));
+        }
+    }
+}
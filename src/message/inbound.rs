@@ -1,13 +1,36 @@
-use super::{CacheStatus, Message, MessageError, Result};
-use crate::SolClientReturnCode;
+use super::{
+    millis_to_system_time, CacheStatus, MessageError, MessageRead, OutboundMessage,
+    OutboundMessageBuilder, OwnedMessage, Result,
+};
+use crate::session::SessionEvent;
+use crate::{Session, SolClientReturnCode};
 use enum_primitive::*;
 use solace_rs_sys as ffi;
 use std::convert::From;
 use std::ffi::CStr;
-use std::time::{Duration, SystemTime};
-use std::{fmt, ptr};
+use std::ops::Deref;
+use std::time::SystemTime;
+use std::{fmt, mem, ptr};
 use tracing::warn;
 
+/// Cache related information of an inbound message, used to tell apart messages answering a
+/// cache request from live data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheInfo {
+    pub status: CacheStatus,
+    /// Id of the cache request the message answers. Only set for cached messages.
+    pub request_id: Option<u64>,
+}
+
+impl CacheInfo {
+    pub fn is_cached(&self) -> bool {
+        matches!(
+            self.status,
+            CacheStatus::CacheMessage | CacheStatus::SuspectMessage
+        )
+    }
+}
+
 pub struct InboundMessage {
     _msg_ptr: ffi::solClient_opaqueMsg_pt,
 }
@@ -109,6 +132,38 @@ impl Drop for InboundMessage {
     }
 }
 
+/// Payload of an [`InboundMessage`] that owns the message, so it can be handed off without
+/// borrowing from it. It implements `AsRef<[u8]>`, for example to wrap it with
+/// `bytes::Bytes::from_owner` without a copy.
+pub struct Payload {
+    _message: InboundMessage,
+    // points into the message, stays valid until the message is freed
+    ptr: *const u8,
+    len: usize,
+}
+
+unsafe impl Send for Payload {}
+
+impl Deref for Payload {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl AsRef<[u8]> for Payload {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl fmt::Debug for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Payload").field("len", &self.len).finish()
+    }
+}
+
 impl From<ffi::solClient_opaqueMsg_pt> for InboundMessage {
     /// .
     ///
@@ -124,23 +179,117 @@ impl From<ffi::solClient_opaqueMsg_pt> for InboundMessage {
     }
 }
 
-impl<'a> Message<'a> for InboundMessage {
+impl<'a> MessageRead<'a> for InboundMessage {
     unsafe fn get_raw_message_ptr(&self) -> ffi::solClient_opaqueMsg_pt {
         self._msg_ptr
     }
 }
 
 impl InboundMessage {
+    /// Duplicates the message. The payload is shared by the C library, so this is cheap.
+    pub fn try_clone(&self) -> Result<Self> {
+        let mut dup_ptr: ffi::solClient_opaqueMsg_pt = ptr::null_mut();
+        let rc = unsafe { ffi::solClient_msg_dup(self.get_raw_message_ptr(), &mut dup_ptr) };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        rc.ok_or_else(MessageError::DuplicationError)?;
+
+        Ok(Self::from(dup_ptr))
+    }
+
+    /// Turns the message into its payload without copying it. The message is kept alive, and
+    /// freed, by the returned [`Payload`]. A message without a payload gives an empty one.
+    pub fn into_payload(self) -> Result<Payload> {
+        let payload = self.get_payload()?.unwrap_or_default();
+        let (ptr, len) = (payload.as_ptr(), payload.len());
+
+        Ok(Payload {
+            _message: self,
+            ptr,
+            len,
+        })
+    }
+
+    /// Turns the message into an [`OutboundMessage`] without copying it, so that it can be
+    /// modified with [`MessageWrite`](super::MessageWrite) and published again. Use
+    /// [`InboundMessage::try_clone`] first to keep the received message.
+    pub fn into_outbound(self) -> OutboundMessage {
+        let msg_ptr = self._msg_ptr;
+        // the outbound message takes over freeing the message
+        mem::forget(self);
+        unsafe { OutboundMessage::from_raw(msg_ptr) }
+    }
+
+    /// Whether the message was sent to the P2P inbox of the session, usually a request or a reply
+    /// addressed to this session only rather than to a topic subscription.
+    pub fn is_addressed_to_inbox<M, E>(&self, session: &Session<'_, M, E>) -> bool
+    where
+        M: FnMut(InboundMessage) + Send,
+        E: FnMut(SessionEvent) + Send,
+    {
+        let (Ok(Some(destination)), Ok(inbox)) =
+            (self.get_destination(), session.inbox_destination())
+        else {
+            return false;
+        };
+        destination.dest_type.is_topic()
+            && in_inbox(
+                &inbox.dest.to_string_lossy(),
+                &destination.dest.to_string_lossy(),
+            )
+    }
+
+    /// Whether the message was sent to a P2P inbox, of this or another session. Unlike
+    /// [`InboundMessage::is_addressed_to_inbox`] the inbox is not queried from the session.
+    pub fn is_p2p(&self) -> bool {
+        self.get_destination().is_ok_and(|destination| {
+            destination.is_some_and(|d| {
+                d.dest_type.is_topic() && d.dest.as_bytes().starts_with(P2P_PREFIX)
+            })
+        })
+    }
+
+    /// Builder for a reply to the message, sent to its reply-to with its correlation id and
+    /// delivery mode. `None` if the message has no reply-to.
+    pub fn reply_builder(&self) -> Result<Option<OutboundMessageBuilder>> {
+        let Some(reply_to) = self.get_reply_to()? else {
+            return Ok(None);
+        };
+
+        let mut builder = OutboundMessageBuilder::new()
+            .destination(reply_to)
+            .delivery_mode(self.get_delivery_mode()?)
+            .is_reply(true);
+        if let Some(correlation_id) = self.get_correlation_id()? {
+            builder = builder.correlation_id(correlation_id);
+        }
+        Ok(Some(builder))
+    }
+
+    /// Copies the payload and metadata into an [`OwnedMessage`] and frees the C message.
+    pub fn into_owned_parts(self) -> Result<OwnedMessage> {
+        OwnedMessage::try_from(&self)
+    }
+
+    /// Time the message was received by the C library, with `generate_rcv_timestamps`. Converts
+    /// into `chrono` and `time` types with `.into()`, like
+    /// [`MessageRead::get_sender_timestamp`].
     pub fn get_receive_timestamp(&self) -> Result<Option<SystemTime>> {
+        Ok(self
+            .get_receive_timestamp_millis()?
+            .map(millis_to_system_time))
+    }
+
+    /// Receive timestamp in milliseconds since the UNIX epoch, see
+    /// [`MessageRead::get_sender_timestamp_millis`].
+    pub fn get_receive_timestamp_millis(&self) -> Result<Option<i64>> {
         let mut ts: i64 = 0;
         let rc = unsafe { ffi::solClient_msg_getRcvTimestamp(self.get_raw_message_ptr(), &mut ts) };
 
         let rc = SolClientReturnCode::from_raw(rc);
         match rc {
             SolClientReturnCode::NotFound => Ok(None),
-            SolClientReturnCode::Ok => Ok(Some(
-                SystemTime::UNIX_EPOCH + Duration::from_millis(ts.try_into().unwrap()),
-            )),
+            SolClientReturnCode::Ok => Ok(Some(ts)),
             _ => Err(MessageError::FieldError("receive_timestamp", rc)),
         }
     }
@@ -166,6 +315,51 @@ impl InboundMessage {
         Ok(Some(str))
     }
 
+    /// Whether the broker delivered the guaranteed message before, for example to a consumer that
+    /// disconnected before acknowledging it. See [`DedupWindow`](super::DedupWindow) to drop
+    /// duplicates.
+    pub fn is_redelivered(&self) -> bool {
+        unsafe { ffi::solClient_msg_isRedelivered(self.get_raw_message_ptr()) != 0 }
+    }
+
+    /// Replication group message id of a guaranteed message, unique across a replication group
+    /// and kept when the message is redelivered.
+    pub fn get_replication_group_message_id(&self) -> Result<Option<String>> {
+        let mut rgmid: ffi::solClient_replicationGroupMessageId = unsafe { mem::zeroed() };
+        let rc = unsafe {
+            ffi::solClient_msg_getReplicationGroupMessageId(
+                self.get_raw_message_ptr(),
+                &mut rgmid,
+                mem::size_of::<ffi::solClient_replicationGroupMessageId>(),
+            )
+        };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        match rc {
+            SolClientReturnCode::Ok => (),
+            SolClientReturnCode::NotFound => return Ok(None),
+            _ => return Err(MessageError::FieldError("replication_group_message_id", rc)),
+        }
+
+        let mut buffer = [0u8; ffi::SOLCLIENT_REPLICATION_GROUP_MESSAGE_ID_STRING_LENGTH as usize];
+        let rc = unsafe {
+            ffi::solClient_replicationGroupMessageId_toString(
+                &mut rgmid,
+                mem::size_of::<ffi::solClient_replicationGroupMessageId>(),
+                buffer.as_mut_ptr().cast(),
+                buffer.len(),
+            )
+        };
+        SolClientReturnCode::from_raw(rc)
+            .ok_or_else(|rc| MessageError::FieldError("replication_group_message_id", rc))?;
+
+        let c_str = CStr::from_bytes_until_nul(&buffer)
+            .map_err(|_| MessageError::FieldConvertionError("replication_group_message_id"))?;
+        Ok(Some(c_str.to_string_lossy().into_owned()))
+    }
+
+    /// Whether messages were discarded before this one, for example because the subscriber fell
+    /// behind. Messages elided by the broker are dropped on purpose and do not set it.
     pub fn is_discard_indication(&self) -> bool {
         let discard_indication =
             unsafe { ffi::solClient_msg_isDiscardIndication(self.get_raw_message_ptr()) };
@@ -195,4 +389,48 @@ impl InboundMessage {
         let raw = unsafe { ffi::solClient_msg_isCacheMsg(self.get_raw_message_ptr()) };
         CacheStatus::from_i32(raw).unwrap_or(CacheStatus::InvalidMessage)
     }
+
+    pub fn cache_info(&self) -> Result<CacheInfo> {
+        let status = self.is_cache_msg();
+        let request_id = match status {
+            CacheStatus::CacheMessage | CacheStatus::SuspectMessage => {
+                self.get_cache_request_id()?
+            }
+            _ => None,
+        };
+
+        Ok(CacheInfo { status, request_id })
+    }
+}
+
+// topics of the P2P inboxes all start with the prefix
+const P2P_PREFIX: &[u8] = b"#P2P/";
+
+/// Matches a topic against the P2P inbox of a session, which may end with a wildcard covering the
+/// topics of the requests it receives.
+fn in_inbox(inbox: &str, topic: &str) -> bool {
+    match inbox.strip_suffix('>').or_else(|| inbox.strip_suffix('#')) {
+        Some(prefix) => topic.starts_with(prefix),
+        None => {
+            topic == inbox
+                || topic
+                    .strip_prefix(inbox)
+                    .is_some_and(|t| t.starts_with('/'))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_match_inbox_topics() {
+        assert!(in_inbox("#P2P/v:r/c/#", "#P2P/v:r/c/_request"));
+        assert!(in_inbox("#P2P/v:r/c/>", "#P2P/v:r/c/a/b"));
+        assert!(in_inbox("#P2P/v:r/c", "#P2P/v:r/c"));
+        assert!(in_inbox("#P2P/v:r/c", "#P2P/v:r/c/a"));
+        assert!(!in_inbox("#P2P/v:r/c", "#P2P/v:r/c2"));
+        assert!(!in_inbox("#P2P/v:r/c/#", "#P2P/v:r/other/a"));
+    }
 }
@@ -0,0 +1,90 @@
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::{MessageError, MessageRead, Result};
+
+// wall clock time read once, later stamps only advance with the monotonic clock
+static ANCHOR: OnceLock<(Instant, u64)> = OnceLock::new();
+
+/// Send timestamp for latency measurements, carried in the
+/// [`LatencyStamp::USER_PROPERTY`] user property, see
+/// [`OutboundMessageBuilder::latency_stamp`](super::OutboundMessageBuilder::latency_stamp).
+///
+/// Stamps are nanoseconds since the UNIX epoch, taken from the wall clock once per process and
+/// advanced with the monotonic clock after that, so that NTP adjustments do not skew the measured
+/// latencies. Stamps of different hosts, or of processes started around a clock adjustment, are
+/// only as comparable as their wall clocks were when the processes took their first stamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LatencyStamp {
+    nanos: u64,
+}
+
+impl LatencyStamp {
+    pub const USER_PROPERTY: &'static str = "solace_rs_latency_stamp_ns";
+
+    pub fn now() -> Self {
+        let (instant, wall_nanos) = ANCHOR.get_or_init(|| {
+            let wall = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            (Instant::now(), wall.as_nanos() as u64)
+        });
+        Self {
+            nanos: wall_nanos + instant.elapsed().as_nanos() as u64,
+        }
+    }
+
+    pub fn from_nanos(nanos: u64) -> Self {
+        Self { nanos }
+    }
+
+    pub fn as_nanos(&self) -> u64 {
+        self.nanos
+    }
+
+    /// Reads the stamp of a received message. `None` if the message was not stamped.
+    pub fn from_message<'a, M: MessageRead<'a>>(message: &'a M) -> Result<Option<Self>> {
+        let Some(value) = message.get_user_property(Self::USER_PROPERTY)? else {
+            return Ok(None);
+        };
+        let nanos = value
+            .parse()
+            .map_err(|_| MessageError::FieldConvertionError("latency_stamp"))?;
+        Ok(Some(Self { nanos }))
+    }
+
+    /// Time from the stamp to now, zero if the stamp is in the future.
+    pub fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self)
+    }
+
+    /// Time from an earlier stamp to this one, zero if `earlier` is later.
+    pub fn duration_since(&self, earlier: LatencyStamp) -> Duration {
+        Duration::from_nanos(self.nanos.saturating_sub(earlier.nanos))
+    }
+}
+
+impl fmt::Display for LatencyStamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.nanos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_advance_monotonically() {
+        let first = LatencyStamp::now();
+        let second = LatencyStamp::now();
+
+        assert!(second >= first);
+        assert_eq!(Duration::ZERO, first.duration_since(second));
+        assert_eq!(
+            Duration::from_nanos(5),
+            LatencyStamp::from_nanos(15).duration_since(LatencyStamp::from_nanos(10))
+        );
+    }
+}
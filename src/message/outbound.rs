@@ -1,17 +1,23 @@
 use super::destination::MessageDestination;
-use super::{ClassOfService, DeliveryMode, Message};
-use crate::SolClientReturnCode;
+use super::sdt::{self, SdtMap, SdtValue};
+use super::{
+    ClassOfService, DeliveryMode, LatencyStamp, MessageError, MessageRead, MessageWrite,
+    JMSX_GROUP_ID,
+};
+use crate::message::InboundMessage;
+use crate::session::SessionEvent;
+use crate::{Session, SolClientReturnCode};
 use solace_rs_sys as ffi;
 use std::ffi::{c_void, CString, NulError};
 use std::ptr;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use thiserror::Error;
 use tracing::warn;
 
 #[derive(Error, Debug)]
 pub enum MessageBuilderError {
-    #[error("builder recieved invalid args")]
-    InvalidArgs(#[from] NulError),
+    #[error("builder recieved invalid args. {0} contains interior nul byte")]
+    InvalidArgs(String, #[source] NulError),
     #[error("{0} arg need to be set")]
     MissingRequiredArgs(String),
     #[error("{0} size need to be less than {1} found {2}")]
@@ -20,10 +26,36 @@ pub enum MessageBuilderError {
     TimestampError,
     #[error("solClient message aloc failed")]
     MessageAlocFailure,
+    #[error("session p2p inbox is not available")]
+    InboxUnavailable,
+    #[error("failed to set user property {0}. SolClient return code: {1}")]
+    UserPropertyError(String, SolClientReturnCode),
+    #[error("{0} can not be used with {1:?} messages")]
+    IncompatibleArgs(String, DeliveryMode),
+    #[error("failed to set field")]
+    Message(#[from] MessageError),
 }
 
 type Result<T> = std::result::Result<T, MessageBuilderError>;
 
+fn to_c_string<T: Into<Vec<u8>>>(field: &str, value: T) -> Result<CString> {
+    CString::new(value).map_err(|e| MessageBuilderError::InvalidArgs(field.to_owned(), e))
+}
+
+enum UserPropertyValue {
+    String(Vec<u8>),
+    Typed(SdtValue),
+}
+
+impl UserPropertyValue {
+    fn size_hint(&self) -> usize {
+        match self {
+            Self::String(value) => value.len(),
+            Self::Typed(value) => value.size_hint(),
+        }
+    }
+}
+
 pub struct OutboundMessage {
     _msg_ptr: ffi::solClient_opaqueMsg_pt,
 }
@@ -43,16 +75,100 @@ impl Drop for OutboundMessage {
     }
 }
 
-impl<'a> Message<'a> for OutboundMessage {
+impl<'a> MessageRead<'a> for OutboundMessage {
     unsafe fn get_raw_message_ptr(&self) -> ffi::solClient_opaqueMsg_pt {
         self._msg_ptr
     }
 }
 
+impl MessageWrite for OutboundMessage {}
+
+impl OutboundMessage {
+    /// Takes ownership of a message built outside of this crate, for example with other
+    /// bindings to the C library or its structured data (SDT) container functions.
+    ///
+    /// # Safety
+    /// `msg_ptr` must be a valid message allocated with `solClient_msg_alloc` and owned by the
+    /// caller, with no other alias used afterwards. It is freed when the returned message is
+    /// dropped.
+    pub unsafe fn from_raw(msg_ptr: ffi::solClient_opaqueMsg_pt) -> Self {
+        Self { _msg_ptr: msg_ptr }
+    }
+
+    /// Gives up ownership of the message, which the caller has to free with `solClient_msg_free`.
+    pub fn into_raw(self) -> ffi::solClient_opaqueMsg_pt {
+        let msg_ptr = self._msg_ptr;
+        std::mem::forget(self);
+        msg_ptr
+    }
+
+    /// Allocates a message with only a destination, delivery mode and payload, without the
+    /// validation of [`OutboundMessageBuilder`]. Other fields can be set with [`MessageWrite`], or
+    /// through [`MessageRead::get_raw_message_ptr`] with functions this crate does not wrap.
+    pub fn from_parts(
+        destination: &MessageDestination,
+        delivery_mode: DeliveryMode,
+        payload: &[u8],
+    ) -> Result<Self> {
+        let mut msg_ptr: ffi::solClient_opaqueMsg_pt = ptr::null_mut();
+        let rc = unsafe { ffi::solClient_msg_alloc(&mut msg_ptr) };
+        if !SolClientReturnCode::from_raw(rc).is_ok() {
+            return Err(MessageBuilderError::MessageAlocFailure);
+        }
+
+        let mut msg = OutboundMessage { _msg_ptr: msg_ptr };
+        msg.set_destination(destination)?;
+        msg.set_delivery_mode(delivery_mode)?;
+        msg.set_payload(payload)?;
+        Ok(msg)
+    }
+
+    /// Duplicates the message. The payload is shared by the C library, so this is cheap.
+    pub fn try_clone(&self) -> std::result::Result<Self, MessageError> {
+        let mut dup_ptr: ffi::solClient_opaqueMsg_pt = ptr::null_mut();
+        let rc = unsafe { ffi::solClient_msg_dup(self._msg_ptr, &mut dup_ptr) };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        rc.ok_or_else(MessageError::DuplicationError)?;
+
+        Ok(Self { _msg_ptr: dup_ptr })
+    }
+
+    /// Copies the message and replaces the destination of the copy. The payload is shared with
+    /// the original message by the C library, so this is cheaper than building the message again.
+    pub fn clone_with_destination(&self, destination: MessageDestination) -> Result<Self> {
+        let mut dup_ptr: ffi::solClient_opaqueMsg_pt = ptr::null_mut();
+        let rc = unsafe { ffi::solClient_msg_dup(self._msg_ptr, &mut dup_ptr) };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        if !rc.is_ok() {
+            return Err(MessageBuilderError::MessageAlocFailure);
+        }
+
+        let mut msg = OutboundMessage { _msg_ptr: dup_ptr };
+        msg.set_destination(&destination)?;
+
+        Ok(msg)
+    }
+}
+
+/// How [`OutboundMessageBuilder::build`] handles fields that have no effect with the delivery
+/// mode, like a time to live on a direct message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Validation {
+    Off,
+    /// Logs a warning and builds the message anyway.
+    #[default]
+    Warn,
+    /// Fails with [`MessageBuilderError::IncompatibleArgs`].
+    Strict,
+}
+
 #[derive(Default)]
 pub struct OutboundMessageBuilder {
     delivery_mode: Option<DeliveryMode>,
     destination: Option<MessageDestination>,
+    reply_to: Option<MessageDestination>,
     message: Option<Vec<u8>>,
     correlation_id: Option<Vec<u8>>,
     class_of_service: Option<ClassOfService>,
@@ -62,8 +178,16 @@ pub struct OutboundMessageBuilder {
     application_msg_type: Option<Vec<u8>>,
     user_data: Option<Vec<u8>>,
     sender_ts: Option<SystemTime>,
+    content_type: Option<Vec<u8>>,
+    content_encoding: Option<Vec<u8>>,
+    user_properties: Vec<(Vec<u8>, UserPropertyValue)>,
     eliding_eligible: Option<()>,
     is_reply: Option<()>,
+    ack_immediately: Option<()>,
+    dmq_eligible: Option<()>,
+    latency_stamp: Option<()>,
+    time_to_live: Option<Duration>,
+    validation: Validation,
 }
 
 impl OutboundMessageBuilder {
@@ -97,6 +221,25 @@ impl OutboundMessageBuilder {
         self
     }
 
+    pub fn reply_to(mut self, reply_to: MessageDestination) -> Self {
+        self.reply_to = Some(reply_to);
+        self
+    }
+
+    /// Sets the reply-to of the message to the P2P inbox of the session, so replies are routed
+    /// back to it without going through [`Session::request`].
+    pub fn reply_to_inbox<M, E>(mut self, session: &Session<'_, M, E>) -> Result<Self>
+    where
+        M: FnMut(InboundMessage) + Send,
+        E: FnMut(SessionEvent) + Send,
+    {
+        let inbox = session
+            .inbox_destination()
+            .map_err(|_| MessageBuilderError::InboxUnavailable)?;
+        self.reply_to = Some(inbox);
+        Ok(self)
+    }
+
     pub fn class_of_service(mut self, cos: ClassOfService) -> Self {
         self.class_of_service = Some(cos);
         self
@@ -165,6 +308,91 @@ impl OutboundMessageBuilder {
         self
     }
 
+    /// Sets the `JMSType` header. Solace JMS carries it in the application message type field.
+    pub fn jms_type<M>(self, jms_type: M) -> Self
+    where
+        M: Into<Vec<u8>>,
+    {
+        self.application_msg_type(jms_type)
+    }
+
+    /// Sets the `JMSCorrelationID` header. Solace JMS carries it in the correlation id field.
+    pub fn jms_correlation_id<M>(self, id: M) -> Self
+    where
+        M: Into<Vec<u8>>,
+    {
+        self.correlation_id(id)
+    }
+
+    /// Sets the `JMSXGroupID` header as a user property.
+    pub fn group_id<M>(self, group_id: M) -> Self
+    where
+        M: Into<Vec<u8>>,
+    {
+        self.user_property(JMSX_GROUP_ID, group_id)
+    }
+
+    pub fn content_type<M>(mut self, content_type: M) -> Self
+    where
+        M: Into<Vec<u8>>,
+    {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    pub fn content_encoding<M>(mut self, content_encoding: M) -> Self
+    where
+        M: Into<Vec<u8>>,
+    {
+        self.content_encoding = Some(content_encoding.into());
+        self
+    }
+
+    /// Adds a string value to the user property map of the message. Setting the same key twice
+    /// replaces the previous value.
+    pub fn user_property<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Vec<u8>>,
+        V: Into<Vec<u8>>,
+    {
+        let key = key.into();
+        self.user_properties.retain(|(k, _)| *k != key);
+        self.user_properties
+            .push((key, UserPropertyValue::String(value.into())));
+        self
+    }
+
+    /// Adds a value of any structured data type to the user property map of the message. Setting
+    /// the same key twice replaces the previous value.
+    pub fn typed_user_property<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Vec<u8>>,
+        V: Into<SdtValue>,
+    {
+        let key = key.into();
+        self.user_properties.retain(|(k, _)| *k != key);
+        self.user_properties
+            .push((key, UserPropertyValue::Typed(value.into())));
+        self
+    }
+
+    /// Adds every field of the map to the user property map of the message, in order.
+    pub fn user_properties(self, properties: SdtMap) -> Self {
+        properties.into_iter().fold(self, |builder, (key, value)| {
+            builder.typed_user_property(key, value)
+        })
+    }
+
+    /// Stamps the message with a [`LatencyStamp`] when it is built, in a user property.
+    pub fn latency_stamp(mut self, latency_stamp: bool) -> Self {
+        if latency_stamp {
+            self.latency_stamp = Some(());
+        } else {
+            self.latency_stamp = None;
+        }
+        self
+    }
+
     pub fn eliding_eligible(mut self, eliding_eligible: bool) -> Self {
         if eliding_eligible {
             self.eliding_eligible = Some(());
@@ -174,7 +402,73 @@ impl OutboundMessageBuilder {
         self
     }
 
+    /// Asks the broker to acknowledge the guaranteed message right away instead of batching the
+    /// acknowledgement with others.
+    pub fn ack_immediately(mut self, ack_immediately: bool) -> Self {
+        if ack_immediately {
+            self.ack_immediately = Some(());
+        } else {
+            self.ack_immediately = None;
+        }
+        self
+    }
+
+    /// Moves the guaranteed message to the dead message queue instead of discarding it once it
+    /// expires.
+    pub fn dmq_eligible(mut self, dmq_eligible: bool) -> Self {
+        if dmq_eligible {
+            self.dmq_eligible = Some(());
+        } else {
+            self.dmq_eligible = None;
+        }
+        self
+    }
+
+    /// Time after which the broker expires the guaranteed message. Only has an effect if the queue
+    /// respects message TTLs.
+    pub fn time_to_live(mut self, ttl: Duration) -> Self {
+        self.time_to_live = Some(ttl);
+        self
+    }
+
+    /// Sets how fields that do not apply to the delivery mode are handled. Defaults to
+    /// [`Validation::Warn`].
+    pub fn validation(mut self, validation: Validation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    fn validate(&self, delivery_mode: DeliveryMode) -> Result<()> {
+        let incompatible = match delivery_mode {
+            DeliveryMode::Direct => vec![
+                ("ack_immediately", self.ack_immediately.is_some()),
+                ("dmq_eligible", self.dmq_eligible.is_some()),
+                ("time_to_live", self.time_to_live.is_some()),
+            ],
+            _ => vec![("eliding_eligible", self.eliding_eligible.is_some())],
+        };
+
+        for (field, _) in incompatible.into_iter().filter(|(_, set)| *set) {
+            match self.validation {
+                Validation::Off => (),
+                Validation::Warn => warn!("{field} has no effect on {delivery_mode:?} messages"),
+                Validation::Strict => {
+                    return Err(MessageBuilderError::IncompatibleArgs(
+                        field.to_owned(),
+                        delivery_mode,
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn build(self) -> Result<OutboundMessage> {
+        if let Some(delivery_mode) = self.delivery_mode {
+            self.validate(delivery_mode)?;
+        }
+
         // message allocation
         let mut msg_ptr: ffi::solClient_opaqueMsg_pt = ptr::null_mut();
         let rc = unsafe { ffi::solClient_msg_alloc(&mut msg_ptr) };
@@ -186,7 +480,7 @@ impl OutboundMessageBuilder {
         };
 
         // OutboundMessage is responsible for dropping the message in-case of any errors
-        let msg = OutboundMessage { _msg_ptr: msg_ptr };
+        let mut msg = OutboundMessage { _msg_ptr: msg_ptr };
 
         // We do not check the return code for many of the setter functions since they only fail
         // on invalid msg_ptr. We validated the message ptr above, so no need to double check.
@@ -205,19 +499,22 @@ impl OutboundMessageBuilder {
                 "destination".to_owned(),
             ));
         };
-        // destination is being copied by solClient_msg_setDestination
-        // so it is fine to create a ptr for the destination.dest
-        let mut destination: ffi::solClient_destination = ffi::solClient_destination {
-            destType: destination.dest_type.to_i32(),
-            dest: destination.dest.as_ptr(),
-        };
-        unsafe {
-            ffi::solClient_msg_setDestination(
-                msg_ptr,
-                &mut destination,
-                std::mem::size_of::<ffi::solClient_destination>(),
-            )
-        };
+        msg.set_destination(&destination)?;
+
+        // reply_to is copied over in the same way as destination
+        if let Some(reply_to) = self.reply_to {
+            let reply_to: ffi::solClient_destination = ffi::solClient_destination {
+                destType: reply_to.dest_type.to_i32(),
+                dest: reply_to.dest.as_ptr(),
+            };
+            unsafe {
+                ffi::solClient_msg_setReplyTo(
+                    msg_ptr,
+                    &reply_to,
+                    std::mem::size_of::<ffi::solClient_destination>(),
+                )
+            };
+        }
 
         if let Some(user_data) = self.user_data {
             if user_data.len()
@@ -261,7 +558,7 @@ impl OutboundMessageBuilder {
         // correlation_id
         if let Some(id) = self.correlation_id {
             // correlation_id is copied over
-            let c_id = CString::new(id)?;
+            let c_id = to_c_string("correlation_id", id)?;
             unsafe { ffi::solClient_msg_setCorrelationId(msg_ptr, c_id.as_ptr()) };
         }
 
@@ -296,17 +593,85 @@ impl OutboundMessageBuilder {
         // Application ID
         if let Some(id) = self.application_id {
             // application id is copied over
-            let c_id = CString::new(id)?;
+            let c_id = to_c_string("application_id", id)?;
             unsafe { ffi::solClient_msg_setApplicationMessageId(msg_ptr, c_id.as_ptr()) };
         }
 
         // Application Message Type
         if let Some(message_type) = self.application_msg_type {
             // application msg type is copied over
-            let c_type = CString::new(message_type)?;
+            let c_type = to_c_string("application_msg_type", message_type)?;
             unsafe { ffi::solClient_msg_setApplicationMsgType(msg_ptr, c_type.as_ptr()) };
         }
 
+        // Content type and encoding are copied over
+        if let Some(content_type) = self.content_type {
+            let c_type = to_c_string("content_type", content_type)?;
+            unsafe { ffi::solClient_msg_setHttpContentType(msg_ptr, c_type.as_ptr()) };
+        }
+
+        if let Some(content_encoding) = self.content_encoding {
+            let c_encoding = to_c_string("content_encoding", content_encoding)?;
+            unsafe { ffi::solClient_msg_setHttpContentEncoding(msg_ptr, c_encoding.as_ptr()) };
+        }
+
+        // User properties, including the latency stamp
+        let mut user_properties = self.user_properties;
+        if self.latency_stamp.is_some() {
+            let key = LatencyStamp::USER_PROPERTY.as_bytes();
+            user_properties.retain(|(k, _)| k != key);
+            let stamp = LatencyStamp::now().to_string().into_bytes();
+            user_properties.push((key.to_vec(), UserPropertyValue::String(stamp)));
+        }
+        if !user_properties.is_empty() {
+            // the map is sized upfront with some room for the per field encoding overhead
+            let map_size: usize = user_properties
+                .iter()
+                .map(|(k, v)| k.len() + v.size_hint() + 16)
+                .sum();
+
+            let mut map: ffi::solClient_opaqueContainer_pt = ptr::null_mut();
+            let rc = unsafe {
+                ffi::solClient_msg_createUserPropertyMap(msg_ptr, &mut map, map_size as u32 + 64)
+            };
+            let rc = SolClientReturnCode::from_raw(rc);
+            if !rc.is_ok() {
+                return Err(MessageBuilderError::UserPropertyError(
+                    "user_property_map".to_owned(),
+                    rc,
+                ));
+            }
+
+            for (key, value) in user_properties {
+                let c_key = to_c_string("user_property", key)?;
+                let result = match value {
+                    UserPropertyValue::String(value) => {
+                        let c_value = to_c_string("user_property", value)?;
+                        let rc = unsafe {
+                            ffi::solClient_container_addString(
+                                map,
+                                c_value.as_ptr(),
+                                c_key.as_ptr(),
+                            )
+                        };
+                        SolClientReturnCode::from_raw(rc).ok_or_else(|rc| rc)
+                    }
+                    UserPropertyValue::Typed(value) => unsafe {
+                        sdt::add_field(map, Some(&c_key), &value)
+                    },
+                };
+                if let Err(rc) = result {
+                    unsafe { ffi::solClient_container_closeMapStream(&mut map) };
+                    return Err(MessageBuilderError::UserPropertyError(
+                        c_key.to_string_lossy().into_owned(),
+                        rc,
+                    ));
+                }
+            }
+
+            unsafe { ffi::solClient_container_closeMapStream(&mut map) };
+        }
+
         if self.eliding_eligible.is_some() {
             unsafe { ffi::solClient_msg_setElidingEligible(msg_ptr, true.into()) };
         }
@@ -315,6 +680,19 @@ impl OutboundMessageBuilder {
             unsafe { ffi::solClient_msg_setAsReplyMsg(msg_ptr, true.into()) };
         }
 
+        if self.ack_immediately.is_some() {
+            unsafe { ffi::solClient_msg_setAckImmediately(msg_ptr, true.into()) };
+        }
+
+        if self.dmq_eligible.is_some() {
+            unsafe { ffi::solClient_msg_setDMQEligible(msg_ptr, true.into()) };
+        }
+
+        if let Some(ttl) = self.time_to_live {
+            let ttl_ms = i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX);
+            unsafe { ffi::solClient_msg_setTimeToLive(msg_ptr, ttl_ms) };
+        }
+
         Ok(msg)
     }
 }
@@ -360,6 +738,69 @@ mod tests {
         assert!(elided_msg.is_eliding_eligible());
     }
 
+    #[test]
+    fn it_should_build_with_guaranteed_only_fields() {
+        let dest = MessageDestination::new(DestinationType::Queue, "test_queue").unwrap();
+        let msg = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Persistent)
+            .destination(dest)
+            .payload("Hello")
+            .ack_immediately(true)
+            .dmq_eligible(true)
+            .time_to_live(Duration::from_secs(5))
+            .validation(Validation::Strict)
+            .build()
+            .unwrap();
+
+        assert!(msg.is_ack_immediately());
+        assert!(msg.is_dmq_eligible());
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            msg.get_time_to_live().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_reject_incompatible_fields_when_strict() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let result = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .time_to_live(Duration::from_secs(5))
+            .validation(Validation::Strict)
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(MessageBuilderError::IncompatibleArgs(field, DeliveryMode::Direct)) if field == "time_to_live"
+        ));
+
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let result = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Persistent)
+            .destination(dest)
+            .eliding_eligible(true)
+            .validation(Validation::Strict)
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(MessageBuilderError::IncompatibleArgs(field, DeliveryMode::Persistent)) if field == "eliding_eligible"
+        ));
+    }
+
+    #[test]
+    fn it_should_build_incompatible_fields_when_not_strict() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let msg = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .dmq_eligible(true)
+            .build();
+
+        assert!(msg.is_ok());
+    }
+
     #[test]
     fn it_should_build_with_is_reply() {
         let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
@@ -399,6 +840,61 @@ mod tests {
         assert!("test_topic" == message_destination.dest.to_string_lossy());
     }
 
+    #[test]
+    fn it_should_name_field_with_nul_byte() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let result = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .correlation_id("id\0")
+            .build();
+
+        assert!(
+            matches!(result, Err(MessageBuilderError::InvalidArgs(field, _)) if field == "correlation_id")
+        );
+    }
+
+    #[test]
+    fn it_should_clone_with_new_destination() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let message = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .payload("Hello")
+            .build()
+            .unwrap();
+
+        let other_dest = MessageDestination::new(DestinationType::Queue, "other_queue").unwrap();
+        let cloned = message.clone_with_destination(other_dest).unwrap();
+
+        let message_destination = message.get_destination().unwrap().unwrap();
+        let cloned_destination = cloned.get_destination().unwrap().unwrap();
+
+        assert!("test_topic" == message_destination.dest.to_string_lossy());
+        assert!("other_queue" == cloned_destination.dest.to_string_lossy());
+        assert_eq!(
+            message.get_payload().unwrap(),
+            cloned.get_payload().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_build_with_same_reply_to() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let reply_to = MessageDestination::new(DestinationType::Queue, "reply_queue").unwrap();
+        let message = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .reply_to(reply_to)
+            .payload("Hello")
+            .build()
+            .unwrap();
+        let reply_to = message.get_reply_to().unwrap().unwrap();
+
+        assert!("reply_queue" == reply_to.dest.to_string_lossy());
+        assert!(DestinationType::Queue == reply_to.dest_type);
+    }
+
     #[test]
     fn it_should_build_with_same_corralation_id() {
         let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
@@ -528,6 +1024,107 @@ mod tests {
         assert!(message.get_application_msg_type().is_none());
     }
 
+    #[test]
+    fn it_should_build_with_latency_stamp() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let before = LatencyStamp::now();
+        let message = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .user_property("region", "EMEA")
+            .latency_stamp(true)
+            .build()
+            .unwrap();
+
+        let stamp = LatencyStamp::from_message(&message).unwrap().unwrap();
+        assert!(stamp >= before);
+        assert!(Some("EMEA") == message.get_user_property("region").unwrap());
+    }
+
+    #[test]
+    fn it_should_round_trip_typed_user_properties() {
+        let reply_to = MessageDestination::new(DestinationType::Queue, "reply_queue").unwrap();
+        let nested: SdtMap = [("b", SdtValue::Int8(-1)), ("a", SdtValue::Null)]
+            .into_iter()
+            .collect();
+        let mut properties = SdtMap::new();
+        properties.insert("bool", true);
+        properties.insert("u16", 7u16);
+        properties.insert("i64", -42i64);
+        properties.insert("f32", 1.5f32);
+        properties.insert("f64", 2.25f64);
+        properties.insert("wchar", SdtValue::WChar('x' as u32));
+        properties.insert("bytes", vec![0u8, 1, 2]);
+        properties.insert("dest", reply_to);
+        properties.insert("map", nested);
+        properties.insert("stream", vec![SdtValue::Uint32(1), "two".into()]);
+
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let message = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .user_property("region", "EMEA")
+            .user_properties(properties.clone())
+            .payload("Hello")
+            .build()
+            .unwrap();
+
+        let mut expected = SdtMap::new();
+        expected.insert("region", "EMEA");
+        expected.extend(properties);
+        assert_eq!(Some(expected), message.get_user_properties().unwrap());
+    }
+
+    #[test]
+    fn it_should_build_with_same_user_properties() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let message = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .user_property("region", "EMEA")
+            .user_property("desk", "fx")
+            .user_property("region", "APAC")
+            .payload("Hello")
+            .build()
+            .unwrap();
+
+        assert!(Some("APAC") == message.get_user_property("region").unwrap());
+        assert!(Some("fx") == message.get_user_property("desk").unwrap());
+        assert!(message.get_user_property("missing").unwrap().is_none());
+
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let message = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .payload("Hello")
+            .build()
+            .unwrap();
+
+        assert!(message.get_user_property("region").unwrap().is_none());
+    }
+
+    #[test]
+    fn it_should_build_with_same_jms_headers() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let message = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .jms_type("order")
+            .jms_correlation_id("corr_1")
+            .group_id("group_1")
+            .content_type("application/json")
+            .content_encoding("gzip")
+            .payload("Hello")
+            .build()
+            .unwrap();
+
+        assert!(Some("order") == message.get_jms_type());
+        assert!(Some("corr_1") == message.get_jms_correlation_id().unwrap());
+        assert!(Some("group_1") == message.get_group_id().unwrap());
+        assert!(Some("application/json") == message.get_content_type().unwrap());
+        assert!(Some("gzip") == message.get_content_encoding().unwrap());
+    }
+
     #[test]
     fn it_should_build_with_same_string_payload() {
         let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
@@ -559,6 +1156,21 @@ mod tests {
         assert!(32_u32.to_be_bytes() == raw_user_data);
     }
 
+    #[test]
+    fn it_should_report_payload_and_total_size() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let message = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .payload("Hello")
+            .user_data(32_u32.to_be_bytes())
+            .build()
+            .unwrap();
+
+        assert_eq!(5, message.payload_len().unwrap());
+        assert_eq!(9, message.total_size().unwrap());
+    }
+
     #[test]
     fn it_should_build_with_same_sender_timestamp() {
         let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
@@ -584,4 +1196,77 @@ mod tests {
 
         assert!(now == ts);
     }
+
+    #[test]
+    fn it_should_build_with_same_sender_timestamp_millis() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let ts = SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(1_700_000_000_123);
+        let message = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .payload("Hello")
+            .sender_timestamp(ts)
+            .build()
+            .unwrap();
+
+        let millis = message.get_sender_timestamp_millis().unwrap().unwrap();
+
+        assert_eq!(1_700_000_000_123, millis);
+    }
+
+    #[test]
+    fn it_should_build_from_parts() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let message =
+            OutboundMessage::from_parts(&dest, DeliveryMode::Persistent, b"Hello").unwrap();
+
+        // ownership round trips through the raw pointer
+        let message = unsafe { OutboundMessage::from_raw(message.into_raw()) };
+
+        assert_eq!(
+            "test_topic",
+            message
+                .get_destination()
+                .unwrap()
+                .unwrap()
+                .dest
+                .to_str()
+                .unwrap()
+        );
+        assert_eq!(
+            DeliveryMode::Persistent,
+            message.get_delivery_mode().unwrap()
+        );
+        assert_eq!(b"Hello", message.get_payload().unwrap().unwrap());
+    }
+
+    #[test]
+    fn it_should_modify_built_message() {
+        let dest = MessageDestination::new(DestinationType::Topic, "test_topic").unwrap();
+        let mut message = OutboundMessageBuilder::new()
+            .delivery_mode(DeliveryMode::Direct)
+            .destination(dest)
+            .payload("Hello")
+            .build()
+            .unwrap();
+
+        let other_dest = MessageDestination::new(DestinationType::Topic, "other_topic").unwrap();
+        message.set_destination(&other_dest).unwrap();
+        message.set_delivery_mode(DeliveryMode::Persistent).unwrap();
+        message.set_correlation_id("id").unwrap();
+        message.set_payload(b"World").unwrap();
+
+        let destination = message.get_destination().unwrap().unwrap();
+        assert!("other_topic" == destination.dest.to_string_lossy());
+        assert_eq!(
+            DeliveryMode::Persistent,
+            message.get_delivery_mode().unwrap()
+        );
+        assert_eq!(Some("id"), message.get_correlation_id().unwrap());
+        assert_eq!(Some(&b"World"[..]), message.get_payload().unwrap());
+        assert!(matches!(
+            message.set_correlation_id("id\0"),
+            Err(MessageError::InvalidArgs("correlation_id", _))
+        ));
+    }
 }
@@ -0,0 +1,59 @@
+use std::time::SystemTime;
+
+use super::{
+    CacheInfo, ClassOfService, DeliveryMode, InboundMessage, MessageDestination, MessageRead,
+    Result,
+};
+
+/// Plain Rust copy of an inbound message that does not hold on to the C message, created with
+/// [`InboundMessage::into_owned_parts`].
+#[derive(Debug, Clone)]
+pub struct OwnedMessage {
+    pub payload: Option<Vec<u8>>,
+    pub destination: Option<MessageDestination>,
+    pub reply_to: Option<MessageDestination>,
+    pub delivery_mode: DeliveryMode,
+    pub class_of_service: ClassOfService,
+    pub correlation_id: Option<String>,
+    pub application_message_id: Option<String>,
+    pub application_msg_type: Option<String>,
+    pub sender_id: Option<String>,
+    pub sender_timestamp: Option<SystemTime>,
+    pub receive_timestamp: Option<SystemTime>,
+    pub sequence_number: Option<i64>,
+    pub priority: Option<u8>,
+    pub user_data: Option<Vec<u8>>,
+    pub content_type: Option<String>,
+    pub content_encoding: Option<String>,
+    pub is_reply: bool,
+    pub is_discard_indication: bool,
+    pub cache_info: CacheInfo,
+}
+
+impl TryFrom<&InboundMessage> for OwnedMessage {
+    type Error = super::MessageError;
+
+    fn try_from(message: &InboundMessage) -> Result<Self> {
+        Ok(Self {
+            payload: message.get_payload()?.map(<[u8]>::to_vec),
+            destination: message.get_destination()?,
+            reply_to: message.get_reply_to()?,
+            delivery_mode: message.get_delivery_mode()?,
+            class_of_service: message.get_class_of_service()?,
+            correlation_id: message.get_correlation_id()?.map(str::to_owned),
+            application_message_id: message.get_application_message_id().map(str::to_owned),
+            application_msg_type: message.get_application_msg_type().map(str::to_owned),
+            sender_id: message.get_sender_id()?.map(str::to_owned),
+            sender_timestamp: message.get_sender_timestamp()?,
+            receive_timestamp: message.get_receive_timestamp()?,
+            sequence_number: message.get_sequence_number()?,
+            priority: message.get_priority()?,
+            user_data: message.get_user_data()?.map(<[u8]>::to_vec),
+            content_type: message.get_content_type()?.map(str::to_owned),
+            content_encoding: message.get_content_encoding()?.map(str::to_owned),
+            is_reply: message.is_reply(),
+            is_discard_indication: message.is_discard_indication(),
+            cache_info: message.cache_info()?,
+        })
+    }
+}
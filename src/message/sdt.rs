@@ -0,0 +1,362 @@
+use std::ffi::{c_char, CStr, CString};
+use std::mem;
+use std::ptr;
+use std::slice;
+
+use solace_rs_sys as ffi;
+
+use super::{MessageDestination, MessageError, Result};
+use crate::SolClientReturnCode;
+
+/// Value of a structured data (SDT) map or stream field. Every type of the C library is kept
+/// as is, so values round trip to JMS applications without changing their type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SdtValue {
+    Null,
+    Bool(bool),
+    Uint8(u8),
+    Int8(i8),
+    Uint16(u16),
+    Int16(i16),
+    Uint32(u32),
+    Int32(i32),
+    Uint64(u64),
+    Int64(i64),
+    /// Wide character, as the `wint_t` of the platform.
+    WChar(u32),
+    Float(f32),
+    Double(f64),
+    String(String),
+    ByteArray(Vec<u8>),
+    Destination(MessageDestination),
+    Map(SdtMap),
+    Stream(Vec<SdtValue>),
+    /// Encoded SMF message.
+    Smf(Vec<u8>),
+}
+
+impl SdtValue {
+    // rough encoded size, used to size containers upfront
+    pub(crate) fn size_hint(&self) -> usize {
+        match self {
+            Self::String(s) => s.len() + 8,
+            Self::ByteArray(b) | Self::Smf(b) => b.len() + 8,
+            Self::Destination(d) => d.dest.as_bytes().len() + 8,
+            Self::Map(m) => m.size_hint() + 8,
+            Self::Stream(s) => s.iter().map(Self::size_hint).sum::<usize>() + 8,
+            _ => 16,
+        }
+    }
+}
+
+macro_rules! impl_from_for_sdt_value {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(impl From<$ty> for SdtValue {
+            fn from(value: $ty) -> Self {
+                Self::$variant(value.into())
+            }
+        })*
+    };
+}
+
+impl_from_for_sdt_value!(
+    bool => Bool,
+    u8 => Uint8,
+    i8 => Int8,
+    u16 => Uint16,
+    i16 => Int16,
+    u32 => Uint32,
+    i32 => Int32,
+    u64 => Uint64,
+    i64 => Int64,
+    f32 => Float,
+    f64 => Double,
+    String => String,
+    &str => String,
+    Vec<u8> => ByteArray,
+    &[u8] => ByteArray,
+    MessageDestination => Destination,
+    SdtMap => Map,
+    Vec<SdtValue> => Stream,
+);
+
+/// Structured data map. Fields keep the order they were added or received in.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SdtMap {
+    fields: Vec<(String, SdtValue)>,
+}
+
+impl SdtMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the field, replacing the value of an existing field with the same name in place.
+    pub fn insert<K, V>(&mut self, name: K, value: V)
+    where
+        K: Into<String>,
+        V: Into<SdtValue>,
+    {
+        let name = name.into();
+        let value = value.into();
+        match self.fields.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value,
+            None => self.fields.push((name, value)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SdtValue> {
+        self.fields.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<SdtValue> {
+        let index = self.fields.iter().position(|(n, _)| n == name)?;
+        Some(self.fields.remove(index).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SdtValue)> {
+        self.fields.iter().map(|(n, v)| (n.as_str(), v))
+    }
+
+    pub(crate) fn size_hint(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|(n, v)| n.len() + v.size_hint())
+            .sum()
+    }
+}
+
+impl<K: Into<String>, V: Into<SdtValue>> FromIterator<(K, V)> for SdtMap {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Into<String>, V: Into<SdtValue>> Extend<(K, V)> for SdtMap {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (name, value) in iter {
+            self.insert(name, value);
+        }
+    }
+}
+
+impl IntoIterator for SdtMap {
+    type Item = (String, SdtValue);
+    type IntoIter = std::vec::IntoIter<(String, SdtValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.into_iter()
+    }
+}
+
+/// Reads every field of an open map container, in order.
+///
+/// # Safety
+/// `container` must be an open map container.
+pub(crate) unsafe fn read_map(container: ffi::solClient_opaqueContainer_pt) -> Result<SdtMap> {
+    let mut map = SdtMap::new();
+    while let Some((name, value)) = next_field(container)? {
+        let name = name.ok_or(MessageError::FieldConvertionError("sdt_field_name"))?;
+        map.fields.push((name, value));
+    }
+    Ok(map)
+}
+
+/// Reads every field of an open stream container, in order.
+///
+/// # Safety
+/// `container` must be an open stream container.
+pub(crate) unsafe fn read_stream(
+    container: ffi::solClient_opaqueContainer_pt,
+) -> Result<Vec<SdtValue>> {
+    let mut stream = Vec::new();
+    while let Some((_, value)) = next_field(container)? {
+        stream.push(value);
+    }
+    Ok(stream)
+}
+
+unsafe fn next_field(
+    container: ffi::solClient_opaqueContainer_pt,
+) -> Result<Option<(Option<String>, SdtValue)>> {
+    let mut field: ffi::solClient_field_t = mem::zeroed();
+    let mut name_p: *const c_char = ptr::null();
+    let rc = ffi::solClient_container_getNextField(
+        container,
+        &mut field,
+        mem::size_of::<ffi::solClient_field_t>(),
+        &mut name_p,
+    );
+    match SolClientReturnCode::from_raw(rc) {
+        SolClientReturnCode::Ok => (),
+        SolClientReturnCode::EndOfStream => return Ok(None),
+        rc => return Err(MessageError::FieldError("sdt_field", rc)),
+    }
+
+    let name = if name_p.is_null() {
+        None
+    } else {
+        let name = CStr::from_ptr(name_p)
+            .to_str()
+            .map_err(|_| MessageError::FieldConvertionError("sdt_field_name"))?;
+        Some(name.to_owned())
+    };
+
+    let value = &field.value;
+    let bytes = |p: *const u8| {
+        if p.is_null() {
+            Vec::new()
+        } else {
+            slice::from_raw_parts(p, field.length as usize).to_vec()
+        }
+    };
+    let value = match field.type_ {
+        ffi::solClient_fieldType_SOLCLIENT_NULL => SdtValue::Null,
+        ffi::solClient_fieldType_SOLCLIENT_BOOL => SdtValue::Bool(value.boolean != 0),
+        ffi::solClient_fieldType_SOLCLIENT_UINT8 => SdtValue::Uint8(value.uint8),
+        ffi::solClient_fieldType_SOLCLIENT_INT8 => SdtValue::Int8(value.int8),
+        ffi::solClient_fieldType_SOLCLIENT_UINT16 => SdtValue::Uint16(value.uint16),
+        ffi::solClient_fieldType_SOLCLIENT_INT16 => SdtValue::Int16(value.int16),
+        ffi::solClient_fieldType_SOLCLIENT_UINT32 => SdtValue::Uint32(value.uint32),
+        ffi::solClient_fieldType_SOLCLIENT_INT32 => SdtValue::Int32(value.int32),
+        ffi::solClient_fieldType_SOLCLIENT_UINT64 => SdtValue::Uint64(value.uint64),
+        ffi::solClient_fieldType_SOLCLIENT_INT64 => SdtValue::Int64(value.int64),
+        ffi::solClient_fieldType_SOLCLIENT_WCHAR => SdtValue::WChar(value.wchar as u32),
+        ffi::solClient_fieldType_SOLCLIENT_FLOAT => SdtValue::Float(value.float32),
+        ffi::solClient_fieldType_SOLCLIENT_DOUBLE => SdtValue::Double(value.float64),
+        ffi::solClient_fieldType_SOLCLIENT_STRING => {
+            let s = CStr::from_ptr(value.string)
+                .to_str()
+                .map_err(|_| MessageError::FieldConvertionError("sdt_string"))?;
+            SdtValue::String(s.to_owned())
+        }
+        ffi::solClient_fieldType_SOLCLIENT_BYTEARRAY => SdtValue::ByteArray(bytes(value.bytearray)),
+        ffi::solClient_fieldType_SOLCLIENT_SMF => SdtValue::Smf(bytes(value.smf)),
+        ffi::solClient_fieldType_SOLCLIENT_DESTINATION => {
+            SdtValue::Destination(MessageDestination::from(value.dest))
+        }
+        ffi::solClient_fieldType_SOLCLIENT_MAP => {
+            // nested containers are opened by the read and have to be closed
+            let mut sub = value.map;
+            let map = read_map(sub);
+            ffi::solClient_container_closeMapStream(&mut sub);
+            SdtValue::Map(map?)
+        }
+        ffi::solClient_fieldType_SOLCLIENT_STREAM => {
+            let mut sub = value.stream;
+            let stream = read_stream(sub);
+            ffi::solClient_container_closeMapStream(&mut sub);
+            SdtValue::Stream(stream?)
+        }
+        _ => return Err(MessageError::FieldConvertionError("sdt_field")),
+    };
+
+    Ok(Some((name, value)))
+}
+
+/// Adds the field to an open container. Map fields need a name, stream fields have none.
+///
+/// # Safety
+/// `container` must be an open map or stream container.
+pub(crate) unsafe fn add_field(
+    container: ffi::solClient_opaqueContainer_pt,
+    name: Option<&CStr>,
+    value: &SdtValue,
+) -> std::result::Result<(), SolClientReturnCode> {
+    let name = name.map_or(ptr::null(), CStr::as_ptr);
+    let rc = match value {
+        SdtValue::Null => ffi::solClient_container_addNull(container, name),
+        SdtValue::Bool(v) => ffi::solClient_container_addBoolean(container, (*v).into(), name),
+        SdtValue::Uint8(v) => ffi::solClient_container_addUint8(container, *v, name),
+        SdtValue::Int8(v) => ffi::solClient_container_addInt8(container, *v, name),
+        SdtValue::Uint16(v) => ffi::solClient_container_addUint16(container, *v, name),
+        SdtValue::Int16(v) => ffi::solClient_container_addInt16(container, *v, name),
+        SdtValue::Uint32(v) => ffi::solClient_container_addUint32(container, *v, name),
+        SdtValue::Int32(v) => ffi::solClient_container_addInt32(container, *v, name),
+        SdtValue::Uint64(v) => ffi::solClient_container_addUint64(container, *v, name),
+        SdtValue::Int64(v) => ffi::solClient_container_addInt64(container, *v, name),
+        SdtValue::WChar(v) => ffi::solClient_container_addWchar(container, *v as _, name),
+        SdtValue::Float(v) => ffi::solClient_container_addFloat(container, *v, name),
+        SdtValue::Double(v) => ffi::solClient_container_addDouble(container, *v, name),
+        SdtValue::String(v) => {
+            let Ok(c_value) = CString::new(v.as_str()) else {
+                return Err(SolClientReturnCode::Fail);
+            };
+            ffi::solClient_container_addString(container, c_value.as_ptr(), name)
+        }
+        SdtValue::ByteArray(v) => {
+            ffi::solClient_container_addByteArray(container, v.as_ptr(), v.len() as u32, name)
+        }
+        SdtValue::Smf(v) => {
+            ffi::solClient_container_addSmf(container, v.as_ptr(), v.len() as u32, name)
+        }
+        SdtValue::Destination(v) => {
+            let raw_dest = ffi::solClient_destination {
+                destType: v.dest_type.to_i32(),
+                dest: v.dest.as_ptr(),
+            };
+            ffi::solClient_container_addDestination(
+                container,
+                &raw_dest,
+                mem::size_of::<ffi::solClient_destination>(),
+                name,
+            )
+        }
+        SdtValue::Map(map) => {
+            let mut sub = ptr::null_mut();
+            let rc = ffi::solClient_container_openSubMap(container, &mut sub, name);
+            SolClientReturnCode::from_raw(rc).ok_or_else(|rc| rc)?;
+            let result = map.fields.iter().try_for_each(|(n, v)| {
+                let Ok(c_name) = CString::new(n.as_str()) else {
+                    return Err(SolClientReturnCode::Fail);
+                };
+                add_field(sub, Some(&c_name), v)
+            });
+            ffi::solClient_container_closeMapStream(&mut sub);
+            return result;
+        }
+        SdtValue::Stream(stream) => {
+            let mut sub = ptr::null_mut();
+            let rc = ffi::solClient_container_openSubStream(container, &mut sub, name);
+            SolClientReturnCode::from_raw(rc).ok_or_else(|rc| rc)?;
+            let result = stream.iter().try_for_each(|v| add_field(sub, None, v));
+            ffi::solClient_container_closeMapStream(&mut sub);
+            return result;
+        }
+    };
+    SolClientReturnCode::from_raw(rc).ok_or_else(|rc| rc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_keep_field_order_when_replacing() {
+        let mut map = SdtMap::new();
+        map.insert("b", 1u8);
+        map.insert("a", "x");
+        map.insert("b", 2i16);
+
+        let fields: Vec<_> = map.iter().collect();
+        assert_eq!(
+            vec![
+                ("b", &SdtValue::Int16(2)),
+                ("a", &SdtValue::String("x".to_owned()))
+            ],
+            fields
+        );
+        assert_eq!(Some(SdtValue::Int16(2)), map.remove("b"));
+        assert_eq!(1, map.len());
+    }
+}
@@ -0,0 +1,100 @@
+use std::ffi::{c_char, CStr};
+use std::marker::PhantomData;
+use std::ptr;
+
+use solace_rs_sys as ffi;
+
+/// Null terminated list of key value pairs passed to the C library.
+///
+/// The list only stores pointers, the lifetime ties it to the values so that it can not outlive
+/// them. Keys and static values are the nul terminated byte strings from the bindings.
+pub(crate) struct PropList<'a> {
+    raw: Vec<*const c_char>,
+    values: PhantomData<&'a CStr>,
+}
+
+impl<'a> PropList<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            raw: vec![ptr::null()],
+            values: PhantomData,
+        }
+    }
+
+    pub(crate) fn push(&mut self, key: &'static [u8], value: &'a CStr) {
+        self.push_raw(key, value.as_ptr());
+    }
+
+    pub(crate) fn push_static(&mut self, key: &'static [u8], value: &'static [u8]) {
+        debug_assert_eq!(Some(&0), value.last());
+        self.push_raw(key, value.as_ptr().cast());
+    }
+
+    pub(crate) fn push_bool(&mut self, key: &'static [u8], value: bool) {
+        let value = if value {
+            &ffi::SOLCLIENT_PROP_ENABLE_VAL[..]
+        } else {
+            &ffi::SOLCLIENT_PROP_DISABLE_VAL[..]
+        };
+        self.push_static(key, value);
+    }
+
+    pub(crate) fn push_opt<V: AsRef<CStr>>(&mut self, key: &'static [u8], value: &'a Option<V>) {
+        if let Some(value) = value {
+            self.push(key, value.as_ref());
+        }
+    }
+
+    pub(crate) fn push_opt_bool(&mut self, key: &'static [u8], value: Option<bool>) {
+        if let Some(value) = value {
+            self.push_bool(key, value);
+        }
+    }
+
+    fn push_raw(&mut self, key: &'static [u8], value: *const c_char) {
+        debug_assert_eq!(Some(&0), key.last());
+        // keeps the terminating null at the end
+        self.raw.pop();
+        self.raw.extend([key.as_ptr().cast(), value, ptr::null()]);
+    }
+
+    /// The C library copies the values, the list can be dropped once the call returns.
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut *const c_char {
+        self.raw.as_mut_ptr()
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [*const c_char] {
+        &mut self.raw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn it_should_stay_null_terminated() {
+        let value = CString::new("localhost").unwrap();
+        let mut props = PropList::new();
+        assert_eq!(&[ptr::null()], props.as_mut_slice());
+
+        props.push(ffi::SOLCLIENT_SESSION_PROP_HOST, &value);
+        props.push_bool(ffi::SOLCLIENT_SESSION_PROP_TCP_NODELAY, true);
+
+        let raw = props.as_mut_slice();
+        assert_eq!(5, raw.len());
+        assert_eq!(value.as_ptr(), raw[1]);
+        assert!(raw[4].is_null());
+    }
+
+    #[test]
+    fn it_should_skip_unset_optional_props() {
+        let value: Option<CString> = None;
+        let mut props = PropList::new();
+        props.push_opt(ffi::SOLCLIENT_SESSION_PROP_CLIENT_NAME, &value);
+        props.push_opt_bool(ffi::SOLCLIENT_SESSION_PROP_NO_LOCAL, None);
+
+        assert_eq!(&[ptr::null()], props.as_mut_slice());
+    }
+}
@@ -0,0 +1,519 @@
+use std::fmt::{self, Display, Write};
+
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SelectorError {
+    #[error("{0} is not a valid selector identifier")]
+    InvalidIdentifier(String),
+    #[error("{0} is not a valid selector number")]
+    InvalidNumber(f64),
+    #[error("in list of {0} needs at least one value")]
+    EmptyInList(String),
+}
+
+type Result<T> = std::result::Result<T, SelectorError>;
+
+// keywords of the JMS selector grammar that can not be used as identifiers
+const RESERVED: [&str; 11] = [
+    "NULL", "TRUE", "FALSE", "NOT", "AND", "OR", "BETWEEN", "LIKE", "IN", "IS", "ESCAPE",
+];
+
+// escape character used for like patterns built from literals
+const LIKE_ESCAPE: char = '\\';
+
+/// Literal that can be compared against a message property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Compare(String, &'static str, Value),
+    Like {
+        name: String,
+        negated: bool,
+        pattern: String,
+        // built from escaped literals, so the pattern needs the `ESCAPE` clause
+        escaped: bool,
+    },
+    In(String, bool, Vec<Value>),
+    Between(String, bool, Value, Value),
+    IsNull(String, bool),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+/// Typed builder for JMS message selectors.
+///
+/// Identifiers are validated and literals are quoted and escaped when the selector is rendered
+/// with [`Selector::build`].
+///
+/// ```
+/// use solace_rs::selector::Selector;
+///
+/// let selector = Selector::field("region")
+///     .eq("EMEA")
+///     .and(Selector::field("price").gt(10));
+///
+/// assert_eq!("(region = 'EMEA') AND (price > 10)", selector.build().unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    expr: Expr,
+}
+
+/// Message property a condition is built on, created by [`Selector::field`].
+#[derive(Debug, Clone)]
+pub struct Field {
+    name: String,
+}
+
+impl Selector {
+    pub fn field<T: Into<String>>(name: T) -> Field {
+        Field { name: name.into() }
+    }
+
+    pub fn and(self, other: Selector) -> Selector {
+        Selector {
+            expr: Expr::And(Box::new(self.expr), Box::new(other.expr)),
+        }
+    }
+
+    pub fn or(self, other: Selector) -> Selector {
+        Selector {
+            expr: Expr::Or(Box::new(self.expr), Box::new(other.expr)),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Selector {
+        Selector {
+            expr: Expr::Not(Box::new(self.expr)),
+        }
+    }
+
+    /// Renders the selector string.
+    pub fn build(&self) -> Result<String> {
+        let mut out = String::new();
+        write_expr(&mut out, &self.expr)?;
+        Ok(out)
+    }
+
+    /// Escapes the `%` and `_` wildcards of a literal so it can be used in a like pattern
+    /// together with `ESCAPE '\'`.
+    pub fn escape_like(literal: &str) -> String {
+        let mut escaped = String::with_capacity(literal.len());
+        for c in literal.chars() {
+            if c == '%' || c == '_' || c == LIKE_ESCAPE {
+                escaped.push(LIKE_ESCAPE);
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+}
+
+impl Field {
+    fn compare<V: Into<Value>>(self, op: &'static str, value: V) -> Selector {
+        Selector {
+            expr: Expr::Compare(self.name, op, value.into()),
+        }
+    }
+
+    pub fn eq<V: Into<Value>>(self, value: V) -> Selector {
+        self.compare("=", value)
+    }
+
+    pub fn ne<V: Into<Value>>(self, value: V) -> Selector {
+        self.compare("<>", value)
+    }
+
+    pub fn gt<V: Into<Value>>(self, value: V) -> Selector {
+        self.compare(">", value)
+    }
+
+    pub fn ge<V: Into<Value>>(self, value: V) -> Selector {
+        self.compare(">=", value)
+    }
+
+    pub fn lt<V: Into<Value>>(self, value: V) -> Selector {
+        self.compare("<", value)
+    }
+
+    pub fn le<V: Into<Value>>(self, value: V) -> Selector {
+        self.compare("<=", value)
+    }
+
+    /// Raw like pattern, `%` and `_` are wildcards. Use [`Field::starts_with`] and friends to
+    /// match literals.
+    pub fn like<T: Into<String>>(self, pattern: T) -> Selector {
+        self.like_pattern(false, pattern.into(), false)
+    }
+
+    pub fn not_like<T: Into<String>>(self, pattern: T) -> Selector {
+        self.like_pattern(true, pattern.into(), false)
+    }
+
+    pub fn starts_with(self, prefix: &str) -> Selector {
+        self.like_pattern(false, format!("{}%", Selector::escape_like(prefix)), true)
+    }
+
+    pub fn ends_with(self, suffix: &str) -> Selector {
+        self.like_pattern(false, format!("%{}", Selector::escape_like(suffix)), true)
+    }
+
+    pub fn contains(self, infix: &str) -> Selector {
+        self.like_pattern(false, format!("%{}%", Selector::escape_like(infix)), true)
+    }
+
+    fn like_pattern(self, negated: bool, pattern: String, escaped: bool) -> Selector {
+        Selector {
+            expr: Expr::Like {
+                name: self.name,
+                negated,
+                pattern,
+                escaped,
+            },
+        }
+    }
+
+    pub fn in_list<I, V>(self, values: I) -> Selector
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        Selector {
+            expr: Expr::In(
+                self.name,
+                false,
+                values.into_iter().map(Into::into).collect(),
+            ),
+        }
+    }
+
+    pub fn not_in_list<I, V>(self, values: I) -> Selector
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        Selector {
+            expr: Expr::In(
+                self.name,
+                true,
+                values.into_iter().map(Into::into).collect(),
+            ),
+        }
+    }
+
+    pub fn between<V: Into<Value>>(self, low: V, high: V) -> Selector {
+        Selector {
+            expr: Expr::Between(self.name, false, low.into(), high.into()),
+        }
+    }
+
+    pub fn not_between<V: Into<Value>>(self, low: V, high: V) -> Selector {
+        Selector {
+            expr: Expr::Between(self.name, true, low.into(), high.into()),
+        }
+    }
+
+    pub fn is_null(self) -> Selector {
+        Selector {
+            expr: Expr::IsNull(self.name, false),
+        }
+    }
+
+    pub fn is_not_null(self) -> Selector {
+        Selector {
+            expr: Expr::IsNull(self.name, true),
+        }
+    }
+}
+
+fn write_identifier(out: &mut String, name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid_start = chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$');
+    let valid_rest = chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    let reserved = RESERVED.iter().any(|r| r.eq_ignore_ascii_case(name));
+
+    if !valid_start || !valid_rest || reserved {
+        return Err(SelectorError::InvalidIdentifier(name.to_owned()));
+    }
+
+    out.push_str(name);
+    Ok(())
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('\'');
+    for c in value.chars() {
+        // quotes are escaped by doubling them
+        if c == '\'' {
+            out.push('\'');
+        }
+        out.push(c);
+    }
+    out.push('\'');
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<()> {
+    match value {
+        Value::String(s) => write_string(out, s),
+        Value::Integer(i) => write!(out, "{i}").unwrap(),
+        Value::Float(f) if !f.is_finite() => return Err(SelectorError::InvalidNumber(*f)),
+        // debug formatting always keeps the decimal point, so the literal stays a float
+        Value::Float(f) => write!(out, "{f:?}").unwrap(),
+        Value::Bool(true) => out.push_str("TRUE"),
+        Value::Bool(false) => out.push_str("FALSE"),
+    }
+    Ok(())
+}
+
+fn write_operand(out: &mut String, expr: &Expr) -> Result<()> {
+    // operands are always parenthesized so that precedence never depends on the grammar
+    out.push('(');
+    write_expr(out, expr)?;
+    out.push(')');
+    Ok(())
+}
+
+fn write_expr(out: &mut String, expr: &Expr) -> Result<()> {
+    match expr {
+        Expr::Compare(name, op, value) => {
+            write_identifier(out, name)?;
+            write!(out, " {op} ").unwrap();
+            write_value(out, value)?;
+        }
+        Expr::Like {
+            name,
+            negated,
+            pattern,
+            escaped,
+        } => {
+            write_identifier(out, name)?;
+            out.push_str(if *negated { " NOT LIKE " } else { " LIKE " });
+            write_string(out, pattern);
+            if *escaped && pattern.contains(LIKE_ESCAPE) {
+                out.push_str(" ESCAPE ");
+                write_string(out, &LIKE_ESCAPE.to_string());
+            }
+        }
+        Expr::In(name, negated, values) => {
+            if values.is_empty() {
+                return Err(SelectorError::EmptyInList(name.to_owned()));
+            }
+            write_identifier(out, name)?;
+            out.push_str(if *negated { " NOT IN (" } else { " IN (" });
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_value(out, value)?;
+            }
+            out.push(')');
+        }
+        Expr::Between(name, negated, low, high) => {
+            write_identifier(out, name)?;
+            out.push_str(if *negated {
+                " NOT BETWEEN "
+            } else {
+                " BETWEEN "
+            });
+            write_value(out, low)?;
+            out.push_str(" AND ");
+            write_value(out, high)?;
+        }
+        Expr::IsNull(name, negated) => {
+            write_identifier(out, name)?;
+            out.push_str(if *negated { " IS NOT NULL" } else { " IS NULL" });
+        }
+        Expr::And(left, right) => {
+            write_operand(out, left)?;
+            out.push_str(" AND ");
+            write_operand(out, right)?;
+        }
+        Expr::Or(left, right) => {
+            write_operand(out, left)?;
+            out.push_str(" OR ");
+            write_operand(out, right)?;
+        }
+        Expr::Not(inner) => {
+            out.push_str("NOT ");
+            write_operand(out, inner)?;
+        }
+    }
+    Ok(())
+}
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.build() {
+            Ok(selector) => f.write_str(&selector),
+            Err(_) => Err(fmt::Error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_quote_strings() {
+        let selector = Selector::field("region").eq("EMEA");
+
+        assert_eq!("region = 'EMEA'", selector.build().unwrap());
+    }
+
+    #[test]
+    fn it_should_escape_quotes() {
+        let selector = Selector::field("name").eq("O'Brien");
+
+        assert_eq!("name = 'O''Brien'", selector.build().unwrap());
+    }
+
+    #[test]
+    fn it_should_render_numbers_and_bools() {
+        let selector = Selector::field("qty")
+            .ge(10)
+            .and(Selector::field("price").lt(1.0))
+            .and(Selector::field("active").eq(true));
+
+        assert_eq!(
+            "((qty >= 10) AND (price < 1.0)) AND (active = TRUE)",
+            selector.build().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_parenthesize_operands() {
+        let selector = Selector::field("a")
+            .eq(1)
+            .or(Selector::field("b").eq(2))
+            .and(Selector::field("c").eq(3).not());
+
+        assert_eq!(
+            "((a = 1) OR (b = 2)) AND (NOT (c = 3))",
+            selector.build().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_reject_invalid_identifiers() {
+        for name in ["", "1abc", "a-b", "a b", "and", "NULL", "Between"] {
+            assert_eq!(
+                Err(SelectorError::InvalidIdentifier(name.to_owned())),
+                Selector::field(name).eq(1).build()
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_accept_identifier_characters() {
+        let selector = Selector::field("$_JMSx1").is_not_null();
+
+        assert_eq!("$_JMSx1 IS NOT NULL", selector.build().unwrap());
+    }
+
+    #[test]
+    fn it_should_escape_like_literals() {
+        let selector = Selector::field("topic").starts_with("100%_a\\b");
+
+        assert_eq!(
+            "topic LIKE '100\\%\\_a\\\\b%' ESCAPE '\\'",
+            selector.build().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_not_add_escape_clause_without_escapes() {
+        let selector = Selector::field("topic").contains("abc");
+
+        assert_eq!("topic LIKE '%abc%'", selector.build().unwrap());
+    }
+
+    #[test]
+    fn it_should_not_add_escape_clause_to_raw_patterns() {
+        let selector = Selector::field("path").like("a\\b");
+
+        assert_eq!("path LIKE 'a\\b'", selector.build().unwrap());
+    }
+
+    #[test]
+    fn it_should_render_in_list() {
+        let selector = Selector::field("region").not_in_list(["EMEA", "APAC"]);
+
+        assert_eq!("region NOT IN ('EMEA', 'APAC')", selector.build().unwrap());
+    }
+
+    #[test]
+    fn it_should_reject_empty_in_list() {
+        let selector = Selector::field("region").in_list(Vec::<&str>::new());
+
+        assert_eq!(
+            Err(SelectorError::EmptyInList("region".to_owned())),
+            selector.build()
+        );
+    }
+
+    #[test]
+    fn it_should_render_between() {
+        let selector = Selector::field("price").between(1, 5);
+
+        assert_eq!("price BETWEEN 1 AND 5", selector.build().unwrap());
+    }
+
+    #[test]
+    fn it_should_reject_non_finite_numbers() {
+        let selector = Selector::field("price").gt(f64::NAN);
+
+        assert!(matches!(
+            selector.build(),
+            Err(SelectorError::InvalidNumber(_))
+        ));
+    }
+}
@@ -1,23 +1,78 @@
+pub mod batch;
+pub mod bridge;
 pub mod builder;
+pub(crate) mod callback;
+pub mod client_name;
+pub mod config;
+pub mod dispatch;
 pub mod event;
+pub mod forward;
+pub mod guaranteed_request;
+pub mod health;
+pub mod metrics;
+pub mod publisher;
+pub mod rate_limit;
+pub mod reconnect;
+pub(crate) mod state;
+pub mod subscription;
+pub mod sync_session;
+pub mod token;
 
-pub use builder::{SessionBuilder, SessionBuilderError};
-pub use event::SessionEvent;
+pub use batch::{BatchOutcome, BatchResult};
+pub use bridge::{Bridge, BridgeBuilder, BridgeStats};
+pub use builder::{
+    AckEventMode, ResubscribeStrategy, SessionBuilder, SessionBuilderError, UnbindFailAction,
+};
+pub use client_name::ClientName;
+pub use config::SessionConfig;
+pub use dispatch::ParallelDispatch;
+pub use event::{DisconnectReason, SessionEvent, SessionEventInfo, Severity};
+pub use forward::ForwardOptions;
+pub use guaranteed_request::GuaranteedRequester;
+pub use health::{BufferUsage, ConnectionInfo, ConnectionState, SessionHealth};
+pub use metrics::SessionMetrics;
+pub use publisher::{PublishReceipt, PublisherHandle};
+pub use rate_limit::RateLimit;
+pub use reconnect::{Backoff, ReconnectError, ReconnectEvent, ReconnectingSession};
+pub use subscription::{SubscribeOutcome, SubscriptionHandle};
+pub use sync_session::SyncSession;
+pub use token::{Token, TokenError, TokenProvider};
 
-use crate::cache_session::CacheSession;
-use crate::context::Context;
-use crate::message::{InboundMessage, Message, OutboundMessage};
-use crate::util::get_last_error_info;
+use crate::cache_session::{CacheSession, CacheSessionBuilder, CacheSessionBuilderError};
+use crate::context::{CallbackKind, Context};
+use crate::instrument;
+use crate::message::codec;
+use crate::message::{
+    DeliveryMode, DestinationType, InboundMessage, MessageDestination, MessageRead, MessageWrite,
+    OutboundMessage, OutboundMessageBuilder,
+};
+use crate::props::PropList;
+use crate::util::{self, get_last_error_info};
 use crate::SessionError;
 use crate::SolClientReturnCode;
+use callback::{EventHandler, MessageHandler};
 use solace_rs_sys::{self as ffi, solClient_opaqueMsg_pt};
-use std::ffi::CString;
+use state::SessionState;
+use std::ffi::{c_void, CStr, CString};
+use std::fmt;
 use std::marker::PhantomData;
 use std::num::NonZeroU32;
-use tracing::warn;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use subscription::SubscriptionOp;
+use token::TokenRefresh;
+use tracing::{info, warn};
 
 type Result<T> = std::result::Result<T, SessionError>;
 
+pub type OwnedMessageCallback = Box<dyn FnMut(InboundMessage) + Send + 'static>;
+pub type OwnedEventCallback = Box<dyn FnMut(SessionEvent) + Send + 'static>;
+
+/// Session without a borrowed lifetime or closure type parameters, built with
+/// [`SessionBuilder::build_owned`]. It can be boxed, moved into spawned threads and stored in
+/// long lived structs.
+pub type OwnedSession = Session<'static, OwnedMessageCallback, OwnedEventCallback>;
+
 pub struct Session<
     'session,
     M: FnMut(InboundMessage) + Send + 'session,
@@ -34,74 +89,533 @@ pub struct Session<
     pub(crate) context: Context,
 
     // These fields are used to store the fn callback. The mutable reference to this fn is passed to the FFI library,
-    #[allow(dead_code, clippy::redundant_allocation)]
-    _msg_fn_ptr: Option<Box<Box<M>>>,
-    #[allow(dead_code, clippy::redundant_allocation)]
-    _event_fn_ptr: Option<Box<Box<E>>>,
+    #[allow(dead_code)]
+    _msg_fn_ptr: Option<Box<MessageHandler<M>>>,
+    #[allow(dead_code)]
+    _event_fn_ptr: Box<EventHandler<E>>,
+
+    pub(crate) state: Arc<SessionState>,
+    // checks the unacked messages for the ack timeout, stopped before the state can be dropped
+    pub(crate) ack_timer: Option<ffi::solClient_context_timerId_t>,
+    // stops the token refresh thread, if the session has a token provider
+    pub(crate) token_refresh: Option<Arc<TokenRefresh>>,
 }
 
+// the C session can be used from any thread and the callbacks are `Send`. It is not `Sync`
+// since it would require the callbacks to be `Sync`, see `SyncSession` to share it instead.
 unsafe impl<M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send> Send
     for Session<'_, M, E>
 {
 }
 
+impl<M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send> fmt::Debug
+    for Session<'_, M, E>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let health = self.state.health(None);
+        f.debug_struct("Session")
+            .field("id", &self.state.id())
+            .field("client_name", &self.state.client_name())
+            .field("state", &health.state)
+            .field("host", &health.host)
+            .finish_non_exhaustive()
+    }
+}
+
 impl<'session, M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send>
     Session<'session, M, E>
 {
-    pub fn publish(&self, message: OutboundMessage) -> Result<()> {
-        let send_message_raw_rc = unsafe {
-            ffi::solClient_session_sendMsg(self._session_ptr, message.get_raw_message_ptr())
+    pub fn publish(&self, message: &OutboundMessage) -> Result<()> {
+        send_message(self._session_ptr, &self.state, message)
+    }
+
+    /// Publishes the messages in order and reports the outcome of each one. Publishing stops at
+    /// the first message that is not sent, so that the batch stays in order; it and the
+    /// following messages are reported as not sent, see [`BatchResult::unsent`], and can be
+    /// published again.
+    pub fn publish_batch(&self, messages: &[OutboundMessage]) -> BatchResult {
+        batch::send_batch(self._session_ptr, &self.state, messages)
+    }
+
+    /// Publishes a message built outside of this crate, see [`OutboundMessage::from_raw`] to
+    /// hand over its ownership instead. The message goes through the same codec, rate limit and
+    /// acknowledgement tracking as [`Session::publish`], so its payload and correlation tag may
+    /// be changed.
+    ///
+    /// # Safety
+    /// `msg_ptr` must be a valid message that is not used by another thread during the call. The
+    /// caller keeps ownership of it.
+    pub unsafe fn publish_raw(&self, msg_ptr: ffi::solClient_opaqueMsg_pt) -> Result<()> {
+        let message = std::mem::ManuallyDrop::new(OutboundMessage::from_raw(msg_ptr));
+        send_message(self._session_ptr, &self.state, &message)
+    }
+
+    /// Publishes a guaranteed message and returns a receipt to wait for its acknowledgement or
+    /// rejection.
+    ///
+    /// The message's correlation tag is overwritten to match the outcome to the message. Direct
+    /// messages are never acknowledged, so their receipt only ever times out.
+    pub fn publish_tracked(&self, message: &OutboundMessage) -> Result<PublishReceipt> {
+        send_tracked(self._session_ptr, &self.state, message)
+    }
+
+    /// Pointer to the C session, to call functions of the C library that are not wrapped yet.
+    ///
+    /// # Safety
+    /// The pointer is only valid while the session is borrowed. It must not be destroyed,
+    /// disconnected or used to replace the callbacks of the session.
+    pub unsafe fn as_raw(&self) -> ffi::solClient_opaqueSession_pt {
+        self._session_ptr
+    }
+
+    /// Cheap handle to publish from other threads without wrapping the session in a mutex.
+    ///
+    /// The handle can outlive the session, in which case publishing returns
+    /// [`SessionError::SessionClosed`].
+    pub fn publisher_handle(&self) -> PublisherHandle {
+        PublisherHandle::new(self._session_ptr, self.state.clone())
+    }
+
+    /// Publishes the same message to every destination.
+    ///
+    /// The message is built once and only its destination is swapped between sends. Any
+    /// destination set on the builder is ignored. Publishing stops at the first failure, in which
+    /// case the message was already sent to the preceding destinations.
+    pub fn publish_to_all(
+        &self,
+        builder: OutboundMessageBuilder,
+        destinations: &[MessageDestination],
+    ) -> Result<()> {
+        let Some((first, rest)) = destinations.split_first() else {
+            return Ok(());
         };
 
-        let rc = SolClientReturnCode::from_raw(send_message_raw_rc);
-        if !rc.is_ok() {
-            let subcode = get_last_error_info();
-            return Err(SessionError::PublishError(rc, subcode));
+        let mut message = builder.destination(first.clone()).build()?;
+        self.publish(&message)?;
+
+        for destination in rest {
+            message.set_destination(destination)?;
+            self.publish(&message)?;
         }
 
         Ok(())
     }
 
-    pub fn subscribe<T>(&self, topic: T) -> Result<()>
+    /// Publishes a copy of a received message to another destination. Unlike building a new
+    /// message from the payload, the copy keeps every header and the structured data of the
+    /// user properties, unless removed with the options.
+    pub fn forward(
+        &self,
+        message: &InboundMessage,
+        destination: MessageDestination,
+        options: ForwardOptions,
+    ) -> Result<()> {
+        let message = forward::forwarded(message, &destination, options)?;
+        self.publish(&message)
+    }
+
+    /// Number of guaranteed messages published on this session that have not yet been
+    /// acknowledged or rejected by the broker.
+    pub fn unacked_messages(&self) -> u64 {
+        self.state.unacked()
+    }
+
+    /// Maximum number of guaranteed messages that can be in-flight before the broker
+    /// acknowledges them. Configured through `SOLCLIENT_SESSION_PROP_PUB_WINDOW_SIZE`.
+    pub fn publisher_window_size(&self) -> Result<u64> {
+        let name = "pub_window_size";
+        self.get_property(ffi::SOLCLIENT_SESSION_PROP_PUB_WINDOW_SIZE)?
+            .parse()
+            .map_err(|_| SessionError::PropertyConversionError(name.to_owned()))
+    }
+
+    /// Blocks until the publisher window has room for another guaranteed message or the timeout
+    /// expires. Returns `true` if a message can be published without exceeding the window.
+    ///
+    /// The window opens as `Acknowledgement` and `RejectedMsgError` events are received for
+    /// in-flight messages.
+    pub fn wait_for_publisher_window(&self, timeout: Duration) -> Result<bool> {
+        let window_size = self.publisher_window_size()?;
+        Ok(self.state.wait_for_window(window_size, timeout))
+    }
+
+    /// Like [`Session::wait_for_publisher_window`], waiting until the deadline.
+    pub fn wait_for_publisher_window_with_deadline(&self, deadline: Instant) -> Result<bool> {
+        self.wait_for_publisher_window(util::remaining(deadline))
+    }
+
+    /// Client name the session is connected with. If no client name was configured, this is the
+    /// name generated by the C library.
+    pub fn client_name(&self) -> Result<String> {
+        self.get_property(ffi::SOLCLIENT_SESSION_PROP_CLIENT_NAME)
+    }
+
+    /// Process unique identifier of the session. It is included in the log lines emitted for
+    /// this session.
+    pub fn session_id(&self) -> u64 {
+        self.state.id()
+    }
+
+    /// Cheap synchronous snapshot of the session health, suitable for liveness probes.
+    pub fn health(&self) -> SessionHealth {
+        let rx_ctl_msgs = self
+            .get_rx_stat(ffi::solClient_stats_rx_SOLCLIENT_STATS_RX_CTL_MSGS)
+            .ok();
+        self.state.health(rx_ctl_msgs)
+    }
+
+    /// Snapshot of the message counters of the session.
+    pub fn metrics(&self) -> SessionMetrics {
+        self.state.metrics()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.health().is_connected()
+    }
+
+    /// Configured send buffer size along with the memory used by the message pool, to monitor the
+    /// memory pressure caused by buffering.
+    pub fn buffer_usage(&self) -> Result<BufferUsage> {
+        let name = "buffer_size";
+        let buffer_size = self
+            .get_property(ffi::SOLCLIENT_SESSION_PROP_BUFFER_SIZE)?
+            .parse()
+            .map_err(|_| SessionError::PropertyConversionError(name.to_owned()))?;
+
+        Ok(BufferUsage {
+            buffer_size,
+            total_memory: get_msg_stat(ffi::solClient_msg_stats_SOLCLIENT_MSG_STATS_TOTAL_MEMORY)?,
+            allocated_memory: get_msg_stat(
+                ffi::solClient_msg_stats_SOLCLIENT_MSG_STATS_ALLOC_MEMORY,
+            )?,
+        })
+    }
+
+    /// Connection parameters and broker details of the connected session, useful to include in
+    /// support tickets.
+    pub fn connection_info(&self) -> Result<ConnectionInfo> {
+        Ok(ConnectionInfo {
+            keep_alive_interval_ms: self.get_parsed_property(
+                ffi::SOLCLIENT_SESSION_PROP_KEEP_ALIVE_INT_MS,
+                "keep_alive_int_ms",
+            )?,
+            keep_alive_limit: self.get_parsed_property(
+                ffi::SOLCLIENT_SESSION_PROP_KEEP_ALIVE_LIMIT,
+                "keep_alive_limit",
+            )?,
+            compression_level: self.get_parsed_property(
+                ffi::SOLCLIENT_SESSION_PROP_COMPRESSION_LEVEL,
+                "compression_level",
+            )?,
+            broker_version: self
+                .get_string_capability(ffi::SOLCLIENT_SESSION_PEER_SOFTWARE_VERSION),
+            broker_platform: self.get_string_capability(ffi::SOLCLIENT_SESSION_PEER_PLATFORM),
+            router_name: self.get_string_capability(ffi::SOLCLIENT_SESSION_PEER_ROUTER_NAME),
+            message_eliding: self
+                .get_bool_capability(ffi::SOLCLIENT_SESSION_CAPABILITY_MESSAGE_ELIDING),
+        })
+    }
+
+    /// Topic of the session's P2P inbox. Replies to messages that carry this destination as their
+    /// reply-to are delivered directly to this session.
+    pub fn inbox_destination(&self) -> Result<MessageDestination> {
+        let name = "p2p_inbox";
+        let inbox = self.get_property(ffi::SOLCLIENT_SESSION_PROP_P2PINBOX_IN_USE)?;
+
+        if inbox.is_empty() {
+            return Err(SessionError::PropertyConversionError(name.to_owned()));
+        }
+
+        MessageDestination::new(DestinationType::Topic, inbox)
+            .map_err(|_| SessionError::PropertyConversionError(name.to_owned()))
+    }
+
+    fn get_rx_stat(&self, stat: ffi::solClient_stats_rx_t) -> Result<u64> {
+        let mut value: u64 = 0;
+        let rc = unsafe { ffi::solClient_session_getRxStat(self._session_ptr, stat, &mut value) };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        rc.to_result(SessionError::StatsError)?;
+
+        Ok(value)
+    }
+
+    fn get_parsed_property<T: std::str::FromStr>(&self, name: &[u8], field: &str) -> Result<T> {
+        self.get_property(name)?
+            .parse()
+            .map_err(|_| SessionError::PropertyConversionError(field.to_owned()))
+    }
+
+    // capabilities are only known once connected, None if the broker did not report it
+    fn get_string_capability(&self, name: &[u8]) -> Option<String> {
+        let mut field: ffi::solClient_field_t = unsafe { std::mem::zeroed() };
+
+        let rc = unsafe {
+            ffi::solClient_session_getCapability(
+                self._session_ptr,
+                name.as_ptr().cast(),
+                &mut field,
+                std::mem::size_of::<ffi::solClient_field_t>(),
+            )
+        };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        if !rc.is_ok() || field.type_ != ffi::solClient_fieldType_SOLCLIENT_STRING {
+            return None;
+        }
+
+        let value = unsafe { field.value.string };
+        if value.is_null() {
+            return None;
+        }
+
+        let value = unsafe { CStr::from_ptr(value) };
+        Some(value.to_string_lossy().into_owned())
+    }
+
+    fn get_bool_capability(&self, name: &[u8]) -> Option<bool> {
+        let mut field: ffi::solClient_field_t = unsafe { std::mem::zeroed() };
+
+        let rc = unsafe {
+            ffi::solClient_session_getCapability(
+                self._session_ptr,
+                name.as_ptr().cast(),
+                &mut field,
+                std::mem::size_of::<ffi::solClient_field_t>(),
+            )
+        };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        if !rc.is_ok() || field.type_ != ffi::solClient_fieldType_SOLCLIENT_BOOL {
+            return None;
+        }
+
+        Some(unsafe { field.value.boolean } != 0)
+    }
+
+    fn get_property(&self, name: &[u8]) -> Result<String> {
+        let mut buffer = [0u8; 256];
+
+        let rc = unsafe {
+            ffi::solClient_session_getProperty(
+                self._session_ptr,
+                name.as_ptr().cast(),
+                buffer.as_mut_ptr().cast(),
+                buffer.len(),
+            )
+        };
+
+        let rc = SolClientReturnCode::from_raw(rc);
+        let name = CStr::from_bytes_until_nul(name)
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        rc.to_result(|rc, subcode| SessionError::PropertyError(name.clone(), rc, subcode))?;
+
+        let value = CStr::from_bytes_until_nul(&buffer)
+            .map_err(|_| SessionError::PropertyConversionError(name))?;
+
+        Ok(value.to_string_lossy().into_owned())
+    }
+
+    /// Subscribes to the topic. Subscribing to a topic the session is already subscribed to sends
+    /// nothing to the broker and returns [`SubscribeOutcome::AlreadySubscribed`].
+    #[cfg_attr(
+        feature = "trace-instrumentation",
+        tracing::instrument(
+            name = "subscribe",
+            skip_all,
+            fields(
+                session_id = self.state.id(),
+                topic = tracing::field::Empty,
+                rc = tracing::field::Empty,
+            )
+        )
+    )]
+    pub fn subscribe<T>(&self, topic: T) -> Result<SubscribeOutcome>
     where
         T: Into<Vec<u8>>,
     {
         let c_topic = CString::new(topic)?;
+        instrument::record_topic(&c_topic);
+        let topic = c_topic.to_string_lossy();
+        if !self.state.add_subscription(&topic) {
+            return Ok(SubscribeOutcome::AlreadySubscribed);
+        }
+
         let subscription_raw_rc =
             unsafe { ffi::solClient_session_topicSubscribe(self._session_ptr, c_topic.as_ptr()) };
 
         let rc = SolClientReturnCode::from_raw(subscription_raw_rc);
+        instrument::record_rc(&rc);
+
+        rc.to_result(|rc, subcode| {
+            self.state.remove_subscription(&topic);
+            SessionError::SubscriptionFailure(topic.clone().into_owned(), rc, subcode)
+        })?;
+
+        Ok(SubscribeOutcome::Subscribed)
+    }
+
+    /// Whether the session subscribed to the topic and did not unsubscribe from it since. The
+    /// topic has to match the subscription exactly, wildcards are not expanded.
+    pub fn is_subscribed<T: AsRef<str>>(&self, topic: T) -> bool {
+        self.state.is_subscribed(topic.as_ref())
+    }
+
+    /// Subscribes to the topic and waits for the broker to confirm the subscription.
+    ///
+    /// Unlike [`Session::subscribe`], which returns as soon as the subscription is sent, the
+    /// result reflects whether the broker accepted the subscription.
+    pub fn subscribe_confirmed<T>(&self, topic: T, timeout: Duration) -> Result<()>
+    where
+        T: Into<Vec<u8>>,
+    {
+        self.subscribe_nonblocking(topic)?.wait(timeout)
+    }
+
+    /// Like [`Session::subscribe_confirmed`], waiting until the deadline.
+    pub fn subscribe_confirmed_with_deadline<T>(&self, topic: T, deadline: Instant) -> Result<()>
+    where
+        T: Into<Vec<u8>>,
+    {
+        self.subscribe_nonblocking(topic)?
+            .wait_with_deadline(deadline)
+    }
+
+    /// Sends the subscription without waiting for the broker and returns a handle to wait for
+    /// the confirmation later, which allows many subscriptions to be in flight at once.
+    ///
+    /// If the session is built with
+    /// [`subscribe_blocking(false)`](SessionBuilder::subscribe_blocking) and the send buffer is
+    /// full, the call fails with a `WouldBlock` return code and can be retried.
+    #[cfg_attr(
+        feature = "trace-instrumentation",
+        tracing::instrument(
+            name = "subscribe_nonblocking",
+            skip_all,
+            fields(
+                session_id = self.state.id(),
+                topic = tracing::field::Empty,
+                rc = tracing::field::Empty,
+            )
+        )
+    )]
+    pub fn subscribe_nonblocking<T>(&self, topic: T) -> Result<SubscriptionHandle>
+    where
+        T: Into<Vec<u8>>,
+    {
+        self.request_subscription(CString::new(topic)?, SubscriptionOp::Subscribe)
+    }
+
+    /// Like [`Session::subscribe_nonblocking`], for removing a subscription.
+    #[cfg_attr(
+        feature = "trace-instrumentation",
+        tracing::instrument(
+            name = "unsubscribe_nonblocking",
+            skip_all,
+            fields(
+                session_id = self.state.id(),
+                topic = tracing::field::Empty,
+                rc = tracing::field::Empty,
+            )
+        )
+    )]
+    pub fn unsubscribe_nonblocking<T>(&self, topic: T) -> Result<SubscriptionHandle>
+    where
+        T: Into<Vec<u8>>,
+    {
+        self.request_subscription(CString::new(topic)?, SubscriptionOp::Unsubscribe)
+    }
+
+    fn request_subscription(
+        &self,
+        c_topic: CString,
+        op: SubscriptionOp,
+    ) -> Result<SubscriptionHandle> {
+        instrument::record_topic(&c_topic);
+        let topic = c_topic.to_string_lossy().into_owned();
+        let tag = self.state.confirmations.register(topic.clone());
+        let tracked = match op {
+            SubscriptionOp::Subscribe => self.state.add_subscription(&topic),
+            SubscriptionOp::Unsubscribe => {
+                self.state.remove_subscription(&topic);
+                false
+            }
+        };
 
-        if !rc.is_ok() {
+        // a null dispatch info delivers the messages to the session callback
+        let raw_rc = unsafe {
+            match op {
+                SubscriptionOp::Subscribe => ffi::solClient_session_topicSubscribeWithDispatch(
+                    self._session_ptr,
+                    ffi::SOLCLIENT_SUBSCRIBE_FLAGS_REQUEST_CONFIRM,
+                    c_topic.as_ptr(),
+                    std::ptr::null_mut(),
+                    tag as *mut c_void,
+                ),
+                SubscriptionOp::Unsubscribe => ffi::solClient_session_topicUnsubscribeWithDispatch(
+                    self._session_ptr,
+                    ffi::SOLCLIENT_SUBSCRIBE_FLAGS_REQUEST_CONFIRM,
+                    c_topic.as_ptr(),
+                    std::ptr::null_mut(),
+                    tag as *mut c_void,
+                ),
+            }
+        };
+
+        let rc = SolClientReturnCode::from_raw(raw_rc);
+        instrument::record_rc(&rc);
+
+        // the handle cancels the tag if the request could not be sent
+        let handle = SubscriptionHandle::new(Arc::clone(&self.state), tag, topic, op, tracked);
+
+        // without waiting for the confirmation the request can still be in progress
+        if !rc.is_ok() && rc != SolClientReturnCode::InProgress {
             let subcode = get_last_error_info();
-            return Err(SessionError::SubscriptionFailure(
-                c_topic.to_string_lossy().into_owned(),
-                rc,
-                subcode,
-            ));
+            let topic = handle.topic().to_owned();
+            if tracked {
+                self.state.remove_subscription(&topic);
+            }
+            return Err(match op {
+                SubscriptionOp::Subscribe => SessionError::SubscriptionFailure(topic, rc, subcode),
+                SubscriptionOp::Unsubscribe => {
+                    SessionError::UnsubscriptionFailure(topic, rc, subcode)
+                }
+            });
         }
-        Ok(())
+
+        Ok(handle)
     }
 
+    #[cfg_attr(
+        feature = "trace-instrumentation",
+        tracing::instrument(
+            name = "unsubscribe",
+            skip_all,
+            fields(
+                session_id = self.state.id(),
+                topic = tracing::field::Empty,
+                rc = tracing::field::Empty,
+            )
+        )
+    )]
     pub fn unsubscribe<T>(&self, topic: T) -> Result<()>
     where
         T: Into<Vec<u8>>,
     {
         let c_topic = CString::new(topic)?;
+        instrument::record_topic(&c_topic);
+        self.state.remove_subscription(&c_topic.to_string_lossy());
         let subscription_raw_rc =
             unsafe { ffi::solClient_session_topicUnsubscribe(self._session_ptr, c_topic.as_ptr()) };
 
         let rc = SolClientReturnCode::from_raw(subscription_raw_rc);
+        instrument::record_rc(&rc);
 
-        if !rc.is_ok() {
-            let subcode = get_last_error_info();
-            return Err(SessionError::UnsubscriptionFailure(
-                c_topic.to_string_lossy().into_owned(),
-                rc,
-                subcode,
-            ));
-        }
-        Ok(())
+        rc.to_result(|rc, subcode| {
+            SessionError::UnsubscriptionFailure(c_topic.to_string_lossy().into_owned(), rc, subcode)
+        })
     }
 
     pub fn request(
@@ -109,35 +623,64 @@ impl<'session, M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send>
         message: OutboundMessage,
         timeout_ms: NonZeroU32,
     ) -> Result<InboundMessage> {
-        let mut reply_ptr: solClient_opaqueMsg_pt = std::ptr::null_mut();
+        send_request(self._session_ptr, &message, timeout_ms)
+    }
 
-        let rc = unsafe {
-            ffi::solClient_session_sendRequest(
-                self._session_ptr,
-                message.get_raw_message_ptr(),
-                &mut reply_ptr,
-                timeout_ms.into(),
-            )
-        };
+    /// Like [`Session::request`], waiting for the reply until the deadline. Returns
+    /// [`SessionError::RequestTimeout`] without sending if the deadline already passed.
+    pub fn request_with_deadline(
+        &self,
+        message: OutboundMessage,
+        deadline: Instant,
+    ) -> Result<InboundMessage> {
+        let timeout_ms = util::remaining_ms(deadline).ok_or(SessionError::RequestTimeout)?;
+        self.request(message, timeout_ms)
+    }
 
-        let rc = SolClientReturnCode::from_raw(rc);
+    /// Sends the request without waiting for the reply, which is delivered to the message
+    /// callback like any other message. The reply-to and correlation id are set by the C
+    /// library, the correlation id is returned to match the reply with
+    /// [`MessageRead::get_correlation_id`].
+    pub fn request_no_wait(&self, message: OutboundMessage) -> Result<String> {
+        send_request_no_wait(self._session_ptr, &message)
+    }
 
-        if !rc.is_ok() {
-            // reply_ptr is always set to null if rc is not Ok
-            // https://docs.solace.com/API-Developer-Online-Ref-Documentation/c/sol_client_8h.html#ac00adf1a9301ebe67fd0790523d5a44b
-            debug_assert!(reply_ptr.is_null());
+    /// Sends a SEMP show request over the message bus, for example
+    /// `<rpc><show><queue><name>*</name></queue></show></rpc>`, and returns the XML reply.
+    ///
+    /// SEMP over the message bus, and show commands over it, have to be enabled on the message
+    /// VPN. A failed command is reported by the broker in the `execute-result` of the reply.
+    pub fn semp_request(&self, xml: &str, timeout_ms: NonZeroU32) -> Result<String> {
+        let router_name = self
+            .get_string_capability(ffi::SOLCLIENT_SESSION_PEER_ROUTER_NAME)
+            .ok_or_else(|| SessionError::PropertyConversionError("router_name".to_owned()))?;
 
-            let subcode = get_last_error_info();
-            return Err(SessionError::RequestError(rc, subcode));
-        }
+        let destination =
+            MessageDestination::new(DestinationType::Topic, format!("#SEMP/{router_name}/SHOW"))?;
+        let request =
+            OutboundMessage::from_parts(&destination, DeliveryMode::Direct, xml.as_bytes())?;
 
-        debug_assert!(!reply_ptr.is_null());
+        let reply = self.request(request, timeout_ms)?;
+        let payload = reply.get_payload()?.unwrap_or_default();
+        Ok(String::from_utf8_lossy(payload).into_owned())
+    }
+
+    /// Binds a temporary queue for guaranteed request/reply, see [`GuaranteedRequester`].
+    pub fn guaranteed_requester(&self) -> Result<GuaranteedRequester<'_>> {
+        GuaranteedRequester::new(self)
+    }
 
-        let reply = InboundMessage::from(reply_ptr);
+    /// Wraps the session to share it between threads, see [`SyncSession`].
+    pub fn into_sync(self) -> SyncSession<'session, M, E> {
+        SyncSession::new(self)
+    }
 
-        Ok(reply)
+    pub fn cache_session_builder(self) -> CacheSessionBuilder<'session, M, E> {
+        CacheSessionBuilder::new(self)
     }
 
+    /// Shorthand for [`Session::cache_session_builder`]. Unset options keep the C library
+    /// defaults.
     pub fn cache_session<N>(
         self,
         cache_name: N,
@@ -148,7 +691,33 @@ impl<'session, M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send>
     where
         N: Into<Vec<u8>>,
     {
-        CacheSession::new(self, cache_name, max_message, max_age, timeout_ms)
+        let mut builder = self.cache_session_builder().cache_name(cache_name);
+
+        if let Some(max_message) = max_message {
+            builder = builder.max_messages(max_message);
+        }
+        if let Some(max_age) = max_age {
+            builder = builder.max_age(max_age);
+        }
+        if let Some(timeout_ms) = timeout_ms {
+            builder = builder.timeout_ms(timeout_ms);
+        }
+
+        builder.build().map_err(|e| match e {
+            CacheSessionBuilderError::InitializationFailure(rc, subcode) => {
+                SessionError::InitializationFailure(rc, subcode)
+            }
+            // keeps the name of the offending field
+            e => SessionError::CacheSessionBuildError(e),
+        })
+    }
+
+    /// Replaces the OAuth access token, which is used the next time the session connects or
+    /// reconnects. Sessions built with a
+    /// [`SessionBuilder::token_provider`](builder::SessionBuilder::token_provider) refresh their
+    /// token on their own.
+    pub fn set_oauth2_access_token(&self, access_token: &str) -> Result<()> {
+        set_oauth2_access_token(self._session_ptr, access_token)
     }
 
     pub fn disconnect(self) -> Result<()> {
@@ -156,21 +725,404 @@ impl<'session, M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send>
 
         let rc = SolClientReturnCode::from_raw(rc);
 
-        if !rc.is_ok() {
-            let subcode = get_last_error_info();
-            return Err(SessionError::DisconnectError(rc, subcode));
-        }
-        Ok(())
+        rc.to_result(SessionError::DisconnectError)
     }
 }
 
 impl<M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send> Drop for Session<'_, M, E> {
     fn drop(&mut self) {
+        if let Some(token_refresh) = &self.token_refresh {
+            token_refresh.stop();
+        }
+        if let Some(mut timer_id) = self.ack_timer.take() {
+            unsafe { ffi::solClient_context_stopTimer(self.context.as_raw(), &mut timer_id) };
+        }
+
+        // waits for publisher handles that are sending on the session and stops dispatching to
+        // the callbacks, which are dropped with the session
+        self.state.close();
+        self.state.wait_for_callbacks();
+        self.context.unregister_session(self.state.id());
+
         let session_free_result = unsafe { ffi::solClient_session_destroy(&mut self._session_ptr) };
         let rc = SolClientReturnCode::from_raw(session_free_result);
 
-        if !rc.is_ok() {
-            warn!("session was not dropped properly. {rc}");
+        if let Err(subcode) = rc.to_result(|_, subcode| subcode) {
+            warn!(
+                session_id = self.state.id(),
+                client_name = self.state.client_name(),
+                "session was not dropped properly. {rc} subcode: {subcode}"
+            );
+        }
+    }
+}
+
+// message pool stats are global to the process, so they are not tied to a session
+fn get_msg_stat(stat: ffi::solClient_msg_stats_t) -> Result<u64> {
+    let mut value: u64 = 0;
+    let rc = unsafe { ffi::solClient_msg_getStat(stat, 0, &mut value) };
+
+    let rc = SolClientReturnCode::from_raw(rc);
+    rc.to_result(SessionError::StatsError)?;
+
+    Ok(value)
+}
+
+// shared by the session and its token refresh thread
+pub(crate) fn set_oauth2_access_token(
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    access_token: &str,
+) -> Result<()> {
+    let access_token = CString::new(access_token)?;
+    let mut props = PropList::new();
+    props.push(
+        ffi::SOLCLIENT_SESSION_PROP_OAUTH2_ACCESS_TOKEN,
+        &access_token,
+    );
+
+    let rc = unsafe { ffi::solClient_session_modifyProperties(session_ptr, props.as_mut_ptr()) };
+
+    let rc = SolClientReturnCode::from_raw(rc);
+    rc.to_result(|rc, subcode| {
+        SessionError::ModifyPropertyError("oauth2_access_token".to_owned(), rc, subcode)
+    })
+}
+
+// shared by the session and its publisher handles
+pub(crate) fn send_message(
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    state: &SessionState,
+    message: &OutboundMessage,
+) -> Result<()> {
+    send_with_tag(session_ptr, state, message, None)
+}
+
+pub(crate) fn send_tracked(
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    state: &Arc<SessionState>,
+    message: &OutboundMessage,
+) -> Result<PublishReceipt> {
+    let name = match message.get_destination() {
+        Ok(Some(destination)) => destination.dest.to_string_lossy().into_owned(),
+        _ => String::new(),
+    };
+    let tag = state.confirmations.register(name);
+
+    // the receipt cancels the tag if sending fails
+    let receipt = PublishReceipt::new(Arc::clone(state), tag);
+    send_with_tag(session_ptr, state, message, Some(tag))?;
+
+    Ok(receipt)
+}
+
+#[cfg_attr(
+    feature = "trace-instrumentation",
+    tracing::instrument(
+        name = "publish",
+        skip_all,
+        fields(
+            session_id = state.id(),
+            destination = tracing::field::Empty,
+            delivery_mode = tracing::field::Empty,
+            payload_size = tracing::field::Empty,
+            rc = tracing::field::Empty,
+        )
+    )
+)]
+fn send_with_tag(
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    state: &SessionState,
+    message: &OutboundMessage,
+    tag: Option<usize>,
+) -> Result<()> {
+    if let Some(codec) = state.codec() {
+        codec::encode(codec, message)?;
+    }
+    instrument::record_message(message);
+
+    if let Some(limiter) = state.rate_limiter() {
+        let bytes = if limiter.limits_bytes() {
+            message.get_payload().ok().flatten().map_or(0, <[u8]>::len)
+        } else {
+            0
+        };
+        limiter.acquire(bytes);
+    }
+
+    // guaranteed messages are tagged so that the broker acknowledgements can be matched to them.
+    // They are counted before sending since the ack can arrive on the context thread before
+    // sendMsg returns
+    let guaranteed = !matches!(message.get_delivery_mode(), Ok(DeliveryMode::Direct));
+    let tag = match tag {
+        Some(tag) => tag,
+        None if guaranteed => state.confirmations.new_tag(),
+        // direct messages are never acknowledged
+        None => 0,
+    };
+    if guaranteed {
+        set_correlation_tag(message, tag)?;
+        if state.retains_unacked() {
+            state.retain(tag, message.try_clone()?);
+        }
+        state.guaranteed_message_sent(tag);
+    }
+
+    // publisher handles keep the session from being destroyed only while sending, not while
+    // waiting on the rate limit
+    let Some(open) = state.open() else {
+        if guaranteed {
+            state.guaranteed_message_settled(tag);
+        }
+        return Err(SessionError::SessionClosed);
+    };
+    let send_message_raw_rc =
+        unsafe { ffi::solClient_session_sendMsg(session_ptr, message.get_raw_message_ptr()) };
+    drop(open);
+
+    let rc = SolClientReturnCode::from_raw(send_message_raw_rc);
+    instrument::record_rc(&rc);
+    if let Err(e) = rc.to_result(SessionError::PublishError) {
+        if guaranteed {
+            state.guaranteed_message_settled(tag);
+        }
+        return Err(e);
+    }
+
+    state.message_published();
+
+    Ok(())
+}
+
+/// Starts the timer checking the unacked messages for the ack timeout of the session, if it has
+/// one. The state must outlive the timer.
+pub(crate) fn start_ack_timer(
+    context: &Context,
+    state: &Arc<SessionState>,
+) -> std::result::Result<Option<ffi::solClient_context_timerId_t>, SolClientReturnCode> {
+    let Some(ack_timeout) = state.ack_timeout() else {
+        return Ok(None);
+    };
+    let interval_ms = (ack_timeout / 4).as_millis().clamp(1, 1_000) as u32;
+
+    let mut timer_id: ffi::solClient_context_timerId_t = 0;
+    let rc = unsafe {
+        ffi::solClient_context_startTimer(
+            context.as_raw(),
+            ffi::solClient_context_timerMode_SOLCLIENT_CONTEXT_TIMER_REPEAT,
+            interval_ms,
+            Some(on_ack_timer),
+            Arc::as_ptr(state) as *mut c_void,
+            &mut timer_id,
+        )
+    };
+    SolClientReturnCode::from_raw(rc).ok_or_else(|rc| rc)?;
+
+    Ok(Some(timer_id))
+}
+
+unsafe extern "C" fn on_ack_timer(
+    _context_p: ffi::solClient_opaqueContext_pt,
+    user_p: *mut c_void,
+) {
+    let state = unsafe { &*(user_p as *const SessionState) };
+    let Some(_guard) = state.enter_callback(CallbackKind::Timer) else {
+        return;
+    };
+    state.expire_unacked(std::time::Instant::now());
+}
+
+/// Republishes the retained copies of the unacked messages, called from the context thread on a
+/// [`SessionEvent::RepublishUnackedMessages`] event. The messages keep their correlation tag, so
+/// that pending confirmations are resolved by the acknowledgement of the republished message.
+///
+/// The codec and rate limit were applied when the messages were first published, and blocking
+/// the context thread on the rate limiter would stall the acknowledgements it waits for.
+pub(crate) fn republish_unacked(
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    state: &SessionState,
+    messages: Vec<(usize, OutboundMessage)>,
+) {
+    if messages.is_empty() {
+        return;
+    }
+
+    let total = messages.len();
+    let mut failed = 0;
+    for (tag, message) in messages {
+        // the copy is retained again in case the session has to republish once more
+        let retained = message.try_clone();
+        if let Ok(copy) = retained {
+            state.retain(tag, copy);
+        }
+        state.guaranteed_message_sent(tag);
+
+        let rc =
+            unsafe { ffi::solClient_session_sendMsg(session_ptr, message.get_raw_message_ptr()) };
+        let rc = SolClientReturnCode::from_raw(rc);
+        instrument::record_rc(&rc);
+        if let Err(subcode) = rc.to_result(|_, subcode| subcode) {
+            failed += 1;
+            state.republish_failed(
+                tag,
+                format!("failed to republish message: {rc} subcode: {subcode}"),
+            );
+        }
+    }
+
+    let republished = total - failed;
+    state.messages_republished(republished as u64);
+    if failed == 0 {
+        info!(
+            session_id = state.id(),
+            client_name = state.client_name(),
+            "republished {republished} unacked messages"
+        );
+    } else {
+        warn!(
+            session_id = state.id(),
+            client_name = state.client_name(),
+            "republished {republished} of {total} unacked messages, {failed} failed"
+        );
+    }
+}
+
+/// Reapplies the tracked subscriptions after a reconnect, called from the context thread on a
+/// [`SessionEvent::ReconnectedNotice`] event with [`ResubscribeStrategy::Managed`]. The
+/// subscriptions are sent without waiting for the broker, which would block the context thread.
+pub(crate) fn resubscribe(session_ptr: ffi::solClient_opaqueSession_pt, state: &SessionState) {
+    let subscriptions = state.subscriptions();
+    let mut failed = 0;
+    for topic in &subscriptions {
+        let Ok(c_topic) = CString::new(topic.as_str()) else {
+            continue;
+        };
+        let rc =
+            unsafe { ffi::solClient_session_topicSubscribeExt(session_ptr, 0, c_topic.as_ptr()) };
+        let rc = SolClientReturnCode::from_raw(rc);
+        instrument::record_rc(&rc);
+        if let Err(subcode) = rc.to_result(|_, subcode| subcode) {
+            failed += 1;
+            warn!(
+                session_id = state.id(),
+                client_name = state.client_name(),
+                "failed to reapply subscription on topic {topic}. {rc} subcode: {subcode}"
+            );
         }
     }
+
+    info!(
+        session_id = state.id(),
+        client_name = state.client_name(),
+        "reapplied {} of {} subscriptions",
+        subscriptions.len() - failed,
+        subscriptions.len()
+    );
+}
+
+fn set_correlation_tag(message: &OutboundMessage, tag: usize) -> Result<()> {
+    // a size of 0 stores the pointer itself, the tag is never dereferenced
+    let rc = unsafe {
+        ffi::solClient_msg_setCorrelationTagPtr(
+            message.get_raw_message_ptr(),
+            tag as *mut c_void,
+            0,
+        )
+    };
+
+    SolClientReturnCode::from_raw(rc).to_result(SessionError::PublishError)
+}
+
+#[cfg_attr(
+    feature = "trace-instrumentation",
+    tracing::instrument(
+        name = "request_no_wait",
+        skip_all,
+        fields(
+            destination = tracing::field::Empty,
+            delivery_mode = tracing::field::Empty,
+            payload_size = tracing::field::Empty,
+            rc = tracing::field::Empty,
+        )
+    )
+)]
+pub(crate) fn send_request_no_wait(
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    message: &OutboundMessage,
+) -> Result<String> {
+    instrument::record_message(message);
+
+    // without a reply pointer the reply is delivered to the message callback
+    let rc = unsafe {
+        ffi::solClient_session_sendRequest(
+            session_ptr,
+            message.get_raw_message_ptr(),
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+
+    let rc = SolClientReturnCode::from_raw(rc);
+    instrument::record_rc(&rc);
+
+    if !matches!(
+        rc,
+        SolClientReturnCode::Ok | SolClientReturnCode::InProgress
+    ) {
+        let subcode = get_last_error_info();
+        return Err(SessionError::RequestError(rc, subcode));
+    }
+
+    // the correlation id is set on the request by the C library
+    let correlation_id = message.get_correlation_id()?.unwrap_or_default();
+    Ok(correlation_id.to_owned())
+}
+
+#[cfg_attr(
+    feature = "trace-instrumentation",
+    tracing::instrument(
+        name = "request",
+        skip_all,
+        fields(
+            timeout_ms = timeout_ms.get(),
+            destination = tracing::field::Empty,
+            delivery_mode = tracing::field::Empty,
+            payload_size = tracing::field::Empty,
+            rc = tracing::field::Empty,
+        )
+    )
+)]
+pub(crate) fn send_request(
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    message: &OutboundMessage,
+    timeout_ms: NonZeroU32,
+) -> Result<InboundMessage> {
+    instrument::record_message(message);
+
+    let mut reply_ptr: solClient_opaqueMsg_pt = std::ptr::null_mut();
+
+    let rc = unsafe {
+        ffi::solClient_session_sendRequest(
+            session_ptr,
+            message.get_raw_message_ptr(),
+            &mut reply_ptr,
+            timeout_ms.into(),
+        )
+    };
+
+    let rc = SolClientReturnCode::from_raw(rc);
+    instrument::record_rc(&rc);
+
+    if let Err(e) = rc.to_result(SessionError::RequestError) {
+        // reply_ptr is always set to null if rc is not Ok
+        // https://docs.solace.com/API-Developer-Online-Ref-Documentation/c/sol_client_8h.html#ac00adf1a9301ebe67fd0790523d5a44b
+        debug_assert!(reply_ptr.is_null());
+
+        return Err(e);
+    }
+
+    debug_assert!(!reply_ptr.is_null());
+
+    let reply = InboundMessage::from(reply_ptr);
+
+    Ok(reply)
 }
@@ -0,0 +1,148 @@
+use std::ops::Range;
+
+use solace_rs_sys as ffi;
+
+use super::send_message;
+use super::state::SessionState;
+use crate::message::OutboundMessage;
+use crate::SessionError;
+
+/// What happened to one message of a batch, see
+/// [`Session::publish_batch`](super::Session::publish_batch).
+#[derive(Debug)]
+pub enum BatchOutcome {
+    /// Handed to the C library. Guaranteed messages can still be rejected by the broker.
+    Accepted,
+    /// Not sent since the send buffer of the session was full and sending did not block.
+    WouldBlock,
+    /// Not sent because of the error.
+    Failed(SessionError),
+    /// Not attempted since an earlier message of the batch was not sent.
+    NotAttempted,
+}
+
+impl BatchOutcome {
+    fn from_result(result: Result<(), SessionError>) -> Self {
+        match result {
+            Ok(()) => Self::Accepted,
+            Err(SessionError::PublishError(rc, _)) if rc.is_would_block() => Self::WouldBlock,
+            Err(e) => Self::Failed(e),
+        }
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Self::Accepted)
+    }
+}
+
+/// Outcome of every message of a batch, in the order of the batch.
+#[derive(Debug)]
+pub struct BatchResult {
+    outcomes: Vec<BatchOutcome>,
+}
+
+impl BatchResult {
+    /// Batch of which no message could be sent because of the error.
+    pub(crate) fn failed(error: SessionError, len: usize) -> Self {
+        let mut outcomes: Vec<_> = (0..len).map(|_| BatchOutcome::NotAttempted).collect();
+        if let Some(first) = outcomes.first_mut() {
+            *first = BatchOutcome::Failed(error);
+        }
+        Self { outcomes }
+    }
+
+    pub fn outcomes(&self) -> &[BatchOutcome] {
+        &self.outcomes
+    }
+
+    /// Number of messages handed to the C library, which are always the first ones of the batch.
+    pub fn accepted(&self) -> usize {
+        self.outcomes.iter().take_while(|o| o.is_accepted()).count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.accepted() == self.outcomes.len()
+    }
+
+    /// Indices of the messages that were not sent and need to be published again.
+    pub fn unsent(&self) -> Range<usize> {
+        self.accepted()..self.outcomes.len()
+    }
+
+    pub fn into_outcomes(self) -> Vec<BatchOutcome> {
+        self.outcomes
+    }
+}
+
+// shared by the session and its publisher handles
+pub(crate) fn send_batch(
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    state: &SessionState,
+    messages: &[OutboundMessage],
+) -> BatchResult {
+    let mut outcomes = Vec::with_capacity(messages.len());
+    let mut messages = messages.iter();
+
+    // later messages are not sent after a failure so that the batch stays in order
+    for message in messages.by_ref() {
+        let outcome = BatchOutcome::from_result(send_message(session_ptr, state, message));
+        let accepted = outcome.is_accepted();
+        outcomes.push(outcome);
+        if !accepted {
+            break;
+        }
+    }
+    outcomes.extend(messages.map(|_| BatchOutcome::NotAttempted));
+
+    BatchResult { outcomes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SolClientReturnCode, SolClientSubCode};
+
+    fn subcode() -> SolClientSubCode {
+        SolClientSubCode {
+            subcode: 0,
+            error_string: String::new(),
+        }
+    }
+
+    #[test]
+    fn it_should_classify_would_block() {
+        let would_block = SessionError::PublishError(SolClientReturnCode::WouldBlock, subcode());
+        assert!(matches!(
+            BatchOutcome::from_result(Err(would_block)),
+            BatchOutcome::WouldBlock
+        ));
+
+        let failed = SessionError::PublishError(SolClientReturnCode::Fail, subcode());
+        assert!(matches!(
+            BatchOutcome::from_result(Err(failed)),
+            BatchOutcome::Failed(SessionError::PublishError(SolClientReturnCode::Fail, _))
+        ));
+        assert!(BatchOutcome::from_result(Ok(())).is_accepted());
+    }
+
+    #[test]
+    fn it_should_report_the_unsent_messages() {
+        let result = BatchResult {
+            outcomes: vec![
+                BatchOutcome::Accepted,
+                BatchOutcome::Accepted,
+                BatchOutcome::WouldBlock,
+                BatchOutcome::NotAttempted,
+            ],
+        };
+        assert_eq!(2, result.accepted());
+        assert_eq!(2..4, result.unsent());
+        assert!(!result.is_complete());
+
+        let result = BatchResult {
+            outcomes: vec![BatchOutcome::Accepted],
+        };
+        assert!(result.is_complete());
+        assert!(result.unsent().is_empty());
+    }
+}
@@ -0,0 +1,332 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use tracing::warn;
+
+use super::forward::{self, ForwardOptions};
+use super::{PublisherHandle, Session, SessionEvent};
+use crate::message::{
+    DestinationType, InboundMessage, MessageDestination, MessageRead, OutboundMessage,
+};
+use crate::SessionError;
+
+type Transform = Box<dyn Fn(&mut OutboundMessage) -> bool + Send + Sync>;
+
+/// Relays messages from one session to another, for example between two brokers or message VPNs.
+///
+/// Messages received on topics matching a route are forwarded to the target session, with all
+/// their headers, on the same topic or with a prefix. The callback returned by
+/// [`Bridge::on_message`] is passed to the source session, and [`Bridge::subscribe`] subscribes
+/// the source session to every route.
+///
+/// Reconnects handled by the C library are transparent to the bridge, as long as the source
+/// session reapplies its subscriptions. A session rebuilt after going down, for example by a
+/// [`ReconnectingSession`](super::ReconnectingSession), needs to be handed to the bridge again,
+/// with [`Bridge::subscribe`] and a new [`Bridge::on_message`] callback for a source, or with
+/// [`Bridge::set_target`] for a target.
+///
+/// ```no_run
+/// # use solace_rs::{Context, SolaceLogLevel};
+/// # use solace_rs::message::InboundMessage;
+/// # use solace_rs::session::{Bridge, SessionEvent};
+/// let context = Context::new(SolaceLogLevel::Warning).unwrap();
+/// let target = context
+///     .session(
+///         "tcp://target:55555",
+///         "default",
+///         "default",
+///         "",
+///         None::<fn(InboundMessage)>,
+///         None::<fn(SessionEvent)>,
+///     )
+///     .unwrap();
+///
+/// let bridge = Bridge::builder(target.publisher_handle())
+///     .route("orders/>")
+///     .route_with_prefix("prices/*/eur", "mirror/")
+///     .build();
+///
+/// let source = context
+///     .session_builder()
+///     .host_name("tcp://source:55555")
+///     .vpn_name("default")
+///     .username("default")
+///     .password("")
+///     .on_message(bridge.on_message())
+///     .on_event(|_: SessionEvent| {})
+///     .build()
+///     .unwrap();
+/// bridge.subscribe(&source).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Bridge {
+    relay: Arc<Relay>,
+}
+
+/// Counts of the messages received by a [`Bridge`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BridgeStats {
+    /// Messages published to the target session.
+    pub relayed: u64,
+    /// Messages that did not match any route, for example because the source session has other
+    /// subscriptions.
+    pub unrouted: u64,
+    /// Messages dropped by the transformation callback.
+    pub dropped: u64,
+    /// Messages that could not be copied or published to the target session.
+    pub failed: u64,
+}
+
+pub struct BridgeBuilder {
+    target: PublisherHandle,
+    routes: Vec<Route>,
+    forward_options: ForwardOptions,
+    transform: Option<Transform>,
+}
+
+struct Route {
+    filter: String,
+    prefix: Option<String>,
+}
+
+struct Relay {
+    target: RwLock<PublisherHandle>,
+    routes: Vec<Route>,
+    forward_options: ForwardOptions,
+    transform: Option<Transform>,
+
+    relayed: AtomicU64,
+    unrouted: AtomicU64,
+    dropped: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl Bridge {
+    pub fn builder(target: PublisherHandle) -> BridgeBuilder {
+        BridgeBuilder {
+            target,
+            routes: Vec::new(),
+            forward_options: ForwardOptions::default(),
+            transform: None,
+        }
+    }
+
+    /// Callback relaying the received messages, to pass to
+    /// [`SessionBuilder::on_message`](super::SessionBuilder::on_message) of the source session.
+    /// Every call returns a new callback, so that the source session can be rebuilt.
+    pub fn on_message(&self) -> impl FnMut(InboundMessage) + Send + 'static {
+        let relay = Arc::clone(&self.relay);
+        move |message| relay.relay(message)
+    }
+
+    /// Subscribes the source session to the topic filter of every route.
+    pub fn subscribe<M, E>(&self, source: &Session<'_, M, E>) -> Result<(), SessionError>
+    where
+        M: FnMut(InboundMessage) + Send,
+        E: FnMut(SessionEvent) + Send,
+    {
+        for route in &self.relay.routes {
+            source.subscribe(route.filter.as_str())?;
+        }
+        Ok(())
+    }
+
+    /// Topic filters of the routes, for example to subscribe a
+    /// [`ReconnectingSession`](super::ReconnectingSession) to them.
+    pub fn filters(&self) -> impl Iterator<Item = &str> {
+        self.relay.routes.iter().map(|route| route.filter.as_str())
+    }
+
+    /// Replaces the target session, after it was rebuilt.
+    pub fn set_target(&self, target: PublisherHandle) {
+        *self.relay.target.write().unwrap() = target;
+    }
+
+    pub fn stats(&self) -> BridgeStats {
+        BridgeStats {
+            relayed: self.relay.relayed.load(Ordering::Relaxed),
+            unrouted: self.relay.unrouted.load(Ordering::Relaxed),
+            dropped: self.relay.dropped.load(Ordering::Relaxed),
+            failed: self.relay.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl BridgeBuilder {
+    /// Relays messages on topics matching the filter to the same topic. Filters use the broker
+    /// wildcards, `*` for a level or the end of a level and `>` for all the remaining levels. The
+    /// first matching route is used.
+    pub fn route<T: Into<String>>(mut self, filter: T) -> Self {
+        self.routes.push(Route {
+            filter: filter.into(),
+            prefix: None,
+        });
+        self
+    }
+
+    /// Relays messages on topics matching the filter to the topic with the prefix prepended.
+    pub fn route_with_prefix<T: Into<String>, P: Into<String>>(
+        mut self,
+        filter: T,
+        prefix: P,
+    ) -> Self {
+        self.routes.push(Route {
+            filter: filter.into(),
+            prefix: Some(prefix.into()),
+        });
+        self
+    }
+
+    pub fn forward_options(mut self, forward_options: ForwardOptions) -> Self {
+        self.forward_options = forward_options;
+        self
+    }
+
+    /// Called with every message before it is published to the target session, for example to
+    /// change the destination or payload with [`MessageWrite`](crate::message::MessageWrite).
+    /// Returning `false` drops the message.
+    ///
+    /// The callback runs on the context thread of the source session.
+    pub fn transform<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(&mut OutboundMessage) -> bool + Send + Sync + 'static,
+    {
+        self.transform = Some(Box::new(transform));
+        self
+    }
+
+    pub fn build(self) -> Bridge {
+        Bridge {
+            relay: Arc::new(Relay {
+                target: RwLock::new(self.target),
+                routes: self.routes,
+                forward_options: self.forward_options,
+                transform: self.transform,
+                relayed: AtomicU64::new(0),
+                unrouted: AtomicU64::new(0),
+                dropped: AtomicU64::new(0),
+                failed: AtomicU64::new(0),
+            }),
+        }
+    }
+}
+
+impl Relay {
+    fn relay(&self, message: InboundMessage) {
+        let topic = match message.get_destination() {
+            Ok(Some(destination))
+                if matches!(
+                    destination.dest_type,
+                    DestinationType::Topic | DestinationType::TopicTemp
+                ) =>
+            {
+                destination.dest.to_string_lossy().into_owned()
+            }
+            _ => {
+                self.unrouted.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        };
+
+        let Some(route) = self
+            .routes
+            .iter()
+            .find(|route| topic_matches(&route.filter, &topic))
+        else {
+            self.unrouted.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+
+        let target_topic = match &route.prefix {
+            Some(prefix) => format!("{prefix}{topic}"),
+            None => topic,
+        };
+
+        // the received message is not used anymore, so it is published without a copy
+        let mut outbound = message.into_outbound();
+        let prepared = MessageDestination::new(DestinationType::Topic, target_topic.as_str())
+            .and_then(|destination| {
+                forward::prepare(&mut outbound, &destination, self.forward_options)
+            });
+        if let Err(e) = prepared {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+            warn!("failed to relay message to {target_topic}: {e}");
+            return;
+        }
+
+        if let Some(transform) = &self.transform {
+            if !transform(&mut outbound) {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+
+        let target = self.target.read().unwrap().clone();
+        match target.publish(&outbound) {
+            Ok(()) => {
+                self.relayed.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                warn!("failed to relay message to {target_topic}: {e}");
+            }
+        }
+    }
+}
+
+/// Matches a topic against a subscription filter with the broker wildcards. A `*` ending a level
+/// matches the rest of that level, and a `>` as the last level matches one or more levels.
+fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut topic_levels = topic.split('/');
+    let mut filter_levels = filter.split('/').peekable();
+
+    while let Some(filter_level) = filter_levels.next() {
+        if filter_level == ">" && filter_levels.peek().is_none() {
+            return topic_levels.next().is_some();
+        }
+
+        let Some(topic_level) = topic_levels.next() else {
+            return false;
+        };
+        let matches = match filter_level.strip_suffix('*') {
+            Some(prefix) => topic_level.starts_with(prefix),
+            None => filter_level == topic_level,
+        };
+        if !matches {
+            return false;
+        }
+    }
+
+    topic_levels.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_match_exact_topics() {
+        assert!(topic_matches("a/b/c", "a/b/c"));
+        assert!(!topic_matches("a/b/c", "a/b"));
+        assert!(!topic_matches("a/b", "a/b/c"));
+    }
+
+    #[test]
+    fn it_should_match_level_wildcards() {
+        assert!(topic_matches("a/*/c", "a/b/c"));
+        assert!(topic_matches("a/b*/c", "a/bcd/c"));
+        assert!(!topic_matches("a/b*/c", "a/cd/c"));
+        assert!(!topic_matches("a/*", "a/b/c"));
+        // only a trailing * is a wildcard
+        assert!(!topic_matches("a/*b", "a/cb"));
+        assert!(topic_matches("a/*b", "a/*b"));
+    }
+
+    #[test]
+    fn it_should_match_trailing_wildcard() {
+        assert!(topic_matches("a/>", "a/b"));
+        assert!(topic_matches("a/>", "a/b/c"));
+        assert!(!topic_matches("a/>", "a"));
+        assert!(!topic_matches("a/>/c", "a/b/c"));
+    }
+}
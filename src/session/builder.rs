@@ -1,16 +1,33 @@
 use solace_rs_sys as ffi;
 use std::{
     ffi::{CString, NulError},
+    fmt,
     marker::PhantomData,
-    mem, ptr,
+    mem,
+    net::{IpAddr, SocketAddr},
+    ptr,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 use crate::{
-    message::InboundMessage,
-    session::SessionEvent,
-    util::{get_last_error_info, on_event_trampoline, on_message_trampoline},
+    message::{InboundMessage, PayloadCodec},
+    props::PropList,
+    session::{
+        callback::{AckTimeoutCallback, EventHandler, EventInfoCallback, MessageHandler},
+        client_name,
+        config::SessionConfig,
+        rate_limit::RateLimit,
+        start_ack_timer,
+        state::{SessionState, SessionStateOptions},
+        token::{refresh_at, spawn_token_refresh, TokenError, TokenProvider, TokenRefresh},
+        OwnedEventCallback, OwnedMessageCallback, OwnedSession, SessionEvent, SessionEventInfo,
+    },
+    util::{get_last_error_info, on_event_trampoline, on_message_trampoline, Redacted},
     Context, Session, SolClientReturnCode, SolClientSubCode,
 };
+use tracing::debug;
 
 #[derive(thiserror::Error, Debug)]
 pub enum SessionBuilderError {
@@ -18,24 +35,153 @@ pub enum SessionBuilderError {
     InitializationFailure(SolClientReturnCode, SolClientSubCode),
     #[error("session failed to connect. SolClient return code: {0} subcode: {1}")]
     ConnectionFailure(SolClientReturnCode, SolClientSubCode),
-    #[error("arg contains interior nul byte")]
-    InvalidArgs(#[from] NulError),
+    #[error("{0} arg contains interior nul byte")]
+    InvalidArgs(String, #[source] NulError),
     #[error("{0} arg need to be set")]
     MissingRequiredArgs(String),
     #[error("{0} valid range is {1} foound {2}")]
     InvalidRange(String, String, String),
+    #[error("incompatible args: {0}")]
+    IncompatibleArgs(String),
+    #[error("invalid value {1:?} for {0}")]
+    InvalidConfig(String, String),
+    #[error("token provider failed to fetch a token")]
+    TokenFetchFailure(#[source] TokenError),
+    #[error("failed to spawn the token refresh thread")]
+    TokenRefreshSpawnFailure(#[source] std::io::Error),
 }
 
 type Result<T> = std::result::Result<T, SessionBuilderError>;
 
-fn bool_to_ptr(b: bool) -> *const i8 {
-    if b {
-        ffi::SOLCLIENT_PROP_ENABLE_VAL.as_ptr() as *const i8
-    } else {
-        ffi::SOLCLIENT_PROP_DISABLE_VAL.as_ptr() as *const i8
+fn to_c_string<T: Into<Vec<u8>>>(field: &str, value: T) -> Result<CString> {
+    CString::new(value).map_err(|e| SessionBuilderError::InvalidArgs(field.to_owned(), e))
+}
+
+fn check_ack_timeout(ack_timeout: Option<Duration>) -> Result<()> {
+    match ack_timeout {
+        Some(x) if x.is_zero() => Err(SessionBuilderError::InvalidRange(
+            "ack_timeout".to_owned(),
+            "> 0".to_owned(),
+            format!("{x:?}"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+// keeps checking the remaining fields after an error
+fn collect<T>(errors: &mut Vec<SessionBuilderError>, result: Result<T>) -> Option<T> {
+    result.map_err(|e| errors.push(e)).ok()
+}
+
+/// How the broker acknowledgements of guaranteed messages are reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AckEventMode {
+    /// One `Acknowledgement` event per message.
+    #[default]
+    PerMessage,
+    /// One `Acknowledgement` event for every window of messages, acknowledging the message it
+    /// is reported for and every message sent before it. Reduces the event overhead for high
+    /// throughput publishers.
+    Windowed,
+}
+
+impl AckEventMode {
+    fn as_prop(self) -> &'static [u8] {
+        match self {
+            Self::PerMessage => ffi::SOLCLIENT_SESSION_PROP_ACK_EVENT_MODE_PER_MSG,
+            Self::Windowed => ffi::SOLCLIENT_SESSION_PROP_ACK_EVENT_MODE_WINDOWED,
+        }
+    }
+}
+
+impl FromStr for AckEventMode {
+    type Err = SessionBuilderError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "per_message" => Ok(Self::PerMessage),
+            "windowed" => Ok(Self::Windowed),
+            _ => Err(SessionBuilderError::InvalidConfig(
+                "ack_event_mode".to_owned(),
+                s.to_owned(),
+            )),
+        }
     }
 }
 
+/// What the session does when it can not unbind a flow after reconnecting, for example to a
+/// different broker after a failover.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum UnbindFailAction {
+    /// Keeps retrying to unbind the flow.
+    #[default]
+    Retry,
+    /// Disconnects the session.
+    Disconnect,
+}
+
+impl UnbindFailAction {
+    fn as_prop(self) -> &'static [u8] {
+        match self {
+            Self::Retry => ffi::SOLCLIENT_SESSION_PROP_UNBIND_FAIL_ACTION_RETRY,
+            Self::Disconnect => ffi::SOLCLIENT_SESSION_PROP_UNBIND_FAIL_ACTION_DISCONNECT,
+        }
+    }
+}
+
+impl FromStr for UnbindFailAction {
+    type Err = SessionBuilderError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "retry" => Ok(Self::Retry),
+            "disconnect" => Ok(Self::Disconnect),
+            _ => Err(SessionBuilderError::InvalidConfig(
+                "unbind_fail_action".to_owned(),
+                s.to_owned(),
+            )),
+        }
+    }
+}
+
+/// Which layer reapplies the topic subscriptions of a session after it reconnects, set with
+/// [`SessionBuilder::resubscribe_strategy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ResubscribeStrategy {
+    /// Subscriptions are lost on reconnect, the default of the C library.
+    #[default]
+    None,
+    /// The C library reapplies every subscription, including the ones added with functions this
+    /// crate does not wrap.
+    Native,
+    /// The session reapplies the subscriptions it tracks, the ones reported by
+    /// [`Session::is_subscribed`], once it is reconnected.
+    Managed,
+}
+
+impl FromStr for ResubscribeStrategy {
+    type Err = SessionBuilderError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(Self::None),
+            "native" => Ok(Self::Native),
+            "managed" => Ok(Self::Managed),
+            _ => Err(SessionBuilderError::InvalidConfig(
+                "resubscribe_strategy".to_owned(),
+                s.to_owned(),
+            )),
+        }
+    }
+}
+
+#[derive(Clone)]
 struct UncheckedSessionProps<Host, Vpn, Username, Password> {
     // Note: required params
     // In the future we can use type state pattern to always force clients to provide these params
@@ -53,6 +199,7 @@ struct UncheckedSessionProps<Host, Vpn, Username, Password> {
     tcp_nodelay: Option<bool>,
     socket_send_buf_size_bytes: Option<u64>,
     socket_rcv_buf_size_bytes: Option<u64>,
+    bind_ip: Option<Vec<u8>>,
     keep_alive_interval_ms: Option<u64>,
     keep_alive_limit: Option<u64>,
     application_description: Option<Vec<u8>>,
@@ -70,7 +217,24 @@ struct UncheckedSessionProps<Host, Vpn, Username, Password> {
     provision_timeout_ms: Option<u64>,
     calculate_message_expiration: Option<bool>,
     no_local: Option<bool>,
+    // subscriptions returning would block are handled by subscribe_nonblocking
+    subscribe_blocking: Option<bool>,
+    ack_event_mode: Option<AckEventMode>,
     modifyprop_timeout_ms: Option<u64>,
+    pub_window_size: Option<u64>,
+    pub_ack_timer_ms: Option<u64>,
+    ad_pub_router_windowed_ack: Option<bool>,
+    unbind_fail_action: Option<UnbindFailAction>,
+    resubscribe_strategy: Option<ResubscribeStrategy>,
+    ssl_trust_store_dir: Option<Vec<u8>>,
+    ssl_downgrade_to_plaintext: Option<bool>,
+    ssl_cipher_suites: Option<Vec<u8>>,
+    ssl_excluded_protocols: Option<Vec<u8>>,
+    ssl_validate_certificate: Option<bool>,
+    ssl_validate_certificate_date: Option<bool>,
+    ssl_trusted_common_name_list: Option<Vec<u8>>,
+    oauth2_access_token: Option<Vec<u8>>,
+    oauth2_issuer_identifier: Option<Vec<u8>>,
 
     // TODO: need to check if some of these params will break other assumptions
     // ex: we might check for ok status on send but if send_blocking is set to false
@@ -81,8 +245,6 @@ struct UncheckedSessionProps<Host, Vpn, Username, Password> {
     #[allow(dead_code)]
     send_blocking: Option<bool>,
     #[allow(dead_code)]
-    subscribe_blocking: Option<bool>,
-    #[allow(dead_code)]
     block_while_connecting: Option<bool>,
 
     // TODO: probably should expose this through some other way
@@ -91,6 +253,128 @@ struct UncheckedSessionProps<Host, Vpn, Username, Password> {
     topic_dispatch: Option<bool>,
 }
 
+impl<Host: fmt::Debug, Vpn: fmt::Debug, Username: fmt::Debug, Password> fmt::Debug
+    for UncheckedSessionProps<Host, Vpn, Username, Password>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionProps")
+            .field("host_name", &self.host_name)
+            .field("vpn_name", &self.vpn_name)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| Redacted))
+            .field("buffer_size_bytes", &self.buffer_size_bytes)
+            .field("block_write_timeout_ms", &self.block_write_timeout_ms)
+            .field("connect_timeout_ms", &self.connect_timeout_ms)
+            .field("subconfirm_timeout_ms", &self.subconfirm_timeout_ms)
+            .field(
+                "ignore_dup_subscription_error",
+                &self.ignore_dup_subscription_error,
+            )
+            .field("tcp_nodelay", &self.tcp_nodelay)
+            .field(
+                "socket_send_buf_size_bytes",
+                &self.socket_send_buf_size_bytes,
+            )
+            .field("socket_rcv_buf_size_bytes", &self.socket_rcv_buf_size_bytes)
+            .field(
+                "bind_ip",
+                &self.bind_ip.as_deref().map(String::from_utf8_lossy),
+            )
+            .field("keep_alive_interval_ms", &self.keep_alive_interval_ms)
+            .field("keep_alive_limit", &self.keep_alive_limit)
+            .field(
+                "application_description",
+                &self
+                    .application_description
+                    .as_deref()
+                    .map(String::from_utf8_lossy),
+            )
+            .field(
+                "client_name",
+                &self.client_name.as_deref().map(String::from_utf8_lossy),
+            )
+            .field("compression_level", &self.compression_level)
+            .field("generate_rcv_timestamps", &self.generate_rcv_timestamps)
+            .field("generate_send_timestamp", &self.generate_send_timestamp)
+            .field("generate_sender_id", &self.generate_sender_id)
+            .field(
+                "generate_sender_sequence_number",
+                &self.generate_sender_sequence_number,
+            )
+            .field("connect_retries_per_host", &self.connect_retries_per_host)
+            .field("connect_retries", &self.connect_retries)
+            .field("reconnect_retries", &self.reconnect_retries)
+            .field("reconnect_retry_wait_ms", &self.reconnect_retry_wait_ms)
+            .field("reapply_subscriptions", &self.reapply_subscriptions)
+            .field("provision_timeout_ms", &self.provision_timeout_ms)
+            .field(
+                "calculate_message_expiration",
+                &self.calculate_message_expiration,
+            )
+            .field("no_local", &self.no_local)
+            .field("subscribe_blocking", &self.subscribe_blocking)
+            .field("ack_event_mode", &self.ack_event_mode)
+            .field("modifyprop_timeout_ms", &self.modifyprop_timeout_ms)
+            .field("pub_window_size", &self.pub_window_size)
+            .field("pub_ack_timer_ms", &self.pub_ack_timer_ms)
+            .field(
+                "ad_pub_router_windowed_ack",
+                &self.ad_pub_router_windowed_ack,
+            )
+            .field("unbind_fail_action", &self.unbind_fail_action)
+            .field("resubscribe_strategy", &self.resubscribe_strategy)
+            .field(
+                "ssl_trust_store_dir",
+                &self
+                    .ssl_trust_store_dir
+                    .as_deref()
+                    .map(String::from_utf8_lossy),
+            )
+            .field(
+                "ssl_downgrade_to_plaintext",
+                &self.ssl_downgrade_to_plaintext,
+            )
+            .field(
+                "ssl_cipher_suites",
+                &self
+                    .ssl_cipher_suites
+                    .as_deref()
+                    .map(String::from_utf8_lossy),
+            )
+            .field(
+                "ssl_excluded_protocols",
+                &self
+                    .ssl_excluded_protocols
+                    .as_deref()
+                    .map(String::from_utf8_lossy),
+            )
+            .field("ssl_validate_certificate", &self.ssl_validate_certificate)
+            .field(
+                "ssl_validate_certificate_date",
+                &self.ssl_validate_certificate_date,
+            )
+            .field(
+                "ssl_trusted_common_name_list",
+                &self
+                    .ssl_trusted_common_name_list
+                    .as_deref()
+                    .map(String::from_utf8_lossy),
+            )
+            .field(
+                "oauth2_access_token",
+                &self.oauth2_access_token.as_ref().map(|_| Redacted),
+            )
+            .field(
+                "oauth2_issuer_identifier",
+                &self
+                    .oauth2_issuer_identifier
+                    .as_deref()
+                    .map(String::from_utf8_lossy),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
 impl<Host, Vpn, Username, Password> Default
     for UncheckedSessionProps<Host, Vpn, Username, Password>
 {
@@ -108,6 +392,7 @@ impl<Host, Vpn, Username, Password> Default
             tcp_nodelay: None,
             socket_send_buf_size_bytes: None,
             socket_rcv_buf_size_bytes: None,
+            bind_ip: None,
             keep_alive_interval_ms: None,
             keep_alive_limit: None,
             application_description: None,
@@ -125,7 +410,22 @@ impl<Host, Vpn, Username, Password> Default
             provision_timeout_ms: None,
             calculate_message_expiration: None,
             no_local: None,
+            ack_event_mode: None,
             modifyprop_timeout_ms: None,
+            pub_window_size: None,
+            pub_ack_timer_ms: None,
+            ad_pub_router_windowed_ack: None,
+            unbind_fail_action: None,
+            resubscribe_strategy: None,
+            ssl_trust_store_dir: None,
+            ssl_downgrade_to_plaintext: None,
+            ssl_cipher_suites: None,
+            ssl_excluded_protocols: None,
+            ssl_validate_certificate: None,
+            ssl_validate_certificate_date: None,
+            ssl_trusted_common_name_list: None,
+            oauth2_access_token: None,
+            oauth2_issuer_identifier: None,
             send_blocking: None,
             subscribe_blocking: None,
             block_while_connecting: None,
@@ -134,6 +434,69 @@ impl<Host, Vpn, Username, Password> Default
     }
 }
 
+impl<Host, Vpn, Username, Password> UncheckedSessionProps<Host, Vpn, Username, Password>
+where
+    Host: Into<Vec<u8>>,
+    Vpn: Into<Vec<u8>>,
+    Username: Into<Vec<u8>>,
+    Password: Into<Vec<u8>>,
+{
+    fn into_bytes(self) -> UncheckedSessionProps<Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>> {
+        UncheckedSessionProps {
+            host_name: self.host_name.map(Into::into),
+            vpn_name: self.vpn_name.map(Into::into),
+            username: self.username.map(Into::into),
+            password: self.password.map(Into::into),
+            buffer_size_bytes: self.buffer_size_bytes,
+            block_write_timeout_ms: self.block_write_timeout_ms,
+            connect_timeout_ms: self.connect_timeout_ms,
+            subconfirm_timeout_ms: self.subconfirm_timeout_ms,
+            ignore_dup_subscription_error: self.ignore_dup_subscription_error,
+            tcp_nodelay: self.tcp_nodelay,
+            socket_send_buf_size_bytes: self.socket_send_buf_size_bytes,
+            socket_rcv_buf_size_bytes: self.socket_rcv_buf_size_bytes,
+            bind_ip: self.bind_ip,
+            keep_alive_interval_ms: self.keep_alive_interval_ms,
+            keep_alive_limit: self.keep_alive_limit,
+            application_description: self.application_description,
+            client_name: self.client_name,
+            compression_level: self.compression_level,
+            generate_rcv_timestamps: self.generate_rcv_timestamps,
+            generate_send_timestamp: self.generate_send_timestamp,
+            generate_sender_id: self.generate_sender_id,
+            generate_sender_sequence_number: self.generate_sender_sequence_number,
+            connect_retries_per_host: self.connect_retries_per_host,
+            connect_retries: self.connect_retries,
+            reconnect_retries: self.reconnect_retries,
+            reconnect_retry_wait_ms: self.reconnect_retry_wait_ms,
+            reapply_subscriptions: self.reapply_subscriptions,
+            provision_timeout_ms: self.provision_timeout_ms,
+            calculate_message_expiration: self.calculate_message_expiration,
+            no_local: self.no_local,
+            subscribe_blocking: self.subscribe_blocking,
+            ack_event_mode: self.ack_event_mode,
+            modifyprop_timeout_ms: self.modifyprop_timeout_ms,
+            pub_window_size: self.pub_window_size,
+            pub_ack_timer_ms: self.pub_ack_timer_ms,
+            ad_pub_router_windowed_ack: self.ad_pub_router_windowed_ack,
+            unbind_fail_action: self.unbind_fail_action,
+            resubscribe_strategy: self.resubscribe_strategy,
+            ssl_trust_store_dir: self.ssl_trust_store_dir,
+            ssl_downgrade_to_plaintext: self.ssl_downgrade_to_plaintext,
+            ssl_cipher_suites: self.ssl_cipher_suites,
+            ssl_excluded_protocols: self.ssl_excluded_protocols,
+            ssl_validate_certificate: self.ssl_validate_certificate,
+            ssl_validate_certificate_date: self.ssl_validate_certificate_date,
+            ssl_trusted_common_name_list: self.ssl_trusted_common_name_list,
+            oauth2_access_token: self.oauth2_access_token,
+            oauth2_issuer_identifier: self.oauth2_issuer_identifier,
+            send_blocking: self.send_blocking,
+            block_while_connecting: self.block_while_connecting,
+            topic_dispatch: self.topic_dispatch,
+        }
+    }
+}
+
 /// `SessionBuilder` allows setting up a session with customizable options that are not exposed by
 /// the `session` function such as buffer size, timeouts, and more.
 ///
@@ -145,6 +508,15 @@ pub struct SessionBuilder<Host, Vpn, Username, Password, OnMessage, OnEvent> {
     // callbacks
     on_message: Option<OnMessage>,
     on_event: Option<OnEvent>,
+    on_event_info: Option<EventInfoCallback>,
+    event_filter: Option<Vec<SessionEvent>>,
+    slow_callback_threshold: Option<Duration>,
+    publish_rate_limit: Option<RateLimit>,
+    codec: Option<Arc<dyn PayloadCodec>>,
+    republish_unacked: bool,
+    ack_timeout: Option<Duration>,
+    on_ack_timeout: Option<AckTimeoutCallback>,
+    token_provider: Option<Arc<dyn TokenProvider>>,
 }
 
 impl<Host, Vpn, Username, Password, OnMessage, OnEvent>
@@ -156,7 +528,65 @@ impl<Host, Vpn, Username, Password, OnMessage, OnEvent>
             props: UncheckedSessionProps::default(),
             on_message: None,
             on_event: None,
+            on_event_info: None,
+            event_filter: None,
+            slow_callback_threshold: None,
+            publish_rate_limit: None,
+            codec: None,
+            republish_unacked: false,
+            ack_timeout: None,
+            on_ack_timeout: None,
+            token_provider: None,
+        }
+    }
+}
+
+impl<Host, Vpn, Username, Password, OnMessage, OnEvent>
+    SessionBuilder<Host, Vpn, Username, Password, OnMessage, OnEvent>
+where
+    Host: Into<Vec<u8>> + Clone,
+    Vpn: Into<Vec<u8>> + Clone,
+    Username: Into<Vec<u8>> + Clone,
+    Password: Into<Vec<u8>> + Clone,
+{
+    /// Runs the checks of [`SessionBuilder::build`], such as the ranges and the interior nul
+    /// bytes, without connecting to the broker. Returns every error instead of the first one, for
+    /// example to validate configuration when it is loaded.
+    pub fn validate(&self) -> std::result::Result<(), Vec<SessionBuilderError>> {
+        let mut errors = CheckedSessionProps::check(self.props.clone())
+            .err()
+            .unwrap_or_default();
+        collect(&mut errors, check_ack_timeout(self.ack_timeout));
+
+        if !errors.is_empty() {
+            return Err(errors);
         }
+        Ok(())
+    }
+}
+
+impl<Host, Vpn, Username, Password, OnMessage, OnEvent> fmt::Debug
+    for SessionBuilder<Host, Vpn, Username, Password, OnMessage, OnEvent>
+where
+    Host: fmt::Debug,
+    Vpn: fmt::Debug,
+    Username: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionBuilder")
+            .field("props", &self.props)
+            .field("on_message", &self.on_message.is_some())
+            .field("on_event", &self.on_event.is_some())
+            .field("on_event_info", &self.on_event_info.is_some())
+            .field("event_filter", &self.event_filter)
+            .field("slow_callback_threshold", &self.slow_callback_threshold)
+            .field("publish_rate_limit", &self.publish_rate_limit)
+            .field("codec", &self.codec.as_ref().map(|c| c.encoding()))
+            .field("republish_unacked", &self.republish_unacked)
+            .field("ack_timeout", &self.ack_timeout)
+            .field("on_ack_timeout", &self.on_ack_timeout.is_some())
+            .field("token_provider", &self.token_provider.is_some())
+            .finish()
     }
 }
 
@@ -170,8 +600,49 @@ where
     OnMessage: FnMut(InboundMessage) + Send + 'session,
     OnEvent: FnMut(SessionEvent) + Send + 'session,
 {
+    /// Builds a [`OwnedSession`]. The callbacks are boxed, so they need to be `'static`.
+    pub fn build_owned(self) -> Result<OwnedSession>
+    where
+        OnMessage: 'static,
+        OnEvent: 'static,
+    {
+        SessionBuilder {
+            context: self.context,
+            props: self.props,
+            on_message: self.on_message.map(|f| Box::new(f) as OwnedMessageCallback),
+            on_event: self.on_event.map(|f| Box::new(f) as OwnedEventCallback),
+            on_event_info: self.on_event_info,
+            event_filter: self.event_filter,
+            slow_callback_threshold: self.slow_callback_threshold,
+            publish_rate_limit: self.publish_rate_limit,
+            codec: self.codec,
+            republish_unacked: self.republish_unacked,
+            ack_timeout: self.ack_timeout,
+            on_ack_timeout: self.on_ack_timeout,
+            token_provider: self.token_provider,
+        }
+        .build()
+    }
+
     pub fn build(mut self) -> Result<Session<'session, OnMessage, OnEvent>> {
+        check_ack_timeout(self.ack_timeout)?;
+
+        let mut token_refresh_at = None;
+        if let Some(provider) = &self.token_provider {
+            let fetched_at = Instant::now();
+            let token = provider
+                .fetch_token()
+                .map_err(SessionBuilderError::TokenFetchFailure)?;
+            token_refresh_at = refresh_at(fetched_at, &token);
+            self.props.oauth2_access_token = Some(token.access_token().into());
+        }
+        let token_refresh = self
+            .token_provider
+            .as_ref()
+            .map(|_| Arc::new(TokenRefresh::default()));
+
         let config = CheckedSessionProps::try_from(mem::take(&mut self.props))?;
+        debug!("creating session with {config:?}");
 
         // Session props is a **char in C
         // it takes in an array of key and values
@@ -180,29 +651,45 @@ where
         // Note: Needs to live long enough for the values to be copied
         let mut session_pt: ffi::solClient_opaqueSession_pt = ptr::null_mut();
 
-        // Box::into_raw(Box::new(Box::new(f))) as *mut _
-        // need to box it twice
-        // first box will result in a fat pointer
-        // causing a seg fault when dereffing in C land.
-        // leaking is also fine since the lifetime of the closure is set to be the lifetime of the
-        // session
+        // The handlers are boxed so that their address stays stable when the session is moved.
+        // The session owns the boxes, so the lifetime of the closures is set to be the lifetime
+        // of the session
+        let windowed_acks = config.ack_event_mode == Some(AckEventMode::Windowed);
+        let state = Arc::new(SessionState::new(SessionStateOptions {
+            windowed_acks,
+            rate_limit: self.publish_rate_limit,
+            codec: self.codec,
+            republish_unacked: self.republish_unacked,
+            managed_resubscribe: config.resubscribe_strategy == Some(ResubscribeStrategy::Managed),
+            ack_timeout: self.ack_timeout,
+            on_ack_timeout: self.on_ack_timeout,
+            context_counters: self.context.counters(),
+        }));
+
         let (static_on_message_callback, user_on_message, msg_func_ptr) = match self.on_message {
             Some(f) => {
-                let tramp = on_message_trampoline(&f);
-                let mut func = Box::new(Box::new(f));
-                (tramp, func.as_mut() as *const _ as *mut _, Some(func))
+                let mut handler = Box::new(MessageHandler::new(
+                    state.clone(),
+                    f,
+                    self.slow_callback_threshold,
+                ));
+                let tramp = on_message_trampoline(&handler);
+                (tramp, handler.as_mut() as *const _ as *mut _, Some(handler))
             }
             _ => (None, ptr::null_mut(), None),
         };
 
-        let (static_on_event_callback, user_on_event, event_func_ptr) = match self.on_event {
-            Some(f) => {
-                let tramp = on_event_trampoline(&f);
-                let mut func = Box::new(Box::new(f));
-                (tramp, func.as_mut() as *const _ as *mut _, Some(func))
-            }
-            _ => (None, ptr::null_mut(), None),
-        };
+        // the event handler is always registered so that the session can keep track of its
+        // own state. The user callback is invoked from within the handler.
+        let mut event_handler = Box::new(EventHandler::new(
+            state.clone(),
+            self.on_event,
+            self.on_event_info,
+            self.event_filter,
+            token_refresh.clone(),
+        ));
+        let static_on_event_callback = on_event_trampoline(&event_handler);
+        let user_on_event = event_handler.as_mut() as *const _ as *mut _;
 
         // Function information for Session creation.
         // The application must set the eventInfo callback information. All Sessions must have an event callback registered.
@@ -223,7 +710,7 @@ where
             };
 
         let mut raw = config.to_raw();
-        let context_ptr = self.context.raw.lock().unwrap();
+        let context_ptr = self.context.lock_raw();
         let session_create_raw_rc = unsafe {
             ffi::solClient_session_create(
                 raw.as_mut_ptr(),
@@ -237,24 +724,61 @@ where
 
         let rc = SolClientReturnCode::from_raw(session_create_raw_rc);
 
-        if !rc.is_ok() {
-            let subcode = get_last_error_info();
-            return Err(SessionBuilderError::InitializationFailure(rc, subcode));
-        }
+        rc.to_result(SessionBuilderError::InitializationFailure)?;
 
         let connection_raw_rc = unsafe { ffi::solClient_session_connect(session_pt) };
 
         let rc = SolClientReturnCode::from_raw(connection_raw_rc);
         if rc.is_ok() {
-            Ok(Session {
+            let mut session = Session {
                 _msg_fn_ptr: msg_func_ptr,
-                _event_fn_ptr: event_func_ptr,
+                _event_fn_ptr: event_handler,
+                state,
                 _session_ptr: session_pt,
-                context: self.context,
+                context: self.context.session_handle(),
                 lifetime: PhantomData,
-            })
+                ack_timer: None,
+                token_refresh,
+            };
+            session.ack_timer =
+                start_ack_timer(&session.context, &session.state).map_err(|rc| {
+                    SessionBuilderError::InitializationFailure(rc, get_last_error_info())
+                })?;
+
+            // captured once so that log lines can be correlated without querying the C library
+            if let Ok(client_name) = session.client_name() {
+                session.state.set_client_name(client_name);
+            }
+            session.context.register_session(
+                session.state.id(),
+                session.state.client_name(),
+                session._session_ptr,
+            );
+
+            if let (Some(provider), Some(token_refresh)) =
+                (self.token_provider, &session.token_refresh)
+            {
+                spawn_token_refresh(
+                    provider,
+                    token_refresh.clone(),
+                    session._session_ptr,
+                    session.state.clone(),
+                    token_refresh_at,
+                )
+                .map_err(SessionBuilderError::TokenRefreshSpawnFailure)?;
+            }
+
+            Ok(session)
         } else {
             let subcode = get_last_error_info();
+
+            // the C session calls into the boxed handlers, so it must be destroyed before they
+            // are dropped at the end of this scope. Callers such as `ReconnectingSession` retry
+            // without limit and would otherwise leak a session per attempt
+            state.close();
+            state.wait_for_callbacks();
+            unsafe { ffi::solClient_session_destroy(&mut session_pt) };
+
             Err(SessionBuilderError::ConnectionFailure(rc, subcode))
         }
     }
@@ -287,6 +811,79 @@ where
         self
     }
 
+    /// Logs a warning and counts a slow callback in [`Session::metrics`](super::Session::metrics)
+    /// whenever `on_message` takes longer than the threshold. Slow callbacks hold up the context
+    /// thread and make the broker buffer messages for the session.
+    pub fn slow_callback_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_callback_threshold = Some(threshold);
+        self
+    }
+
+    /// Throttles publishing on the session, see [`RateLimit`]. Unlimited by default.
+    pub fn publish_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.publish_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Keeps a copy of every guaranteed message until the broker acknowledges it, and republishes
+    /// the unacked copies when the session reports
+    /// [`SessionEvent::RepublishUnackedMessages`], for example after reconnecting to another
+    /// host. Pending confirmations are resolved by the acknowledgements of the republished
+    /// messages, and only a summary is logged and counted in
+    /// [`SessionMetrics::republished`](super::SessionMetrics::republished). The event is still
+    /// passed to `on_event`. Off by default.
+    pub fn republish_unacked(mut self, republish_unacked: bool) -> Self {
+        self.republish_unacked = republish_unacked;
+        self
+    }
+
+    /// Gives up on guaranteed messages that are neither acknowledged nor rejected within the
+    /// timeout, for example when the broker hangs. Their receipts fail with
+    /// [`SessionError::AckTimedOut`](crate::SessionError::AckTimedOut), they no longer count
+    /// towards the publisher window, and they are logged and counted in
+    /// [`SessionMetrics::ack_timeouts`](super::SessionMetrics::ack_timeouts). Checked on the
+    /// context thread with a resolution of a quarter of the timeout, at most a second. Off by
+    /// default, building fails with [`SessionBuilderError::InvalidRange`] for a zero timeout.
+    pub fn ack_timeout(mut self, ack_timeout: Duration) -> Self {
+        self.ack_timeout = Some(ack_timeout);
+        self
+    }
+
+    /// Called on the context thread with the number of guaranteed messages given up on whenever
+    /// the [`ack_timeout`](Self::ack_timeout) expires, for example to alert on a hanging broker.
+    /// The receipts of the messages fail as well.
+    pub fn on_ack_timeout<F>(mut self, on_ack_timeout: F) -> Self
+    where
+        F: FnMut(usize) + Send + 'static,
+    {
+        self.on_ack_timeout = Some(Box::new(on_ack_timeout));
+        self
+    }
+
+    /// Like `on_event`, with the details the C library reports with the event, such as the host
+    /// the session is connecting to. Called before `on_event` and subject to the same filter.
+    pub fn on_event_info<F>(mut self, on_event_info: F) -> Self
+    where
+        F: FnMut(&SessionEventInfo) + Send + 'static,
+    {
+        self.on_event_info = Some(Box::new(on_event_info));
+        self
+    }
+
+    /// Encodes published payloads and decodes received ones with the codec, see [`PayloadCodec`].
+    /// Requests and their replies are not encoded.
+    pub fn codec<C: PayloadCodec + 'static>(mut self, codec: C) -> Self {
+        self.codec = Some(Arc::new(codec));
+        self
+    }
+
+    /// Only the given events are passed to the `on_event` callback, the rest are logged at debug
+    /// level. By default every event is passed.
+    pub fn event_filter(mut self, events: &[SessionEvent]) -> Self {
+        self.event_filter = Some(events.to_vec());
+        self
+    }
+
     pub fn buffer_size_bytes(mut self, buffer_size_bytes: u64) -> Self {
         self.props.buffer_size_bytes = Some(buffer_size_bytes);
         self
@@ -319,6 +916,13 @@ where
         self.props.socket_rcv_buf_size_bytes = Some(socket_rcv_buf_size_bytes);
         self
     }
+    /// Local address the connection to the broker is made from, `address` or `address:port`
+    /// with IPv6 addresses in brackets, for example to pin the traffic to one interface of a
+    /// multi-homed host. The port is picked by the system if not set or 0.
+    pub fn bind_ip<BindIp: Into<Vec<u8>>>(mut self, bind_ip: BindIp) -> Self {
+        self.props.bind_ip = Some(bind_ip.into());
+        self
+    }
     pub fn keep_alive_interval_ms(mut self, keep_alive_interval_ms: u64) -> Self {
         self.props.keep_alive_interval_ms = Some(keep_alive_interval_ms);
         self
@@ -334,6 +938,8 @@ where
         self.props.application_description = Some(application_description.into());
         self
     }
+    /// Names of at most [`ClientName::MAX_LEN`](super::ClientName::MAX_LEN) bytes are accepted,
+    /// see [`ClientName::generate`](super::ClientName::generate) for unique names.
     pub fn client_name<ClientName: Into<Vec<u8>>>(mut self, client_name: ClientName) -> Self {
         self.props.client_name = Some(client_name.into());
         self
@@ -377,6 +983,8 @@ where
         self.props.reconnect_retry_wait_ms = Some(reconnect_retry_wait_ms);
         self
     }
+    /// See [`SessionBuilder::resubscribe_strategy`], which can not be combined with a conflicting
+    /// value.
     pub fn reapply_subscriptions(mut self, reapply_subscriptions: bool) -> Self {
         self.props.reapply_subscriptions = Some(reapply_subscriptions);
         self
@@ -393,10 +1001,290 @@ where
         self.props.no_local = Some(no_local);
         self
     }
+    /// When false, subscription calls return `WouldBlock` instead of waiting for room in the send
+    /// buffer. Use with [`Session::subscribe_nonblocking`](super::Session::subscribe_nonblocking).
+    pub fn subscribe_blocking(mut self, subscribe_blocking: bool) -> Self {
+        self.props.subscribe_blocking = Some(subscribe_blocking);
+        self
+    }
+    pub fn ack_event_mode(mut self, ack_event_mode: AckEventMode) -> Self {
+        self.props.ack_event_mode = Some(ack_event_mode);
+        self
+    }
     pub fn modifyprop_timeout_ms(mut self, modifyprop_timeout_ms: u64) -> Self {
         self.props.modifyprop_timeout_ms = Some(modifyprop_timeout_ms);
         self
     }
+    /// Guaranteed messages that can be in flight before the broker acknowledges them, between 1
+    /// and 255.
+    pub fn pub_window_size(mut self, pub_window_size: u64) -> Self {
+        self.props.pub_window_size = Some(pub_window_size);
+        self
+    }
+    /// Time to wait for an acknowledgement before guaranteed messages are resent, between 20 and
+    /// 60000.
+    pub fn pub_ack_timer_ms(mut self, pub_ack_timer_ms: u64) -> Self {
+        self.props.pub_ack_timer_ms = Some(pub_ack_timer_ms);
+        self
+    }
+    /// Lets the broker acknowledge guaranteed messages per window instead of per message.
+    pub fn ad_pub_router_windowed_ack(mut self, ad_pub_router_windowed_ack: bool) -> Self {
+        self.props.ad_pub_router_windowed_ack = Some(ad_pub_router_windowed_ack);
+        self
+    }
+    pub fn unbind_fail_action(mut self, unbind_fail_action: UnbindFailAction) -> Self {
+        self.props.unbind_fail_action = Some(unbind_fail_action);
+        self
+    }
+    /// Configures both the C library and the session so that subscriptions are reapplied once
+    /// after a reconnect, see [`ResubscribeStrategy`]. Setting
+    /// [`SessionBuilder::reapply_subscriptions`] to a value that does not match the strategy fails
+    /// the build.
+    ///
+    /// Flows, such as the reply queue of a
+    /// [`GuaranteedRequester`](super::GuaranteedRequester), are rebound by the C library after a
+    /// reconnect whatever the strategy. A [`ReconnectingSession`](super::ReconnectingSession)
+    /// reapplies its own subscriptions to every session it builds, which only needs `None`.
+    pub fn resubscribe_strategy(mut self, resubscribe_strategy: ResubscribeStrategy) -> Self {
+        self.props.resubscribe_strategy = Some(resubscribe_strategy);
+        self
+    }
+
+    /// Directory with the trusted certificates used to validate the broker certificate.
+    pub fn ssl_trust_store_dir<TrustStoreDir: Into<Vec<u8>>>(
+        mut self,
+        ssl_trust_store_dir: TrustStoreDir,
+    ) -> Self {
+        self.props.ssl_trust_store_dir = Some(ssl_trust_store_dir.into());
+        self
+    }
+
+    /// Downgrades the connection to plain text after authenticating over TLS. Requires every
+    /// host to use `tcps` and can not be combined with compression.
+    pub fn ssl_downgrade_to_plaintext(mut self, ssl_downgrade_to_plaintext: bool) -> Self {
+        self.props.ssl_downgrade_to_plaintext = Some(ssl_downgrade_to_plaintext);
+        self
+    }
+
+    /// Comma separated list of cipher suites, in order of preference.
+    pub fn ssl_cipher_suites<CipherSuites: Into<Vec<u8>>>(
+        mut self,
+        ssl_cipher_suites: CipherSuites,
+    ) -> Self {
+        self.props.ssl_cipher_suites = Some(ssl_cipher_suites.into());
+        self
+    }
+
+    /// Comma separated list of protocols that must not be used, e.g. `SSLv3,TLSv1,TLSv1.1`.
+    pub fn ssl_excluded_protocols<ExcludedProtocols: Into<Vec<u8>>>(
+        mut self,
+        ssl_excluded_protocols: ExcludedProtocols,
+    ) -> Self {
+        self.props.ssl_excluded_protocols = Some(ssl_excluded_protocols.into());
+        self
+    }
+
+    pub fn ssl_validate_certificate(mut self, ssl_validate_certificate: bool) -> Self {
+        self.props.ssl_validate_certificate = Some(ssl_validate_certificate);
+        self
+    }
+
+    pub fn ssl_validate_certificate_date(mut self, ssl_validate_certificate_date: bool) -> Self {
+        self.props.ssl_validate_certificate_date = Some(ssl_validate_certificate_date);
+        self
+    }
+
+    /// Comma separated list of common names the broker certificate is accepted with.
+    pub fn ssl_trusted_common_name_list<CommonNames: Into<Vec<u8>>>(
+        mut self,
+        ssl_trusted_common_name_list: CommonNames,
+    ) -> Self {
+        self.props.ssl_trusted_common_name_list = Some(ssl_trusted_common_name_list.into());
+        self
+    }
+
+    /// Authenticates with the OAuth access token instead of the password, which can be left
+    /// empty. The token can be replaced with
+    /// [`Session::set_oauth2_access_token`](super::Session::set_oauth2_access_token), see
+    /// [`SessionBuilder::token_provider`] to refresh it automatically.
+    pub fn oauth2_access_token<AccessToken: Into<Vec<u8>>>(
+        mut self,
+        oauth2_access_token: AccessToken,
+    ) -> Self {
+        self.props.oauth2_access_token = Some(oauth2_access_token.into());
+        self
+    }
+
+    /// Issuer the broker validates the OAuth access token with, when the message VPN has more
+    /// than one OAuth provider.
+    pub fn oauth2_issuer_identifier<Issuer: Into<Vec<u8>>>(
+        mut self,
+        oauth2_issuer_identifier: Issuer,
+    ) -> Self {
+        self.props.oauth2_issuer_identifier = Some(oauth2_issuer_identifier.into());
+        self
+    }
+
+    /// Authenticates with OAuth access tokens fetched from the provider, like
+    /// [`SessionBuilder::oauth2_access_token`]. The first token is fetched when the session is
+    /// built. The session then fetches a new token on a background thread once 80% of the
+    /// lifetime of the current one has passed, and whenever the broker refuses the login, and
+    /// updates the session with it. Failed refreshes are logged and retried every 5 seconds.
+    pub fn token_provider<P: TokenProvider + 'static>(mut self, token_provider: P) -> Self {
+        self.token_provider = Some(Arc::new(token_provider));
+        self
+    }
+}
+
+impl<Host, Vpn, Username, Password, OnMessage, OnEvent>
+    SessionBuilder<Host, Vpn, Username, Password, OnMessage, OnEvent>
+where
+    Host: Into<Vec<u8>>,
+    Vpn: Into<Vec<u8>>,
+    Username: Into<Vec<u8>>,
+    Password: Into<Vec<u8>>,
+{
+    /// Sets every property that is set in the config. Properties set on the builder before are
+    /// overwritten, so later builder calls can still override the config. The host name, vpn
+    /// name, username and password of the returned builder are bytes, e.g.
+    /// `.password("secret".into())`. See
+    /// [`Context::session_builder_from_config`](crate::Context::session_builder_from_config) to
+    /// start from a config.
+    pub fn config(
+        self,
+        config: &SessionConfig,
+    ) -> SessionBuilder<Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>, OnMessage, OnEvent> {
+        let config = config.clone();
+        let mut builder = SessionBuilder {
+            context: self.context,
+            props: self.props.into_bytes(),
+            on_message: self.on_message,
+            on_event: self.on_event,
+            on_event_info: self.on_event_info,
+            event_filter: self.event_filter,
+            slow_callback_threshold: self.slow_callback_threshold,
+            publish_rate_limit: self.publish_rate_limit,
+            codec: self.codec,
+            republish_unacked: self.republish_unacked,
+            ack_timeout: self.ack_timeout,
+            on_ack_timeout: self.on_ack_timeout,
+            token_provider: self.token_provider,
+        };
+        let props = &mut builder.props;
+
+        props.host_name = config
+            .host_name
+            .map(String::into_bytes)
+            .or(props.host_name.take());
+        props.vpn_name = config
+            .vpn_name
+            .map(String::into_bytes)
+            .or(props.vpn_name.take());
+        props.username = config
+            .username
+            .map(String::into_bytes)
+            .or(props.username.take());
+        props.password = config
+            .password
+            .map(String::into_bytes)
+            .or(props.password.take());
+        props.client_name = config
+            .client_name
+            .map(String::into_bytes)
+            .or(props.client_name.take());
+        props.application_description = config
+            .application_description
+            .map(String::into_bytes)
+            .or(props.application_description.take());
+
+        props.buffer_size_bytes = config.buffer_size_bytes.or(props.buffer_size_bytes);
+        props.block_write_timeout_ms = config
+            .block_write_timeout_ms
+            .or(props.block_write_timeout_ms);
+        props.connect_timeout_ms = config.connect_timeout_ms.or(props.connect_timeout_ms);
+        props.subconfirm_timeout_ms = config.subconfirm_timeout_ms.or(props.subconfirm_timeout_ms);
+        props.ignore_dup_subscription_error = config
+            .ignore_dup_subscription_error
+            .or(props.ignore_dup_subscription_error);
+        props.tcp_nodelay = config.tcp_nodelay.or(props.tcp_nodelay);
+        props.socket_send_buf_size_bytes = config
+            .socket_send_buf_size_bytes
+            .or(props.socket_send_buf_size_bytes);
+        props.socket_rcv_buf_size_bytes = config
+            .socket_rcv_buf_size_bytes
+            .or(props.socket_rcv_buf_size_bytes);
+        props.bind_ip = config
+            .bind_ip
+            .map(String::into_bytes)
+            .or(props.bind_ip.take());
+        props.keep_alive_interval_ms = config
+            .keep_alive_interval_ms
+            .or(props.keep_alive_interval_ms);
+        props.keep_alive_limit = config.keep_alive_limit.or(props.keep_alive_limit);
+        props.compression_level = config.compression_level.or(props.compression_level);
+        props.generate_rcv_timestamps = config
+            .generate_rcv_timestamps
+            .or(props.generate_rcv_timestamps);
+        props.generate_send_timestamp = config
+            .generate_send_timestamp
+            .or(props.generate_send_timestamp);
+        props.generate_sender_id = config.generate_sender_id.or(props.generate_sender_id);
+        props.generate_sender_sequence_number = config
+            .generate_sender_sequence_number
+            .or(props.generate_sender_sequence_number);
+        props.connect_retries_per_host = config
+            .connect_retries_per_host
+            .or(props.connect_retries_per_host);
+        props.connect_retries = config.connect_retries.or(props.connect_retries);
+        props.reconnect_retries = config.reconnect_retries.or(props.reconnect_retries);
+        props.reconnect_retry_wait_ms = config
+            .reconnect_retry_wait_ms
+            .or(props.reconnect_retry_wait_ms);
+        props.reapply_subscriptions = config.reapply_subscriptions.or(props.reapply_subscriptions);
+        props.provision_timeout_ms = config.provision_timeout_ms.or(props.provision_timeout_ms);
+        props.calculate_message_expiration = config
+            .calculate_message_expiration
+            .or(props.calculate_message_expiration);
+        props.no_local = config.no_local.or(props.no_local);
+        props.subscribe_blocking = config.subscribe_blocking.or(props.subscribe_blocking);
+        props.ack_event_mode = config.ack_event_mode.or(props.ack_event_mode);
+        props.modifyprop_timeout_ms = config.modifyprop_timeout_ms.or(props.modifyprop_timeout_ms);
+        props.pub_window_size = config.pub_window_size.or(props.pub_window_size);
+        props.pub_ack_timer_ms = config.pub_ack_timer_ms.or(props.pub_ack_timer_ms);
+        props.ad_pub_router_windowed_ack = config
+            .ad_pub_router_windowed_ack
+            .or(props.ad_pub_router_windowed_ack);
+        props.unbind_fail_action = config.unbind_fail_action.or(props.unbind_fail_action);
+        props.resubscribe_strategy = config.resubscribe_strategy.or(props.resubscribe_strategy);
+
+        props.ssl_trust_store_dir = config
+            .ssl_trust_store_dir
+            .map(String::into_bytes)
+            .or(props.ssl_trust_store_dir.take());
+        props.ssl_downgrade_to_plaintext = config
+            .ssl_downgrade_to_plaintext
+            .or(props.ssl_downgrade_to_plaintext);
+        props.ssl_cipher_suites = config
+            .ssl_cipher_suites
+            .map(String::into_bytes)
+            .or(props.ssl_cipher_suites.take());
+        props.ssl_excluded_protocols = config
+            .ssl_excluded_protocols
+            .map(String::into_bytes)
+            .or(props.ssl_excluded_protocols.take());
+        props.ssl_validate_certificate = config
+            .ssl_validate_certificate
+            .or(props.ssl_validate_certificate);
+        props.ssl_validate_certificate_date = config
+            .ssl_validate_certificate_date
+            .or(props.ssl_validate_certificate_date);
+        props.ssl_trusted_common_name_list = config
+            .ssl_trusted_common_name_list
+            .map(String::into_bytes)
+            .or(props.ssl_trusted_common_name_list.take());
+
+        builder
+    }
 }
 
 struct CheckedSessionProps {
@@ -414,6 +1302,7 @@ struct CheckedSessionProps {
     tcp_nodelay: Option<bool>,
     socket_send_buf_size_bytes: Option<CString>,
     socket_rcv_buf_size_bytes: Option<CString>,
+    bind_ip: Option<CString>,
     keep_alive_interval_ms: Option<CString>,
     keep_alive_limit: Option<CString>,
     application_description: Option<CString>,
@@ -431,139 +1320,280 @@ struct CheckedSessionProps {
     provision_timeout_ms: Option<CString>,
     calculate_message_expiration: Option<bool>,
     no_local: Option<bool>,
+    subscribe_blocking: Option<bool>,
+    ack_event_mode: Option<AckEventMode>,
     modifyprop_timeout_ms: Option<CString>,
+    pub_window_size: Option<CString>,
+    pub_ack_timer_ms: Option<CString>,
+    ad_pub_router_windowed_ack: Option<bool>,
+    unbind_fail_action: Option<UnbindFailAction>,
+    resubscribe_strategy: Option<ResubscribeStrategy>,
+    ssl_trust_store_dir: Option<CString>,
+    ssl_downgrade_to_plaintext: Option<bool>,
+    ssl_cipher_suites: Option<CString>,
+    ssl_excluded_protocols: Option<CString>,
+    ssl_validate_certificate: Option<bool>,
+    ssl_validate_certificate_date: Option<bool>,
+    ssl_trusted_common_name_list: Option<CString>,
+    oauth2_access_token: Option<CString>,
+    oauth2_issuer_identifier: Option<CString>,
+}
+
+impl fmt::Debug for CheckedSessionProps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionProps")
+            .field("host_name", &self.host_name)
+            .field("vpn_name", &self.vpn_name)
+            .field("username", &self.username)
+            .field("password", &Redacted)
+            .field("buffer_size_bytes", &self.buffer_size_bytes)
+            .field("block_write_timeout_ms", &self.block_write_timeout_ms)
+            .field("connect_timeout_ms", &self.connect_timeout_ms)
+            .field("subconfirm_timeout_ms", &self.subconfirm_timeout_ms)
+            .field(
+                "ignore_dup_subscription_error",
+                &self.ignore_dup_subscription_error,
+            )
+            .field("tcp_nodelay", &self.tcp_nodelay)
+            .field(
+                "socket_send_buf_size_bytes",
+                &self.socket_send_buf_size_bytes,
+            )
+            .field("socket_rcv_buf_size_bytes", &self.socket_rcv_buf_size_bytes)
+            .field("bind_ip", &self.bind_ip)
+            .field("keep_alive_interval_ms", &self.keep_alive_interval_ms)
+            .field("keep_alive_limit", &self.keep_alive_limit)
+            .field("application_description", &self.application_description)
+            .field("client_name", &self.client_name)
+            .field("compression_level", &self.compression_level)
+            .field("generate_rcv_timestamps", &self.generate_rcv_timestamps)
+            .field("generate_send_timestamp", &self.generate_send_timestamp)
+            .field("generate_sender_id", &self.generate_sender_id)
+            .field(
+                "generate_sender_sequence_number",
+                &self.generate_sender_sequence_number,
+            )
+            .field("connect_retries_per_host", &self.connect_retries_per_host)
+            .field("connect_retries", &self.connect_retries)
+            .field("reconnect_retries", &self.reconnect_retries)
+            .field("reconnect_retry_wait_ms", &self.reconnect_retry_wait_ms)
+            .field("reapply_subscriptions", &self.reapply_subscriptions)
+            .field("provision_timeout_ms", &self.provision_timeout_ms)
+            .field(
+                "calculate_message_expiration",
+                &self.calculate_message_expiration,
+            )
+            .field("no_local", &self.no_local)
+            .field("subscribe_blocking", &self.subscribe_blocking)
+            .field("ack_event_mode", &self.ack_event_mode)
+            .field("modifyprop_timeout_ms", &self.modifyprop_timeout_ms)
+            .field("pub_window_size", &self.pub_window_size)
+            .field("pub_ack_timer_ms", &self.pub_ack_timer_ms)
+            .field(
+                "ad_pub_router_windowed_ack",
+                &self.ad_pub_router_windowed_ack,
+            )
+            .field("unbind_fail_action", &self.unbind_fail_action)
+            .field("resubscribe_strategy", &self.resubscribe_strategy)
+            .field("ssl_trust_store_dir", &self.ssl_trust_store_dir)
+            .field(
+                "ssl_downgrade_to_plaintext",
+                &self.ssl_downgrade_to_plaintext,
+            )
+            .field("ssl_cipher_suites", &self.ssl_cipher_suites)
+            .field("ssl_excluded_protocols", &self.ssl_excluded_protocols)
+            .field("ssl_validate_certificate", &self.ssl_validate_certificate)
+            .field(
+                "ssl_validate_certificate_date",
+                &self.ssl_validate_certificate_date,
+            )
+            .field(
+                "ssl_trusted_common_name_list",
+                &self.ssl_trusted_common_name_list,
+            )
+            .field(
+                "oauth2_access_token",
+                &self.oauth2_access_token.as_ref().map(|_| Redacted),
+            )
+            .field("oauth2_issuer_identifier", &self.oauth2_issuer_identifier)
+            .finish()
+    }
 }
 
 impl CheckedSessionProps {
-    fn to_raw(&self) -> Vec<*const i8> {
-        let mut props = vec![
-            ffi::SOLCLIENT_SESSION_PROP_HOST.as_ptr() as *const i8,
-            self.host_name.as_ptr(),
-            ffi::SOLCLIENT_SESSION_PROP_VPN_NAME.as_ptr() as *const i8,
-            self.vpn_name.as_ptr(),
-            ffi::SOLCLIENT_SESSION_PROP_USERNAME.as_ptr() as *const i8,
-            self.username.as_ptr(),
-            ffi::SOLCLIENT_SESSION_PROP_PASSWORD.as_ptr() as *const i8,
-            self.password.as_ptr(),
-            ffi::SOLCLIENT_SESSION_PROP_CONNECT_BLOCKING.as_ptr() as *const i8,
-            ffi::SOLCLIENT_PROP_ENABLE_VAL.as_ptr() as *const i8,
-        ];
-
-        if let Some(x) = &self.buffer_size_bytes {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_BUFFER_SIZE.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
+    fn to_raw(&self) -> PropList<'_> {
+        let mut props = PropList::new();
+        props.push(ffi::SOLCLIENT_SESSION_PROP_HOST, &self.host_name);
+        props.push(ffi::SOLCLIENT_SESSION_PROP_VPN_NAME, &self.vpn_name);
+        props.push(ffi::SOLCLIENT_SESSION_PROP_USERNAME, &self.username);
+        props.push(ffi::SOLCLIENT_SESSION_PROP_PASSWORD, &self.password);
+        props.push_static(
+            ffi::SOLCLIENT_SESSION_PROP_CONNECT_BLOCKING,
+            ffi::SOLCLIENT_PROP_ENABLE_VAL,
+        );
 
-        if let Some(x) = &self.block_write_timeout_ms {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_BLOCKING_WRITE_TIMEOUT_MS.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-        if let Some(x) = &self.connect_timeout_ms {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_CONNECT_TIMEOUT_MS.as_ptr() as *const i8);
-            props.push(x.as_ptr());
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_BUFFER_SIZE,
+            &self.buffer_size_bytes,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_BLOCKING_WRITE_TIMEOUT_MS,
+            &self.block_write_timeout_ms,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_CONNECT_TIMEOUT_MS,
+            &self.connect_timeout_ms,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_SUBCONFIRM_TIMEOUT_MS,
+            &self.subconfirm_timeout_ms,
+        );
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_IGNORE_DUP_SUBSCRIPTION_ERROR,
+            self.ignore_dup_subscription_error,
+        );
+        props.push_opt_bool(ffi::SOLCLIENT_SESSION_PROP_TCP_NODELAY, self.tcp_nodelay);
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_SOCKET_SEND_BUF_SIZE,
+            &self.socket_send_buf_size_bytes,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_SOCKET_RCV_BUF_SIZE,
+            &self.socket_rcv_buf_size_bytes,
+        );
+        props.push_opt(ffi::SOLCLIENT_SESSION_PROP_BIND_IP, &self.bind_ip);
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_KEEP_ALIVE_INT_MS,
+            &self.keep_alive_interval_ms,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_KEEP_ALIVE_LIMIT,
+            &self.keep_alive_limit,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_APPLICATION_DESCRIPTION,
+            &self.application_description,
+        );
+        props.push_opt(ffi::SOLCLIENT_SESSION_PROP_CLIENT_NAME, &self.client_name);
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_COMPRESSION_LEVEL,
+            &self.compression_level,
+        );
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_GENERATE_RCV_TIMESTAMPS,
+            self.generate_rcv_timestamps,
+        );
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_GENERATE_SEND_TIMESTAMPS,
+            self.generate_send_timestamp,
+        );
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_GENERATE_SENDER_ID,
+            self.generate_sender_id,
+        );
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_GENERATE_SEQUENCE_NUMBER,
+            self.generate_sender_sequence_number,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_CONNECT_RETRIES_PER_HOST,
+            &self.connect_retries_per_host,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_CONNECT_RETRIES,
+            &self.connect_retries,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_RECONNECT_RETRIES,
+            &self.reconnect_retries,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_RECONNECT_RETRY_WAIT_MS,
+            &self.reconnect_retry_wait_ms,
+        );
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_REAPPLY_SUBSCRIPTIONS,
+            self.reapply_subscriptions,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_PROVISION_TIMEOUT_MS,
+            &self.provision_timeout_ms,
+        );
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_CALCULATE_MESSAGE_EXPIRATION,
+            self.calculate_message_expiration,
+        );
+        props.push_opt_bool(ffi::SOLCLIENT_SESSION_PROP_NO_LOCAL, self.no_local);
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_SUBSCRIBE_BLOCKING,
+            self.subscribe_blocking,
+        );
+        if let Some(x) = self.ack_event_mode {
+            props.push_static(ffi::SOLCLIENT_SESSION_PROP_ACK_EVENT_MODE, x.as_prop());
         }
-
-        if let Some(x) = &self.subconfirm_timeout_ms {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_SUBCONFIRM_TIMEOUT_MS.as_ptr() as *const i8);
-            props.push(x.as_ptr());
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_MODIFYPROP_TIMEOUT_MS,
+            &self.modifyprop_timeout_ms,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_PUB_WINDOW_SIZE,
+            &self.pub_window_size,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_PUB_ACK_TIMER,
+            &self.pub_ack_timer_ms,
+        );
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_AD_PUB_ROUTER_WINDOWED_ACK,
+            self.ad_pub_router_windowed_ack,
+        );
+        if let Some(x) = self.unbind_fail_action {
+            props.push_static(ffi::SOLCLIENT_SESSION_PROP_UNBIND_FAIL_ACTION, x.as_prop());
         }
-        if let Some(x) = &self.ignore_dup_subscription_error {
-            props.push(
-                ffi::SOLCLIENT_SESSION_PROP_IGNORE_DUP_SUBSCRIPTION_ERROR.as_ptr() as *const i8,
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_SSL_TRUST_STORE_DIR,
+            &self.ssl_trust_store_dir,
+        );
+        if let Some(true) = self.ssl_downgrade_to_plaintext {
+            props.push_static(
+                ffi::SOLCLIENT_SESSION_PROP_SSL_CONNECTION_DOWNGRADE_TO,
+                ffi::SOLCLIENT_TRANSPORT_PROTOCOL_PLAIN_TEXT,
             );
-            props.push(bool_to_ptr(*x));
-        }
-
-        if let Some(x) = &self.tcp_nodelay {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_TCP_NODELAY.as_ptr() as *const i8);
-            props.push(bool_to_ptr(*x));
-        }
-        if let Some(x) = &self.socket_send_buf_size_bytes {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_SOCKET_SEND_BUF_SIZE.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-
-        if let Some(x) = &self.socket_rcv_buf_size_bytes {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_SOCKET_RCV_BUF_SIZE.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-        if let Some(x) = &self.keep_alive_interval_ms {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_KEEP_ALIVE_INT_MS.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-        if let Some(x) = &self.keep_alive_limit {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_KEEP_ALIVE_LIMIT.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-        if let Some(x) = &self.application_description {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_APPLICATION_DESCRIPTION.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-        if let Some(x) = &self.client_name {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_CLIENT_NAME.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-
-        if let Some(x) = &self.compression_level {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_COMPRESSION_LEVEL.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-        if let Some(x) = &self.generate_rcv_timestamps {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_GENERATE_RCV_TIMESTAMPS.as_ptr() as *const i8);
-            props.push(bool_to_ptr(*x));
-        }
-        if let Some(x) = &self.generate_send_timestamp {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_GENERATE_SEND_TIMESTAMPS.as_ptr() as *const i8);
-            props.push(bool_to_ptr(*x));
-        }
-        if let Some(x) = &self.generate_sender_id {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_GENERATE_SENDER_ID.as_ptr() as *const i8);
-            props.push(bool_to_ptr(*x));
-        }
-        if let Some(x) = &self.generate_sender_sequence_number {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_GENERATE_SEQUENCE_NUMBER.as_ptr() as *const i8);
-            props.push(bool_to_ptr(*x));
-        }
-        if let Some(x) = &self.connect_retries_per_host {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_CONNECT_RETRIES_PER_HOST.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-        if let Some(x) = &self.connect_retries {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_CONNECT_RETRIES.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-        if let Some(x) = &self.reconnect_retries {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_RECONNECT_RETRIES.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-        if let Some(x) = &self.reconnect_retry_wait_ms {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_RECONNECT_RETRY_WAIT_MS.as_ptr() as *const i8);
-            props.push(x.as_ptr());
         }
-        if let Some(x) = &self.reapply_subscriptions {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_REAPPLY_SUBSCRIPTIONS.as_ptr() as *const i8);
-            props.push(bool_to_ptr(*x));
-        }
-        if let Some(x) = &self.provision_timeout_ms {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_PROVISION_TIMEOUT_MS.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-        if let Some(x) = &self.calculate_message_expiration {
-            props.push(
-                ffi::SOLCLIENT_SESSION_PROP_CALCULATE_MESSAGE_EXPIRATION.as_ptr() as *const i8,
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_SSL_CIPHER_SUITES,
+            &self.ssl_cipher_suites,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_SSL_EXCLUDED_PROTOCOLS,
+            &self.ssl_excluded_protocols,
+        );
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_SSL_VALIDATE_CERTIFICATE,
+            self.ssl_validate_certificate,
+        );
+        props.push_opt_bool(
+            ffi::SOLCLIENT_SESSION_PROP_SSL_VALIDATE_CERTIFICATE_DATE,
+            self.ssl_validate_certificate_date,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_SSL_TRUSTED_COMMON_NAME_LIST,
+            &self.ssl_trusted_common_name_list,
+        );
+        if self.oauth2_access_token.is_some() {
+            props.push_static(
+                ffi::SOLCLIENT_SESSION_PROP_AUTHENTICATION_SCHEME,
+                ffi::SOLCLIENT_SESSION_PROP_AUTHENTICATION_SCHEME_OAUTH2,
             );
-            props.push(bool_to_ptr(*x));
-        }
-        if let Some(x) = &self.no_local {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_NO_LOCAL.as_ptr() as *const i8);
-            props.push(bool_to_ptr(*x));
         }
-        if let Some(x) = &self.modifyprop_timeout_ms {
-            props.push(ffi::SOLCLIENT_SESSION_PROP_MODIFYPROP_TIMEOUT_MS.as_ptr() as *const i8);
-            props.push(x.as_ptr());
-        }
-
-        props.push(ptr::null());
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_OAUTH2_ACCESS_TOKEN,
+            &self.oauth2_access_token,
+        );
+        props.push_opt(
+            ffi::SOLCLIENT_SESSION_PROP_OAUTH2_ISSUER_IDENTIFIER,
+            &self.oauth2_issuer_identifier,
+        );
 
         props
     }
@@ -579,213 +1609,397 @@ where
 {
     type Error = SessionBuilderError;
 
+    /// Fails with the first invalid field, see [`CheckedSessionProps::check`] for all of them.
     fn try_from(
         value: UncheckedSessionProps<Host, Vpn, Username, Password>,
     ) -> std::prelude::v1::Result<Self, Self::Error> {
+        Self::check(value).map_err(|mut errors| errors.remove(0))
+    }
+}
+
+impl CheckedSessionProps {
+    /// Checks every field and returns all the errors, in the order of the fields.
+    fn check<Host, Vpn, Username, Password>(
+        value: UncheckedSessionProps<Host, Vpn, Username, Password>,
+    ) -> std::result::Result<Self, Vec<SessionBuilderError>>
+    where
+        Host: Into<Vec<u8>>,
+        Vpn: Into<Vec<u8>>,
+        Username: Into<Vec<u8>>,
+        Password: Into<Vec<u8>>,
+    {
+        let mut errors = Vec::new();
+
         let host_name = match value.host_name {
-            Some(x) => CString::new(x)?,
+            Some(x) => collect(&mut errors, to_c_string("host_name", x)),
             None => {
-                return Err(SessionBuilderError::MissingRequiredArgs(
+                errors.push(SessionBuilderError::MissingRequiredArgs(
                     "host_name".to_owned(),
                 ));
+                None
             }
         };
 
         let vpn_name = match value.vpn_name {
-            Some(x) => CString::new(x)?,
+            Some(x) => collect(&mut errors, to_c_string("vpn_name", x)),
             None => {
-                return Err(SessionBuilderError::MissingRequiredArgs(
+                errors.push(SessionBuilderError::MissingRequiredArgs(
                     "vpn_name".to_owned(),
                 ));
+                None
             }
         };
 
         let username = match value.username {
-            Some(x) => CString::new(x)?,
+            Some(x) => collect(&mut errors, to_c_string("username", x)),
             None => {
-                return Err(SessionBuilderError::MissingRequiredArgs(
+                errors.push(SessionBuilderError::MissingRequiredArgs(
                     "username".to_owned(),
                 ));
+                None
             }
         };
 
         let password = match value.password {
-            Some(x) => CString::new(x)?,
+            Some(x) => collect(&mut errors, to_c_string("password", x)),
             None => {
-                return Err(SessionBuilderError::MissingRequiredArgs(
+                errors.push(SessionBuilderError::MissingRequiredArgs(
                     "password".to_owned(),
                 ));
+                None
             }
         };
 
+        // the strategy decides for the C library, an explicit setting has to agree with it
+        let reapply_subscriptions = match (value.resubscribe_strategy, value.reapply_subscriptions)
+        {
+            (Some(strategy), Some(reapply))
+                if reapply != (strategy == ResubscribeStrategy::Native) =>
+            {
+                errors.push(SessionBuilderError::IncompatibleArgs(format!(
+                    "reapply_subscriptions({reapply}) with resubscribe_strategy {strategy:?}"
+                )));
+                None
+            }
+            (Some(strategy), _) => Some(strategy == ResubscribeStrategy::Native),
+            (None, reapply) => reapply,
+        };
+
         let client_name = match value.client_name {
-            Some(x) => Some(CString::new(x)?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("client_name", x)
+                    .and_then(|x| client_name::validate(&x.to_string_lossy()).map(|_| x)),
+            ),
             None => None,
         };
 
         let application_description = match value.application_description {
-            Some(x) => Some(CString::new(x)?),
+            Some(x) => collect(&mut errors, to_c_string("application_description", x)),
             None => None,
         };
 
         let buffer_size_bytes = match value.buffer_size_bytes {
             Some(x) if x < 1 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "buffer_size_bytes".to_owned(),
                     ">= 1".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(b) => Some(CString::new(b.to_string())?),
+            Some(b) => collect(&mut errors, to_c_string("buffer_size_bytes", b.to_string())),
             None => None,
         };
 
         let block_write_timeout_ms = match value.block_write_timeout_ms {
             Some(x) if x < 1 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "block_write_timeout_ms".to_owned(),
                     ">= 1".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("block_write_timeout_ms", x.to_string()),
+            ),
             None => None,
         };
 
         let connect_timeout_ms = match value.connect_timeout_ms {
             Some(x) if x < 1 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "connect_timeout_ms".to_owned(),
                     ">= 1".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("connect_timeout_ms", x.to_string()),
+            ),
             None => None,
         };
 
         let subconfirm_timeout_ms = match value.subconfirm_timeout_ms {
             Some(x) if x < 1000 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "subconfirm_timeout_ms".to_owned(),
                     ">= 1000".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("subconfirm_timeout_ms", x.to_string()),
+            ),
             None => None,
         };
 
         let socket_send_buf_size_bytes = match value.socket_send_buf_size_bytes {
             Some(x) if x != 0 && x < 1024 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "socket_send_buf_size_bytes".to_owned(),
                     "0 or >= 1024".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("socket_send_buf_size_bytes", x.to_string()),
+            ),
             None => None,
         };
 
         let socket_rcv_buf_size_bytes = match value.socket_rcv_buf_size_bytes {
             Some(x) if x != 0 && x < 1024 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "socket_rcv_buf_size_bytes".to_owned(),
                     "0 or >= 1024".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("socket_rcv_buf_size_bytes", x.to_string()),
+            ),
+            None => None,
+        };
+
+        let bind_ip = match value.bind_ip {
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("bind_ip", x).and_then(|x| {
+                    let bind_ip = x.to_string_lossy();
+                    if bind_ip.parse::<IpAddr>().is_ok() || bind_ip.parse::<SocketAddr>().is_ok() {
+                        return Ok(x);
+                    }
+                    Err(SessionBuilderError::InvalidConfig(
+                        "bind_ip".to_owned(),
+                        bind_ip.into_owned(),
+                    ))
+                }),
+            ),
             None => None,
         };
 
         let keep_alive_interval_ms = match value.keep_alive_interval_ms {
             Some(x) if x != 0 && x < 50 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "keep_alive_interval_ms".to_owned(),
                     "0 or >= 50".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("keep_alive_interval_ms", x.to_string()),
+            ),
             None => None,
         };
 
         let keep_alive_limit = match value.keep_alive_limit {
             Some(x) if x < 3 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "keep_alive_limit".to_owned(),
                     ">= 3".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(&mut errors, to_c_string("keep_alive_limit", x.to_string())),
             None => None,
         };
 
         let compression_level = match value.compression_level {
             Some(x) if x > 9 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "compression_level".to_owned(),
                     "<= 9".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(&mut errors, to_c_string("compression_level", x.to_string())),
             None => None,
         };
 
         let connect_retries_per_host = match value.connect_retries_per_host {
             Some(x) if x < -1 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "connect_retries_per_host".to_owned(),
                     ">= -1".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("connect_retries_per_host", x.to_string()),
+            ),
             None => None,
         };
 
         let connect_retries = match value.connect_retries {
             Some(x) if x < -1 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "connect_retries ".to_owned(),
                     ">= -1".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(&mut errors, to_c_string("connect_retries", x.to_string())),
             None => None,
         };
 
         let reconnect_retries = match value.reconnect_retries {
             Some(x) if x < -1 => {
-                return Err(SessionBuilderError::InvalidRange(
+                errors.push(SessionBuilderError::InvalidRange(
                     "reconnect_retries ".to_owned(),
                     ">= -1".to_owned(),
                     x.to_string(),
                 ));
+                None
             }
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(&mut errors, to_c_string("reconnect_retries", x.to_string())),
             None => None,
         };
 
         let reconnect_retry_wait_ms = match value.reconnect_retry_wait_ms {
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("reconnect_retry_wait_ms", x.to_string()),
+            ),
             None => None,
         };
 
         let provision_timeout_ms = match value.provision_timeout_ms {
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("provision_timeout_ms", x.to_string()),
+            ),
             None => None,
         };
         let modifyprop_timeout_ms = match value.modifyprop_timeout_ms {
-            Some(x) => Some(CString::new(x.to_string())?),
+            Some(x) => collect(
+                &mut errors,
+                to_c_string("modifyprop_timeout_ms", x.to_string()),
+            ),
+            None => None,
+        };
+
+        let pub_window_size = match value.pub_window_size {
+            Some(x) if !(1..=255).contains(&x) => {
+                errors.push(SessionBuilderError::InvalidRange(
+                    "pub_window_size".to_owned(),
+                    "1 to 255".to_owned(),
+                    x.to_string(),
+                ));
+                None
+            }
+            Some(x) => collect(&mut errors, to_c_string("pub_window_size", x.to_string())),
+            None => None,
+        };
+
+        let pub_ack_timer_ms = match value.pub_ack_timer_ms {
+            Some(x) if !(20..=60000).contains(&x) => {
+                errors.push(SessionBuilderError::InvalidRange(
+                    "pub_ack_timer_ms".to_owned(),
+                    "20 to 60000".to_owned(),
+                    x.to_string(),
+                ));
+                None
+            }
+            Some(x) => collect(&mut errors, to_c_string("pub_ack_timer_ms", x.to_string())),
+            None => None,
+        };
+
+        let ssl_trust_store_dir = match value.ssl_trust_store_dir {
+            Some(x) => collect(&mut errors, to_c_string("ssl_trust_store_dir", x)),
             None => None,
         };
 
+        let ssl_cipher_suites = match value.ssl_cipher_suites {
+            Some(x) => collect(&mut errors, to_c_string("ssl_cipher_suites", x)),
+            None => None,
+        };
+
+        let ssl_excluded_protocols = match value.ssl_excluded_protocols {
+            Some(x) => collect(&mut errors, to_c_string("ssl_excluded_protocols", x)),
+            None => None,
+        };
+
+        let ssl_trusted_common_name_list = match value.ssl_trusted_common_name_list {
+            Some(x) => collect(&mut errors, to_c_string("ssl_trusted_common_name_list", x)),
+            None => None,
+        };
+
+        let oauth2_access_token = match value.oauth2_access_token {
+            Some(x) => collect(&mut errors, to_c_string("oauth2_access_token", x)),
+            None => None,
+        };
+
+        let oauth2_issuer_identifier = match value.oauth2_issuer_identifier {
+            Some(x) => collect(&mut errors, to_c_string("oauth2_issuer_identifier", x)),
+            None => None,
+        };
+
+        if value.ssl_downgrade_to_plaintext == Some(true) {
+            // the host list is comma separated, the downgrade only applies to TLS connections
+            // a missing host is already reported
+            let all_tls = host_name.as_ref().map_or(true, |host_name| {
+                host_name
+                    .to_string_lossy()
+                    .split(',')
+                    .all(|host| host.trim().to_ascii_lowercase().starts_with("tcps:"))
+            });
+            if !all_tls {
+                errors.push(SessionBuilderError::IncompatibleArgs(
+                    "ssl_downgrade_to_plaintext requires tcps hosts".to_owned(),
+                ));
+            }
+
+            if value.compression_level.is_some_and(|x| x > 0) {
+                errors.push(SessionBuilderError::IncompatibleArgs(
+                    "ssl_downgrade_to_plaintext can not be used with compression".to_owned(),
+                ));
+            }
+        }
+
+        let (Some(host_name), Some(vpn_name), Some(username), Some(password), true) =
+            (host_name, vpn_name, username, password, errors.is_empty())
+        else {
+            return Err(errors);
+        };
+
         Ok(Self {
             host_name,
             vpn_name,
@@ -799,6 +2013,7 @@ where
             tcp_nodelay: value.tcp_nodelay,
             socket_send_buf_size_bytes,
             socket_rcv_buf_size_bytes,
+            bind_ip,
             keep_alive_interval_ms,
             keep_alive_limit,
             application_description,
@@ -812,11 +2027,242 @@ where
             connect_retries,
             reconnect_retries,
             reconnect_retry_wait_ms,
-            reapply_subscriptions: value.reapply_subscriptions,
+            reapply_subscriptions,
             provision_timeout_ms,
             calculate_message_expiration: value.calculate_message_expiration,
             no_local: value.no_local,
+            subscribe_blocking: value.subscribe_blocking,
+            ack_event_mode: value.ack_event_mode,
             modifyprop_timeout_ms,
+            pub_window_size,
+            pub_ack_timer_ms,
+            ad_pub_router_windowed_ack: value.ad_pub_router_windowed_ack,
+            unbind_fail_action: value.unbind_fail_action,
+            resubscribe_strategy: value.resubscribe_strategy,
+            ssl_trust_store_dir,
+            ssl_downgrade_to_plaintext: value.ssl_downgrade_to_plaintext,
+            ssl_cipher_suites,
+            ssl_excluded_protocols,
+            ssl_validate_certificate: value.ssl_validate_certificate,
+            ssl_validate_certificate_date: value.ssl_validate_certificate_date,
+            ssl_trusted_common_name_list,
+            oauth2_access_token,
+            oauth2_issuer_identifier,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn props(host_name: &str) -> UncheckedSessionProps<&str, &str, &str, &str> {
+        UncheckedSessionProps {
+            host_name: Some(host_name),
+            vpn_name: Some("default"),
+            username: Some("default"),
+            password: Some(""),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_should_allow_ssl_downgrade_with_tcps_hosts() {
+        let mut props = props("tcps://host1:55443, TCPS://host2:55443");
+        props.ssl_downgrade_to_plaintext = Some(true);
+
+        assert!(CheckedSessionProps::try_from(props).is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_ssl_downgrade_with_plain_host() {
+        let mut props = props("tcps://host1:55443,tcp://host2:55555");
+        props.ssl_downgrade_to_plaintext = Some(true);
+
+        assert!(matches!(
+            CheckedSessionProps::try_from(props),
+            Err(SessionBuilderError::IncompatibleArgs(_))
+        ));
+    }
+
+    #[test]
+    fn it_should_reject_ssl_downgrade_with_compression() {
+        let mut props = props("tcps://host1:55443");
+        props.ssl_downgrade_to_plaintext = Some(true);
+        props.compression_level = Some(5);
+
+        assert!(matches!(
+            CheckedSessionProps::try_from(props),
+            Err(SessionBuilderError::IncompatibleArgs(_))
+        ));
+    }
+
+    #[test]
+    fn it_should_validate_publisher_window_ranges() {
+        let mut tuned = props("tcp://host:55555");
+        tuned.pub_window_size = Some(256);
+        assert!(matches!(
+            CheckedSessionProps::try_from(tuned),
+            Err(SessionBuilderError::InvalidRange(..))
+        ));
+
+        let mut tuned = props("tcp://host:55555");
+        tuned.pub_ack_timer_ms = Some(10);
+        assert!(matches!(
+            CheckedSessionProps::try_from(tuned),
+            Err(SessionBuilderError::InvalidRange(..))
+        ));
+
+        let mut tuned = props("tcp://host:55555");
+        tuned.pub_window_size = Some(255);
+        tuned.pub_ack_timer_ms = Some(60000);
+        tuned.unbind_fail_action = Some(UnbindFailAction::Disconnect);
+        assert!(CheckedSessionProps::try_from(tuned).is_ok());
+    }
+
+    #[test]
+    fn it_should_report_every_invalid_field() {
+        let mut invalid = props("tcp://host:55555");
+        invalid.username = None;
+        invalid.pub_window_size = Some(0);
+        invalid.compression_level = Some(10);
+        invalid.client_name = Some(b"a\0b".to_vec());
+
+        let errors = CheckedSessionProps::check(invalid).err().unwrap();
+        assert_eq!(4, errors.len());
+        assert!(matches!(
+            errors[0],
+            SessionBuilderError::MissingRequiredArgs(ref field) if field == "username"
+        ));
+    }
+
+    #[test]
+    fn it_should_reject_a_zero_ack_timeout() {
+        assert!(matches!(
+            check_ack_timeout(Some(Duration::ZERO)),
+            Err(SessionBuilderError::InvalidRange(ref field, ..)) if field == "ack_timeout"
+        ));
+        assert!(check_ack_timeout(Some(Duration::from_millis(1))).is_ok());
+        assert!(check_ack_timeout(None).is_ok());
+    }
+
+    #[test]
+    fn it_should_derive_reapply_subscriptions_from_strategy() {
+        let mut managed = props("tcp://host:55555");
+        managed.resubscribe_strategy = Some(ResubscribeStrategy::Managed);
+        let checked = CheckedSessionProps::try_from(managed).unwrap();
+        assert_eq!(Some(false), checked.reapply_subscriptions);
+
+        let mut native = props("tcp://host:55555");
+        native.resubscribe_strategy = Some(ResubscribeStrategy::Native);
+        native.reapply_subscriptions = Some(true);
+        let checked = CheckedSessionProps::try_from(native).unwrap();
+        assert_eq!(Some(true), checked.reapply_subscriptions);
+
+        let mut conflicting = props("tcp://host:55555");
+        conflicting.resubscribe_strategy = Some(ResubscribeStrategy::Managed);
+        conflicting.reapply_subscriptions = Some(true);
+        assert!(matches!(
+            CheckedSessionProps::try_from(conflicting),
+            Err(SessionBuilderError::IncompatibleArgs(..))
+        ));
+    }
+
+    #[test]
+    fn it_should_validate_client_name_length() {
+        let mut named = props("tcp://host:55555");
+        named.client_name = Some(vec![b'a'; 161]);
+        assert!(matches!(
+            CheckedSessionProps::try_from(named),
+            Err(SessionBuilderError::InvalidRange(..))
+        ));
+
+        let mut named = props("tcp://host:55555");
+        named.client_name = Some(crate::session::ClientName::generate("app").unwrap().into());
+        assert!(CheckedSessionProps::try_from(named).is_ok());
+    }
+
+    #[test]
+    fn it_should_redact_the_password() {
+        let mut props = props("tcp://host:55555");
+        props.password = Some("hunter2");
+        props.client_name = Some(b"client".to_vec());
+
+        let unchecked = format!("{props:?}");
+        let checked = format!("{:?}", CheckedSessionProps::try_from(props).unwrap());
+
+        for debug in [unchecked, checked] {
+            assert!(debug.contains("client"), "{debug}");
+            assert!(!debug.contains("hunter2"), "{debug}");
+        }
+    }
+
+    #[test]
+    fn it_should_validate_bind_ip() {
+        for bind_ip in ["10.0.0.1", "10.0.0.1:0", "::1", "[fe80::1]:50000"] {
+            let mut bound = props("tcp://host:55555");
+            bound.bind_ip = Some(bind_ip.into());
+            assert!(CheckedSessionProps::try_from(bound).is_ok(), "{bind_ip}");
+        }
+
+        let mut bound = props("tcp://host:55555");
+        bound.bind_ip = Some(b"eth0".to_vec());
+        assert!(matches!(
+            CheckedSessionProps::try_from(bound),
+            Err(SessionBuilderError::InvalidConfig(..))
+        ));
+    }
+
+    #[test]
+    fn it_should_redact_the_oauth2_access_token() {
+        let mut props = props("tcp://host:55555");
+        props.oauth2_access_token = Some(b"eyJhbGciOi".to_vec());
+        props.oauth2_issuer_identifier = Some(b"https://issuer".to_vec());
+
+        let unchecked = format!("{props:?}");
+        let checked = format!("{:?}", CheckedSessionProps::try_from(props).unwrap());
+
+        for debug in [unchecked, checked] {
+            assert!(debug.contains("https://issuer"), "{debug}");
+            assert!(!debug.contains("eyJhbGciOi"), "{debug}");
+        }
+    }
+
+    #[test]
+    fn it_should_override_the_config_with_later_builder_calls() {
+        let config = SessionConfig::from_lookup("SOLACE_", |key| match key {
+            "SOLACE_HOST_NAME" => Some("tcp://config:55555".to_owned()),
+            "SOLACE_USERNAME" => Some("config-user".to_owned()),
+            _ => None,
         })
+        .unwrap();
+
+        let context = crate::Context::new(crate::SolaceLogLevel::Warning).unwrap();
+        type Builder = SessionBuilder<
+            Vec<u8>,
+            Vec<u8>,
+            Vec<u8>,
+            Vec<u8>,
+            fn(crate::message::InboundMessage),
+            fn(SessionEvent),
+        >;
+
+        let builder: Builder = context
+            .session_builder::<&str, &str, &str, &str, _, _>()
+            .vpn_name("vpn")
+            .username("builder-user")
+            .config(&config)
+            .password("secret".into());
+
+        let props = &builder.props;
+        assert_eq!(Some(b"tcp://config:55555".to_vec()), props.host_name);
+        assert_eq!(Some(b"vpn".to_vec()), props.vpn_name);
+        assert_eq!(Some(b"config-user".to_vec()), props.username);
+        assert_eq!(Some(b"secret".to_vec()), props.password);
+
+        let builder: Builder = context
+            .session_builder_from_config(&config)
+            .username("override".into());
+        assert_eq!(Some(b"override".to_vec()), builder.props.username);
     }
 }
@@ -0,0 +1,165 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use solace_rs_sys as ffi;
+use tracing::{debug, warn};
+
+use super::state::SessionState;
+use super::token::TokenRefresh;
+use super::{republish_unacked, resubscribe};
+use super::{DisconnectReason, SessionEvent, SessionEventInfo};
+use crate::context::CallbackKind;
+use crate::message::{codec, InboundMessage};
+
+/// Receives every message from the context thread and forwards it to the user callback.
+pub(crate) struct MessageHandler<M> {
+    state: Arc<SessionState>,
+    on_message: M,
+    slow_threshold: Option<Duration>,
+}
+
+impl<M: FnMut(InboundMessage)> MessageHandler<M> {
+    pub(crate) fn new(
+        state: Arc<SessionState>,
+        on_message: M,
+        slow_threshold: Option<Duration>,
+    ) -> Self {
+        Self {
+            state,
+            on_message,
+            slow_threshold,
+        }
+    }
+
+    /// Returns `false` if the message was not dispatched because the session is closing, in which
+    /// case the C library keeps ownership of it.
+    pub(crate) fn handle(&mut self, msg_p: ffi::solClient_opaqueMsg_pt) -> bool {
+        let Some(_guard) = self.state.enter_callback(CallbackKind::Message) else {
+            return false;
+        };
+
+        self.state.message_received();
+        let message = InboundMessage::from(msg_p);
+        if let Some(codec) = self.state.codec() {
+            if let Err(e) = codec::decode(codec, &message) {
+                warn!(
+                    session_id = self.state.id(),
+                    client_name = self.state.client_name(),
+                    "passing message with undecoded payload: {e}"
+                );
+            }
+        }
+
+        let Some(threshold) = self.slow_threshold else {
+            (self.on_message)(message);
+            return true;
+        };
+
+        let start = Instant::now();
+        (self.on_message)(message);
+        let elapsed = start.elapsed();
+
+        if elapsed > threshold {
+            self.state.slow_callback();
+            warn!(
+                session_id = self.state.id(),
+                client_name = self.state.client_name(),
+                "on_message callback took {elapsed:?}, over the {threshold:?} threshold"
+            );
+        }
+
+        true
+    }
+}
+
+pub(crate) type EventInfoCallback = Box<dyn FnMut(&SessionEventInfo) + Send>;
+
+pub(crate) type AckTimeoutCallback = Box<dyn FnMut(usize) + Send>;
+
+/// Receives every session event from the context thread, updates the session bookkeeping and then
+/// forwards the event to the user callback, if any.
+pub(crate) struct EventHandler<E> {
+    state: Arc<SessionState>,
+    on_event: Option<E>,
+    on_event_info: Option<EventInfoCallback>,
+    // None forwards every event
+    filter: Option<Vec<SessionEvent>>,
+    // refreshes the token when the broker refuses it
+    token_refresh: Option<Arc<TokenRefresh>>,
+}
+
+impl<E: FnMut(SessionEvent)> EventHandler<E> {
+    pub(crate) fn new(
+        state: Arc<SessionState>,
+        on_event: Option<E>,
+        on_event_info: Option<EventInfoCallback>,
+        filter: Option<Vec<SessionEvent>>,
+        token_refresh: Option<Arc<TokenRefresh>>,
+    ) -> Self {
+        Self {
+            state,
+            on_event,
+            on_event_info,
+            filter,
+            token_refresh,
+        }
+    }
+
+    pub(crate) fn handle(
+        &mut self,
+        session_ptr: ffi::solClient_opaqueSession_pt,
+        event: SessionEvent,
+        info: &ffi::solClient_session_eventCallbackInfo,
+    ) {
+        let Some(_guard) = self.state.enter_callback(CallbackKind::Event) else {
+            return;
+        };
+
+        // the retained copies are taken before the unacked messages are reset
+        let retained = match event {
+            SessionEvent::RepublishUnackedMessages => self.state.take_retained(),
+            _ => None,
+        };
+        self.state.on_event(event, info);
+        if let Some(retained) = retained {
+            republish_unacked(session_ptr, &self.state, retained);
+        }
+        if event == SessionEvent::ReconnectedNotice && self.state.manages_resubscribe() {
+            resubscribe(session_ptr, &self.state);
+        }
+        if let Some(token_refresh) = &self.token_refresh {
+            let reason = SessionEventInfo::new(event, info).disconnect_reason();
+            if reason == Some(DisconnectReason::LoginFailure) {
+                token_refresh.refresh_now();
+            }
+        }
+
+        if self.on_event.is_none() && self.on_event_info.is_none() {
+            return;
+        }
+
+        if self.filter.as_ref().is_some_and(|f| !f.contains(&event)) {
+            debug!(
+                session_id = self.state.id(),
+                client_name = self.state.client_name(),
+                "filtered session event {event}"
+            );
+            return;
+        }
+
+        if let Some(on_event_info) = self.on_event_info.as_mut() {
+            on_event_info(&SessionEventInfo::new(event, info));
+        }
+        if let Some(on_event) = self.on_event.as_mut() {
+            on_event(event);
+        }
+    }
+
+    pub(crate) fn unknown_event(&self, raw_event: u32) {
+        warn!(
+            session_id = self.state.id(),
+            client_name = self.state.client_name(),
+            "received unknown session event {raw_event}"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::SessionBuilderError;
+
+type Result<T> = std::result::Result<T, SessionBuilderError>;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Validated client name, accepted by
+/// [`SessionBuilder::client_name`](super::SessionBuilder::client_name).
+///
+/// The broker disconnects a session when another one connects with the same client name, so
+/// sessions that are not meant to replace each other need distinct names. Without a client name
+/// the C library generates a unique one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClientName(String);
+
+impl ClientName {
+    /// Longest client name accepted by the broker, in bytes.
+    pub const MAX_LEN: usize = 160;
+
+    pub fn new<T: Into<String>>(name: T) -> Result<Self> {
+        let name = name.into();
+        validate(&name)?;
+        Ok(Self(name))
+    }
+
+    /// Generates a name unique to the process and host, `{prefix}/{host}/{pid}/{counter}`, with
+    /// the counter incremented on every call.
+    pub fn generate(prefix: &str) -> Result<Self> {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        Self::new(format!(
+            "{prefix}/{}/{}/{id}",
+            host_name(),
+            std::process::id()
+        ))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ClientName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for ClientName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<ClientName> for Vec<u8> {
+    fn from(name: ClientName) -> Self {
+        name.0.into_bytes()
+    }
+}
+
+impl From<ClientName> for String {
+    fn from(name: ClientName) -> Self {
+        name.0
+    }
+}
+
+/// Checks the length of a client name, also used for names set as plain strings.
+pub(crate) fn validate(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > ClientName::MAX_LEN {
+        return Err(SessionBuilderError::InvalidRange(
+            "client_name".to_owned(),
+            format!("1..={} bytes", ClientName::MAX_LEN),
+            name.len().to_string(),
+        ));
+    }
+    if name.contains('\0') {
+        return Err(SessionBuilderError::InvalidConfig(
+            "client_name".to_owned(),
+            name.to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+// std has no portable way to get the host name, the environment covers most setups
+fn host_name() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_generate_unique_names() {
+        let first = ClientName::generate("app").unwrap();
+        let second = ClientName::generate("app").unwrap();
+
+        assert!(first.as_str().starts_with("app/"));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn it_should_validate_length() {
+        assert!(ClientName::new("").is_err());
+        assert!(ClientName::new("a".repeat(ClientName::MAX_LEN)).is_ok());
+        assert!(ClientName::new("a".repeat(ClientName::MAX_LEN + 1)).is_err());
+        assert!(ClientName::generate(&"a".repeat(ClientName::MAX_LEN)).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use super::builder::{AckEventMode, ResubscribeStrategy, SessionBuilderError, UnbindFailAction};
+use crate::util::Redacted;
+
+type Result<T> = std::result::Result<T, SessionBuilderError>;
+
+/// Session properties read from the environment with [`SessionConfig::from_env`] or, with the
+/// `serde` feature, deserialized from a configuration file. Applied to a builder with
+/// [`SessionBuilder::config`](super::SessionBuilder::config), or used as the starting point of a
+/// builder with [`Context::session_builder_from_config`](crate::Context::session_builder_from_config).
+///
+/// Unset fields keep the value of the builder. The fields match the builder methods of the same
+/// name.
+#[derive(Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct SessionConfig {
+    pub host_name: Option<String>,
+    pub vpn_name: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub client_name: Option<String>,
+    pub application_description: Option<String>,
+
+    pub buffer_size_bytes: Option<u64>,
+    pub block_write_timeout_ms: Option<u64>,
+    pub connect_timeout_ms: Option<u64>,
+    pub subconfirm_timeout_ms: Option<u64>,
+    pub ignore_dup_subscription_error: Option<bool>,
+    pub tcp_nodelay: Option<bool>,
+    pub socket_send_buf_size_bytes: Option<u64>,
+    pub socket_rcv_buf_size_bytes: Option<u64>,
+    pub bind_ip: Option<String>,
+    pub keep_alive_interval_ms: Option<u64>,
+    pub keep_alive_limit: Option<u64>,
+    pub compression_level: Option<u8>,
+    pub generate_rcv_timestamps: Option<bool>,
+    pub generate_send_timestamp: Option<bool>,
+    pub generate_sender_id: Option<bool>,
+    pub generate_sender_sequence_number: Option<bool>,
+    pub connect_retries_per_host: Option<i64>,
+    pub connect_retries: Option<i64>,
+    pub reconnect_retries: Option<i64>,
+    pub reconnect_retry_wait_ms: Option<u64>,
+    pub reapply_subscriptions: Option<bool>,
+    pub provision_timeout_ms: Option<u64>,
+    pub calculate_message_expiration: Option<bool>,
+    pub no_local: Option<bool>,
+    pub subscribe_blocking: Option<bool>,
+    pub ack_event_mode: Option<AckEventMode>,
+    pub modifyprop_timeout_ms: Option<u64>,
+    pub pub_window_size: Option<u64>,
+    pub pub_ack_timer_ms: Option<u64>,
+    pub ad_pub_router_windowed_ack: Option<bool>,
+    pub unbind_fail_action: Option<UnbindFailAction>,
+    pub resubscribe_strategy: Option<ResubscribeStrategy>,
+
+    pub ssl_trust_store_dir: Option<String>,
+    pub ssl_downgrade_to_plaintext: Option<bool>,
+    pub ssl_cipher_suites: Option<String>,
+    pub ssl_excluded_protocols: Option<String>,
+    pub ssl_validate_certificate: Option<bool>,
+    pub ssl_validate_certificate_date: Option<bool>,
+    pub ssl_trusted_common_name_list: Option<String>,
+}
+
+impl SessionConfig {
+    /// Reads the config from environment variables named after the fields in upper case, with the
+    /// given prefix. For example `SOLACE_HOST_NAME` and `SOLACE_CONNECT_TIMEOUT_MS` with the
+    /// `SOLACE_` prefix.
+    ///
+    /// Booleans are `true` or `false`, [`AckEventMode`] is `per_message` or `windowed`,
+    /// [`UnbindFailAction`] is `retry` or `disconnect` and [`ResubscribeStrategy`] is `none`,
+    /// `native` or `managed`. Variables that are not set, or are not valid unicode, are left
+    /// unset.
+    pub fn from_env(prefix: &str) -> Result<Self> {
+        Self::from_lookup(prefix, |key| env::var(key).ok())
+    }
+
+    pub(crate) fn from_lookup<F: Fn(&str) -> Option<String>>(
+        prefix: &str,
+        lookup: F,
+    ) -> Result<Self> {
+        let vars = Vars { prefix, lookup };
+
+        Ok(Self {
+            host_name: vars.get("host_name"),
+            vpn_name: vars.get("vpn_name"),
+            username: vars.get("username"),
+            password: vars.get("password"),
+            client_name: vars.get("client_name"),
+            application_description: vars.get("application_description"),
+            buffer_size_bytes: vars.parse("buffer_size_bytes")?,
+            block_write_timeout_ms: vars.parse("block_write_timeout_ms")?,
+            connect_timeout_ms: vars.parse("connect_timeout_ms")?,
+            subconfirm_timeout_ms: vars.parse("subconfirm_timeout_ms")?,
+            ignore_dup_subscription_error: vars.parse("ignore_dup_subscription_error")?,
+            tcp_nodelay: vars.parse("tcp_nodelay")?,
+            socket_send_buf_size_bytes: vars.parse("socket_send_buf_size_bytes")?,
+            socket_rcv_buf_size_bytes: vars.parse("socket_rcv_buf_size_bytes")?,
+            bind_ip: vars.get("bind_ip"),
+            keep_alive_interval_ms: vars.parse("keep_alive_interval_ms")?,
+            keep_alive_limit: vars.parse("keep_alive_limit")?,
+            compression_level: vars.parse("compression_level")?,
+            generate_rcv_timestamps: vars.parse("generate_rcv_timestamps")?,
+            generate_send_timestamp: vars.parse("generate_send_timestamp")?,
+            generate_sender_id: vars.parse("generate_sender_id")?,
+            generate_sender_sequence_number: vars.parse("generate_sender_sequence_number")?,
+            connect_retries_per_host: vars.parse("connect_retries_per_host")?,
+            connect_retries: vars.parse("connect_retries")?,
+            reconnect_retries: vars.parse("reconnect_retries")?,
+            reconnect_retry_wait_ms: vars.parse("reconnect_retry_wait_ms")?,
+            reapply_subscriptions: vars.parse("reapply_subscriptions")?,
+            provision_timeout_ms: vars.parse("provision_timeout_ms")?,
+            calculate_message_expiration: vars.parse("calculate_message_expiration")?,
+            no_local: vars.parse("no_local")?,
+            subscribe_blocking: vars.parse("subscribe_blocking")?,
+            ack_event_mode: vars.parse("ack_event_mode")?,
+            modifyprop_timeout_ms: vars.parse("modifyprop_timeout_ms")?,
+            pub_window_size: vars.parse("pub_window_size")?,
+            pub_ack_timer_ms: vars.parse("pub_ack_timer_ms")?,
+            ad_pub_router_windowed_ack: vars.parse("ad_pub_router_windowed_ack")?,
+            unbind_fail_action: vars.parse("unbind_fail_action")?,
+            resubscribe_strategy: vars.parse("resubscribe_strategy")?,
+            ssl_trust_store_dir: vars.get("ssl_trust_store_dir"),
+            ssl_downgrade_to_plaintext: vars.parse("ssl_downgrade_to_plaintext")?,
+            ssl_cipher_suites: vars.get("ssl_cipher_suites"),
+            ssl_excluded_protocols: vars.get("ssl_excluded_protocols"),
+            ssl_validate_certificate: vars.parse("ssl_validate_certificate")?,
+            ssl_validate_certificate_date: vars.parse("ssl_validate_certificate_date")?,
+            ssl_trusted_common_name_list: vars.get("ssl_trusted_common_name_list"),
+        })
+    }
+}
+
+impl fmt::Debug for SessionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionConfig")
+            .field("host_name", &self.host_name)
+            .field("vpn_name", &self.vpn_name)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| Redacted))
+            .field("client_name", &self.client_name)
+            .field("application_description", &self.application_description)
+            .field("buffer_size_bytes", &self.buffer_size_bytes)
+            .field("block_write_timeout_ms", &self.block_write_timeout_ms)
+            .field("connect_timeout_ms", &self.connect_timeout_ms)
+            .field("subconfirm_timeout_ms", &self.subconfirm_timeout_ms)
+            .field(
+                "ignore_dup_subscription_error",
+                &self.ignore_dup_subscription_error,
+            )
+            .field("tcp_nodelay", &self.tcp_nodelay)
+            .field(
+                "socket_send_buf_size_bytes",
+                &self.socket_send_buf_size_bytes,
+            )
+            .field("socket_rcv_buf_size_bytes", &self.socket_rcv_buf_size_bytes)
+            .field("bind_ip", &self.bind_ip)
+            .field("keep_alive_interval_ms", &self.keep_alive_interval_ms)
+            .field("keep_alive_limit", &self.keep_alive_limit)
+            .field("compression_level", &self.compression_level)
+            .field("generate_rcv_timestamps", &self.generate_rcv_timestamps)
+            .field("generate_send_timestamp", &self.generate_send_timestamp)
+            .field("generate_sender_id", &self.generate_sender_id)
+            .field(
+                "generate_sender_sequence_number",
+                &self.generate_sender_sequence_number,
+            )
+            .field("connect_retries_per_host", &self.connect_retries_per_host)
+            .field("connect_retries", &self.connect_retries)
+            .field("reconnect_retries", &self.reconnect_retries)
+            .field("reconnect_retry_wait_ms", &self.reconnect_retry_wait_ms)
+            .field("reapply_subscriptions", &self.reapply_subscriptions)
+            .field("provision_timeout_ms", &self.provision_timeout_ms)
+            .field(
+                "calculate_message_expiration",
+                &self.calculate_message_expiration,
+            )
+            .field("no_local", &self.no_local)
+            .field("subscribe_blocking", &self.subscribe_blocking)
+            .field("ack_event_mode", &self.ack_event_mode)
+            .field("modifyprop_timeout_ms", &self.modifyprop_timeout_ms)
+            .field("pub_window_size", &self.pub_window_size)
+            .field("pub_ack_timer_ms", &self.pub_ack_timer_ms)
+            .field(
+                "ad_pub_router_windowed_ack",
+                &self.ad_pub_router_windowed_ack,
+            )
+            .field("unbind_fail_action", &self.unbind_fail_action)
+            .field("resubscribe_strategy", &self.resubscribe_strategy)
+            .field("ssl_trust_store_dir", &self.ssl_trust_store_dir)
+            .field(
+                "ssl_downgrade_to_plaintext",
+                &self.ssl_downgrade_to_plaintext,
+            )
+            .field("ssl_cipher_suites", &self.ssl_cipher_suites)
+            .field("ssl_excluded_protocols", &self.ssl_excluded_protocols)
+            .field("ssl_validate_certificate", &self.ssl_validate_certificate)
+            .field(
+                "ssl_validate_certificate_date",
+                &self.ssl_validate_certificate_date,
+            )
+            .field(
+                "ssl_trusted_common_name_list",
+                &self.ssl_trusted_common_name_list,
+            )
+            .finish()
+    }
+}
+
+struct Vars<'a, F> {
+    prefix: &'a str,
+    lookup: F,
+}
+
+impl<F: Fn(&str) -> Option<String>> Vars<'_, F> {
+    fn get(&self, field: &str) -> Option<String> {
+        (self.lookup)(&self.key(field))
+    }
+
+    fn parse<T: FromStr>(&self, field: &str) -> Result<Option<T>> {
+        let key = self.key(field);
+        match (self.lookup)(&key) {
+            Some(value) => match value.trim().parse() {
+                Ok(x) => Ok(Some(x)),
+                Err(_) => Err(SessionBuilderError::InvalidConfig(key, value)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn key(&self, field: &str) -> String {
+        format!("{}{}", self.prefix, field.to_ascii_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn it_should_read_prefixed_vars() {
+        let config = SessionConfig::from_lookup(
+            "SOLACE_",
+            lookup(&[
+                ("SOLACE_HOST_NAME", "tcp://localhost:55555"),
+                ("SOLACE_CONNECT_TIMEOUT_MS", "5000"),
+                ("SOLACE_TCP_NODELAY", "true"),
+                ("SOLACE_ACK_EVENT_MODE", "windowed"),
+                ("HOST_NAME", "tcp://other:55555"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            SessionConfig {
+                host_name: Some("tcp://localhost:55555".to_owned()),
+                connect_timeout_ms: Some(5000),
+                tcp_nodelay: Some(true),
+                ack_event_mode: Some(AckEventMode::Windowed),
+                ..Default::default()
+            },
+            config
+        );
+    }
+
+    #[test]
+    fn it_should_reject_invalid_values() {
+        let res = SessionConfig::from_lookup("", lookup(&[("KEEP_ALIVE_LIMIT", "three")]));
+
+        assert!(matches!(
+            res,
+            Err(SessionBuilderError::InvalidConfig(key, value))
+                if key == "KEEP_ALIVE_LIMIT" && value == "three"
+        ));
+    }
+
+    #[test]
+    fn it_should_redact_the_password() {
+        let config = SessionConfig {
+            username: Some("admin".to_owned()),
+            password: Some("hunter2".to_owned()),
+            ..Default::default()
+        };
+        let debug = format!("{config:?}");
+
+        assert!(debug.contains("admin"));
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains("hunter2"));
+    }
+}
@@ -0,0 +1,205 @@
+use std::io;
+use std::num::NonZeroUsize;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use tracing::warn;
+
+use crate::message::InboundMessage;
+
+/// Runs the `on_message` callback on a pool of worker threads instead of the context thread.
+///
+/// The context thread also sends keepalives and processes acks, so heavy per message processing
+/// on it can bring the session down. With parallel dispatch the context thread only queues the
+/// message. Once the queue is full it blocks until a worker frees up, pushing back on the broker.
+///
+/// Messages are processed concurrently, so they are not handled in the order they were received.
+///
+/// ```no_run
+/// # use std::num::NonZeroUsize;
+/// # use solace_rs::{Context, SolaceLogLevel};
+/// # use solace_rs::message::InboundMessage;
+/// # use solace_rs::session::{ParallelDispatch, SessionEvent};
+/// let context = Context::new(SolaceLogLevel::Warning).unwrap();
+/// let on_message = ParallelDispatch::new(NonZeroUsize::new(4).unwrap())
+///     .queue_capacity(256)
+///     .spawn(|message: InboundMessage| {
+///         // heavy processing
+///     })
+///     .unwrap();
+///
+/// let session = context
+///     .session_builder()
+///     .host_name("tcp://localhost:55555")
+///     .vpn_name("default")
+///     .username("default")
+///     .password("")
+///     .on_message(on_message)
+///     .on_event(|_: SessionEvent| {})
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParallelDispatch {
+    workers: NonZeroUsize,
+    queue_capacity: usize,
+}
+
+impl ParallelDispatch {
+    pub fn new(workers: NonZeroUsize) -> Self {
+        Self {
+            workers,
+            queue_capacity: 1024,
+        }
+    }
+
+    /// Number of messages waiting for a worker before the context thread blocks. Defaults to 1024.
+    pub fn queue_capacity(mut self, queue_capacity: usize) -> Self {
+        self.queue_capacity = queue_capacity;
+        self
+    }
+
+    /// Starts the workers and returns the callback to pass to
+    /// [`SessionBuilder::on_message`](super::SessionBuilder::on_message).
+    ///
+    /// Dropping the callback, which happens when the session is dropped, lets the workers finish
+    /// the queued messages and waits for them.
+    pub fn spawn<F>(self, on_message: F) -> io::Result<impl FnMut(InboundMessage) + Send + 'static>
+    where
+        F: Fn(InboundMessage) + Send + Sync + 'static,
+    {
+        let pool = WorkerPool::spawn(self.workers, self.queue_capacity, on_message)?;
+        Ok(move |message| pool.dispatch(message))
+    }
+}
+
+struct WorkerPool<T> {
+    // only None while dropping
+    tx: Option<SyncSender<T>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> WorkerPool<T> {
+    fn spawn<F>(workers: NonZeroUsize, queue_capacity: usize, on_item: F) -> io::Result<Self>
+    where
+        F: Fn(T) + Send + Sync + 'static,
+    {
+        let (tx, rx) = mpsc::sync_channel(queue_capacity);
+        let rx = Arc::new(Mutex::new(rx));
+        let on_item = Arc::new(on_item);
+
+        let mut pool = Self {
+            tx: Some(tx),
+            workers: Vec::with_capacity(workers.get()),
+        };
+
+        for i in 0..workers.get() {
+            let rx = Arc::clone(&rx);
+            let on_item = Arc::clone(&on_item);
+            let worker = thread::Builder::new()
+                .name(format!("solace-dispatch-{i}"))
+                .spawn(move || work(&rx, &*on_item))?;
+            pool.workers.push(worker);
+        }
+
+        Ok(pool)
+    }
+
+    fn dispatch(&self, item: T) {
+        let Some(tx) = self.tx.as_ref() else {
+            return;
+        };
+
+        // only fails once every worker panicked
+        if tx.send(item).is_err() {
+            warn!("all dispatch workers stopped, dropping message");
+        }
+    }
+}
+
+impl<T> Drop for WorkerPool<T> {
+    fn drop(&mut self) {
+        // closing the channel stops the workers once the queue is drained
+        drop(self.tx.take());
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                warn!("dispatch worker panicked");
+            }
+        }
+    }
+}
+
+fn work<T, F: Fn(T)>(rx: &Mutex<Receiver<T>>, on_item: &F) {
+    loop {
+        // the lock is released before the item is processed
+        let item = rx.lock().unwrap().recv();
+        match item {
+            Ok(item) => on_item(item),
+            Err(_) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn it_should_process_every_item_before_drop_returns() {
+        let processed = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&processed);
+
+        let pool = WorkerPool::spawn(NonZeroUsize::new(4).unwrap(), 8, move |item: usize| {
+            counter.fetch_add(item, Ordering::Relaxed);
+        })
+        .unwrap();
+
+        for _ in 0..100 {
+            pool.dispatch(1);
+        }
+        drop(pool);
+
+        assert_eq!(100, processed.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn it_should_process_items_concurrently() {
+        let workers = 3;
+        let barrier = Arc::new(std::sync::Barrier::new(workers));
+        let pool_barrier = Arc::clone(&barrier);
+
+        // every worker has to be waiting on the barrier at the same time for it to release
+        let pool = WorkerPool::spawn(NonZeroUsize::new(workers).unwrap(), 0, move |_: ()| {
+            pool_barrier.wait();
+        })
+        .unwrap();
+
+        for _ in 0..workers {
+            pool.dispatch(());
+        }
+        drop(pool);
+    }
+
+    #[test]
+    fn it_should_keep_going_after_a_worker_panics() {
+        let processed = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&processed);
+
+        let pool = WorkerPool::spawn(NonZeroUsize::new(2).unwrap(), 0, move |item: usize| {
+            if item == 0 {
+                panic!("worker failure");
+            }
+            counter.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+
+        for item in 0..10 {
+            pool.dispatch(item);
+        }
+        drop(pool);
+
+        assert_eq!(9, processed.load(Ordering::Relaxed));
+    }
+}
@@ -3,6 +3,9 @@ use enum_primitive::*;
 use solace_rs_sys as ffi;
 use std::ffi::CStr;
 
+use crate::util::get_last_error_info;
+use crate::SolClientSubCode;
+
 enum_from_primitive! {
     #[derive(Debug, PartialEq, Eq, Copy, Clone)]
     #[repr(u32)]
@@ -41,3 +44,280 @@ impl fmt::Display for SessionEvent {
         write!(f, "{}", message)
     }
 }
+
+/// How serious a [`SessionEvent`] is, see [`SessionEvent::severity`].
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub enum Severity {
+    Info,
+    /// The session is degraded but keeps working, for example while reconnecting.
+    Warning,
+    /// A single operation failed, the session itself is fine.
+    Error,
+    /// The session is down and can not be used anymore.
+    Fatal,
+}
+
+impl SessionEvent {
+    pub fn severity(&self) -> Severity {
+        // kept exhaustive so that new events have to be classified
+        match self {
+            Self::UpNotice
+            | Self::Acknowledgement
+            | Self::AssuredPublishingUp
+            | Self::TeUnsubscribeOk
+            | Self::CanSend
+            | Self::ReconnectedNotice
+            | Self::ProvisionOk
+            | Self::SubscriptionOk
+            | Self::ModifypropOk => Severity::Info,
+            Self::AssuredDeliveryDown
+            | Self::ReconnectingNotice
+            | Self::VirtualRouterNameChanged
+            | Self::RepublishUnackedMessages => Severity::Warning,
+            Self::RejectedMsgError
+            | Self::SubscriptionError
+            | Self::RxMsgTooBigError
+            | Self::TeUnsubscribeError
+            | Self::ProvisionError
+            | Self::ModifypropFail => Severity::Error,
+            Self::DownError | Self::ConnectFailedError => Severity::Fatal,
+        }
+    }
+
+    /// True if the session is down for good after this event and has to be rebuilt.
+    pub fn is_terminal(&self) -> bool {
+        self.severity() == Severity::Fatal
+    }
+}
+
+/// Why a session lost its connection or failed to connect, see
+/// [`SessionEventInfo::disconnect_reason`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum DisconnectReason {
+    /// The broker stopped answering keepalives, usually a network partition.
+    KeepaliveTimeout,
+    /// The connection failed or was closed at the network level.
+    CommunicationError,
+    /// The broker closed or refused the session, for example because the client was deleted or
+    /// the message VPN was shut down.
+    BrokerInitiated,
+    /// The broker refused the credentials or certificates of the client.
+    LoginFailure,
+    Other,
+}
+
+impl DisconnectReason {
+    /// Classifies on the subcode, the error string of the subcode only tells keepalive failures
+    /// apart from other communication errors. The event info is never used since it contains
+    /// host names.
+    fn classify(subcode: u32, error_string: &str) -> Self {
+        match subcode {
+            ffi::solClient_subCode_SOLCLIENT_SUBCODE_KEEP_ALIVE_FAILURE => Self::KeepaliveTimeout,
+            ffi::solClient_subCode_SOLCLIENT_SUBCODE_COMMUNICATION_ERROR
+                if is_keepalive_error(error_string) =>
+            {
+                Self::KeepaliveTimeout
+            }
+            ffi::solClient_subCode_SOLCLIENT_SUBCODE_COMMUNICATION_ERROR
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_TIMEOUT => Self::CommunicationError,
+            ffi::solClient_subCode_SOLCLIENT_SUBCODE_CLIENT_DELETE_IN_PROGRESS
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_CLIENT_USERNAME_IS_SHUTDOWN
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_MSG_VPN_NOT_ALLOWED
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_MSG_VPN_UNAVAILABLE
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_REPLICATION_IS_STANDBY
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_SERVICE_UNAVAILABLE => Self::BrokerInitiated,
+            ffi::solClient_subCode_SOLCLIENT_SUBCODE_LOGIN_FAILURE
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_UNTRUSTED_CERTIFICATE
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_UNTRUSTED_COMMONNAME
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_UNTRUSTED_CLIENT_CERTIFICATE
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_BASIC_AUTHENTICATION_IS_SHUTDOWN
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_CLIENT_CERTIFICATE_AUTHENTICATION_IS_SHUTDOWN
+            | ffi::solClient_subCode_SOLCLIENT_SUBCODE_KERBEROS_AUTHENTICATION_IS_SHUTDOWN => {
+                Self::LoginFailure
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
+// matches "keepalive", "keep alive" and "Keep-alive"
+fn is_keepalive_error(error_string: &str) -> bool {
+    let normalized: String = error_string
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    normalized.contains("keepalive")
+}
+
+/// Details reported by the C library along with a [`SessionEvent`], passed to
+/// [`SessionBuilder::on_event_info`](super::SessionBuilder::on_event_info).
+#[derive(Debug, Clone)]
+pub struct SessionEventInfo {
+    event: SessionEvent,
+    response_code: u32,
+    info: String,
+    subcode: SolClientSubCode,
+}
+
+impl SessionEventInfo {
+    /// Must be called on the context thread while handling the event, since the subcode is read
+    /// from the last error info.
+    pub(crate) fn new(event: SessionEvent, raw: &ffi::solClient_session_eventCallbackInfo) -> Self {
+        let info = if raw.info_p.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(raw.info_p) }
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        Self {
+            event,
+            response_code: raw.responseCode,
+            info,
+            subcode: get_last_error_info(),
+        }
+    }
+
+    pub fn event(&self) -> SessionEvent {
+        self.event
+    }
+
+    /// Response code of the broker, 0 if the event is not caused by a broker response.
+    pub fn response_code(&self) -> u32 {
+        self.response_code
+    }
+
+    pub fn info(&self) -> &str {
+        &self.info
+    }
+
+    /// Last error of the C library when the event was raised. Only meaningful for error events.
+    pub fn subcode(&self) -> &SolClientSubCode {
+        &self.subcode
+    }
+
+    /// Classifies the cause of `DownError`, `ConnectFailedError` and `ReconnectingNotice`
+    /// events, for example to tell network partitions apart from broker initiated disconnects.
+    /// `None` for other events.
+    pub fn disconnect_reason(&self) -> Option<DisconnectReason> {
+        match self.event {
+            SessionEvent::DownError
+            | SessionEvent::ConnectFailedError
+            | SessionEvent::ReconnectingNotice => Some(DisconnectReason::classify(
+                self.subcode.subcode,
+                &self.subcode.error_string,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Host the session is connecting or connected to, for connection events such as
+    /// `UpNotice` and `ReconnectingNotice`. With a host list it changes as the session fails
+    /// over between hosts.
+    pub fn host(&self) -> Option<&str> {
+        parse_host(&self.info)
+    }
+}
+
+/// Extracts the host from the info string of connection events, which reads like
+/// `host 'tcp:broker:55555', hostname 'broker:55555' IP 10.0.0.1:55555 (host 1 of 2) ...`.
+pub(crate) fn parse_host(info: &str) -> Option<&str> {
+    let start = info.find("host '")? + "host '".len();
+    let len = info[start..].find('\'')?;
+    let host = &info[start..start + len];
+
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_host_from_connection_info() {
+        let info = "host 'tcp:broker-2:55555', hostname 'broker-2:55555' IP 10.0.0.2:55555 \
+                    (host 2 of 2) (host connection attempt 1 of 1)";
+        assert_eq!(Some("tcp:broker-2:55555"), parse_host(info));
+    }
+
+    #[test]
+    fn it_should_classify_keepalive_failures() {
+        assert_eq!(
+            DisconnectReason::KeepaliveTimeout,
+            DisconnectReason::classify(
+                ffi::solClient_subCode_SOLCLIENT_SUBCODE_KEEP_ALIVE_FAILURE,
+                ""
+            )
+        );
+        assert_eq!(
+            DisconnectReason::KeepaliveTimeout,
+            DisconnectReason::classify(
+                ffi::solClient_subCode_SOLCLIENT_SUBCODE_COMMUNICATION_ERROR,
+                "Keep-alive timer expired"
+            )
+        );
+        assert_eq!(
+            DisconnectReason::CommunicationError,
+            DisconnectReason::classify(
+                ffi::solClient_subCode_SOLCLIENT_SUBCODE_COMMUNICATION_ERROR,
+                "Peer closed socket"
+            )
+        );
+    }
+
+    #[test]
+    fn it_should_not_classify_on_the_event_info() {
+        let info = SessionEventInfo {
+            event: SessionEvent::DownError,
+            response_code: 0,
+            info: "host 'tcp:housekeeping.example:55555'".to_owned(),
+            subcode: SolClientSubCode {
+                subcode: ffi::solClient_subCode_SOLCLIENT_SUBCODE_COMMUNICATION_ERROR,
+                error_string: "Peer closed socket".to_owned(),
+            },
+        };
+        assert_eq!(
+            Some(DisconnectReason::CommunicationError),
+            info.disconnect_reason()
+        );
+    }
+
+    #[test]
+    fn it_should_classify_broker_disconnects() {
+        assert_eq!(
+            DisconnectReason::BrokerInitiated,
+            DisconnectReason::classify(
+                ffi::solClient_subCode_SOLCLIENT_SUBCODE_CLIENT_DELETE_IN_PROGRESS,
+                ""
+            )
+        );
+        assert_eq!(
+            DisconnectReason::Other,
+            DisconnectReason::classify(ffi::solClient_subCode_SOLCLIENT_SUBCODE_OK, "")
+        );
+    }
+
+    #[test]
+    fn it_should_not_parse_host_without_one() {
+        assert_eq!(None, parse_host(""));
+        assert_eq!(None, parse_host("Subscription already exists"));
+        assert_eq!(None, parse_host("host ''"));
+        assert_eq!(None, parse_host("host 'unterminated"));
+    }
+
+    #[test]
+    fn it_should_only_be_terminal_when_session_is_down() {
+        assert!(SessionEvent::DownError.is_terminal());
+        assert!(SessionEvent::ConnectFailedError.is_terminal());
+        assert!(!SessionEvent::ReconnectingNotice.is_terminal());
+        assert!(!SessionEvent::RejectedMsgError.is_terminal());
+    }
+
+    #[test]
+    fn it_should_order_severities() {
+        assert!(SessionEvent::UpNotice.severity() < SessionEvent::ReconnectingNotice.severity());
+        assert!(SessionEvent::SubscriptionError.severity() < SessionEvent::DownError.severity());
+    }
+}
@@ -0,0 +1,97 @@
+use std::ptr;
+use std::time::Duration;
+
+use solace_rs_sys as ffi;
+
+use crate::message::{
+    InboundMessage, MessageDestination, MessageError, MessageRead, MessageWrite, OutboundMessage,
+};
+use crate::SolClientReturnCode;
+
+type Result<T> = std::result::Result<T, MessageError>;
+
+/// What [`Session::forward`](super::Session::forward) removes from the forwarded message. By
+/// default the message is forwarded as received, with only the destination replaced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ForwardOptions {
+    strip_sender_info: bool,
+    strip_time_to_live: bool,
+    strip_user_properties: bool,
+}
+
+impl ForwardOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes the sender id and sender timestamp, so that they can be generated by the
+    /// forwarding session.
+    pub fn strip_sender_info(mut self, strip_sender_info: bool) -> Self {
+        self.strip_sender_info = strip_sender_info;
+        self
+    }
+
+    pub fn strip_time_to_live(mut self, strip_time_to_live: bool) -> Self {
+        self.strip_time_to_live = strip_time_to_live;
+        self
+    }
+
+    /// Replaces the user properties, including the structured data of SDT maps, with an empty
+    /// map.
+    pub fn strip_user_properties(mut self, strip_user_properties: bool) -> Self {
+        self.strip_user_properties = strip_user_properties;
+        self
+    }
+}
+
+/// Duplicates the received message and prepares the copy to be published to `destination`. The
+/// payload is shared with the received message by the C library.
+pub(crate) fn forwarded(
+    message: &InboundMessage,
+    destination: &MessageDestination,
+    options: ForwardOptions,
+) -> Result<OutboundMessage> {
+    let mut outbound = message.try_clone()?.into_outbound();
+    prepare(&mut outbound, destination, options)?;
+    Ok(outbound)
+}
+
+/// Prepares a received message, turned into an outbound one, to be published to `destination`.
+pub(crate) fn prepare(
+    outbound: &mut OutboundMessage,
+    destination: &MessageDestination,
+    options: ForwardOptions,
+) -> Result<()> {
+    outbound.set_destination(destination)?;
+
+    let msg_ptr = unsafe { outbound.get_raw_message_ptr() };
+    if options.strip_sender_info {
+        check_delete("sender_id", unsafe {
+            ffi::solClient_msg_deleteSenderId(msg_ptr)
+        })?;
+        check_delete("sender_timestamp", unsafe {
+            ffi::solClient_msg_deleteSenderTimestamp(msg_ptr)
+        })?;
+    }
+    if options.strip_time_to_live {
+        outbound.set_time_to_live(Duration::ZERO)?;
+    }
+    if options.strip_user_properties {
+        // creating a map replaces the existing one
+        let mut map: ffi::solClient_opaqueContainer_pt = ptr::null_mut();
+        let rc = unsafe { ffi::solClient_msg_createUserPropertyMap(msg_ptr, &mut map, 0) };
+        SolClientReturnCode::from_raw(rc)
+            .ok_or_else(|rc| MessageError::FieldError("user_properties", rc))?;
+        unsafe { ffi::solClient_container_closeMapStream(&mut map) };
+    }
+
+    Ok(())
+}
+
+fn check_delete(field: &'static str, rc: ffi::solClient_returnCode_t) -> Result<()> {
+    match SolClientReturnCode::from_raw(rc) {
+        // nothing to delete
+        SolClientReturnCode::Ok | SolClientReturnCode::NotFound => Ok(()),
+        rc => Err(MessageError::FieldError(field, rc)),
+    }
+}
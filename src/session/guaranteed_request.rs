@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use solace_rs_sys as ffi;
+use tracing::{debug, warn};
+
+use super::{PublisherHandle, Session, SessionEvent};
+use crate::message::{
+    DeliveryMode, InboundMessage, MessageDestination, MessageRead, MessageWrite, OutboundMessage,
+};
+use crate::props::PropList;
+use crate::util;
+use crate::{SessionError, SolClientReturnCode};
+
+type Result<T> = std::result::Result<T, SessionError>;
+
+/// Guaranteed request/reply over a temporary queue, returned by
+/// [`Session::guaranteed_requester`](super::Session::guaranteed_requester).
+///
+/// Requests are published as persistent messages with the temporary queue as their reply-to and
+/// a correlation id generated by the requester, which replaces the one set on the message.
+/// Replies must be sent to the reply-to of the request with its correlation id. Replies are
+/// acknowledged as soon as they are received, and replies to requests that timed out are
+/// discarded.
+///
+/// The temporary queue is deleted by the broker when the requester is dropped.
+pub struct GuaranteedRequester<'session> {
+    lifetime: PhantomData<&'session ()>,
+    flow_ptr: ffi::solClient_opaqueFlow_pt,
+    // the flow callbacks point into the box, it is only dropped after the flow is destroyed
+    inbox: Box<ReplyInbox>,
+    publisher: PublisherHandle,
+    reply_to: MessageDestination,
+    next_id: AtomicU64,
+}
+
+unsafe impl Send for GuaranteedRequester<'_> {}
+unsafe impl Sync for GuaranteedRequester<'_> {}
+
+impl<'session> GuaranteedRequester<'session> {
+    pub(crate) fn new<M, E>(session: &'session Session<'_, M, E>) -> Result<Self>
+    where
+        M: FnMut(InboundMessage) + Send,
+        E: FnMut(SessionEvent) + Send,
+    {
+        let mut inbox = Box::new(ReplyInbox::default());
+        let user_p = inbox.as_mut() as *mut ReplyInbox as *mut c_void;
+
+        let mut func_info = ffi::solClient_flow_createFuncInfo {
+            rxInfo: ffi::solClient_flow_createRxCallbackFuncInfo {
+                callback_p: ptr::null_mut(),
+                user_p: ptr::null_mut(),
+            },
+            eventInfo: ffi::solClient_flow_createEventCallbackFuncInfo {
+                callback_p: Some(on_flow_event),
+                user_p,
+            },
+            rxMsgInfo: ffi::solClient_flow_createRxMsgCallbackFuncInfo {
+                callback_p: Some(on_reply),
+                user_p,
+            },
+        };
+
+        // a non durable queue without a name is a temporary queue named by the broker
+        let mut props = PropList::new();
+        props.push_static(
+            ffi::SOLCLIENT_FLOW_PROP_BIND_BLOCKING,
+            ffi::SOLCLIENT_PROP_ENABLE_VAL,
+        );
+        props.push_static(
+            ffi::SOLCLIENT_FLOW_PROP_BIND_ENTITY_ID,
+            ffi::SOLCLIENT_FLOW_PROP_BIND_ENTITY_QUEUE,
+        );
+        props.push_bool(ffi::SOLCLIENT_FLOW_PROP_BIND_ENTITY_DURABLE, false);
+        props.push_static(
+            ffi::SOLCLIENT_FLOW_PROP_ACKMODE,
+            ffi::SOLCLIENT_FLOW_PROP_ACKMODE_CLIENT,
+        );
+
+        let mut flow_ptr: ffi::solClient_opaqueFlow_pt = ptr::null_mut();
+        let rc = unsafe {
+            ffi::solClient_session_createFlow(
+                props.as_mut_ptr(),
+                session._session_ptr,
+                &mut flow_ptr,
+                &mut func_info,
+                mem::size_of::<ffi::solClient_flow_createFuncInfo_t>(),
+            )
+        };
+        SolClientReturnCode::from_raw(rc).to_result(SessionError::ReplyQueueError)?;
+
+        let mut raw_dest = ffi::solClient_destination {
+            destType: ffi::solClient_destinationType_SOLCLIENT_NULL_DESTINATION,
+            dest: ptr::null_mut(),
+        };
+        let rc = unsafe {
+            ffi::solClient_flow_getDestination(
+                flow_ptr,
+                &mut raw_dest,
+                mem::size_of::<ffi::solClient_destination>(),
+            )
+        };
+        if let Err(e) = SolClientReturnCode::from_raw(rc).to_result(SessionError::ReplyQueueError) {
+            unsafe { ffi::solClient_flow_destroy(&mut flow_ptr) };
+            return Err(e);
+        }
+
+        Ok(Self {
+            lifetime: PhantomData,
+            flow_ptr,
+            inbox,
+            publisher: session.publisher_handle(),
+            reply_to: MessageDestination::from(raw_dest),
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// The temporary queue replies are received on.
+    pub fn reply_to(&self) -> &MessageDestination {
+        &self.reply_to
+    }
+
+    /// Publishes the request and blocks until the reply is received or the timeout expires,
+    /// which returns [`SessionError::RequestTimeout`]. Direct requests are published as
+    /// persistent.
+    pub fn request(
+        &self,
+        mut message: OutboundMessage,
+        timeout: Duration,
+    ) -> Result<InboundMessage> {
+        let correlation_id = format!("#GR{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        message.set_correlation_id(&correlation_id)?;
+        message.set_reply_to(&self.reply_to)?;
+        if matches!(message.get_delivery_mode(), Ok(DeliveryMode::Direct)) {
+            message.set_delivery_mode(DeliveryMode::Persistent)?;
+        }
+
+        // expected before publishing, the reply can arrive before publish returns
+        self.inbox.expect(&correlation_id);
+        if let Err(e) = self.publisher.publish(&message) {
+            self.inbox.cancel(&correlation_id);
+            return Err(e);
+        }
+
+        self.inbox
+            .wait(&correlation_id, timeout)
+            .ok_or(SessionError::RequestTimeout)
+    }
+
+    /// Like [`GuaranteedRequester::request`], waiting for the reply until the deadline. Nothing
+    /// is published if the deadline already passed.
+    pub fn request_with_deadline(
+        &self,
+        message: OutboundMessage,
+        deadline: Instant,
+    ) -> Result<InboundMessage> {
+        let timeout_ms = util::remaining_ms(deadline).ok_or(SessionError::RequestTimeout)?;
+        self.request(message, Duration::from_millis(timeout_ms.get().into()))
+    }
+}
+
+impl Drop for GuaranteedRequester<'_> {
+    fn drop(&mut self) {
+        let rc = unsafe { ffi::solClient_flow_destroy(&mut self.flow_ptr) };
+        let rc = SolClientReturnCode::from_raw(rc);
+        if !rc.is_ok() {
+            warn!(
+                session_id = self.publisher.session_id(),
+                "reply queue flow was not destroyed properly. {rc}"
+            );
+        }
+    }
+}
+
+/// Replies received on the flow, by correlation id. `None` for a request still waiting.
+#[derive(Default)]
+struct ReplyInbox {
+    pending: Mutex<HashMap<String, Option<InboundMessage>>>,
+    received: Condvar,
+}
+
+impl ReplyInbox {
+    fn expect(&self, correlation_id: &str) {
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(correlation_id.to_owned(), None);
+    }
+
+    fn cancel(&self, correlation_id: &str) {
+        self.pending.lock().unwrap().remove(correlation_id);
+    }
+
+    fn deliver(&self, reply: InboundMessage) {
+        let Ok(Some(correlation_id)) = reply.get_correlation_id() else {
+            debug!("discarding reply without correlation id");
+            return;
+        };
+
+        let mut pending = self.pending.lock().unwrap();
+        match pending.get_mut(correlation_id) {
+            Some(slot @ None) => {
+                *slot = Some(reply);
+                self.received.notify_all();
+            }
+            _ => debug!("discarding unexpected reply {correlation_id}"),
+        }
+    }
+
+    fn wait(&self, correlation_id: &str, timeout: Duration) -> Option<InboundMessage> {
+        let deadline = Instant::now() + timeout;
+        let mut pending = self.pending.lock().unwrap();
+        loop {
+            if let Some(Some(_)) = pending.get(correlation_id) {
+                return pending.remove(correlation_id).flatten();
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                pending.remove(correlation_id);
+                return None;
+            }
+            pending = self.received.wait_timeout(pending, remaining).unwrap().0;
+        }
+    }
+}
+
+extern "C" fn on_reply(
+    flow_p: ffi::solClient_opaqueFlow_pt,
+    msg_p: ffi::solClient_opaqueMsg_pt,
+    user_p: *mut c_void,
+) -> ffi::solClient_rxMsgCallback_returnCode_t {
+    let inbox = unsafe { &*(user_p as *const ReplyInbox) };
+
+    // replies are only of use to the waiting request, so they are acknowledged right away
+    let mut msg_id: ffi::solClient_msgId_t = 0;
+    let rc = unsafe { ffi::solClient_msg_getMsgId(msg_p, &mut msg_id) };
+    if SolClientReturnCode::from_raw(rc).is_ok() {
+        unsafe { ffi::solClient_flow_sendAck(flow_p, msg_id) };
+    }
+
+    // ownership is taken, an unexpected reply is freed when dropped
+    inbox.deliver(InboundMessage::from(msg_p));
+    ffi::solClient_rxMsgCallback_returnCode_SOLCLIENT_CALLBACK_TAKE_MSG
+}
+
+extern "C" fn on_flow_event(
+    _flow_p: ffi::solClient_opaqueFlow_pt,
+    event_info_p: ffi::solClient_flow_eventCallbackInfo_pt,
+    _user_p: *mut c_void,
+) {
+    let event_info = unsafe { &*event_info_p };
+    let event = unsafe { CStr::from_ptr(ffi::solClient_flow_eventToString(event_info.flowEvent)) };
+    let info = if event_info.info_p.is_null() {
+        Default::default()
+    } else {
+        unsafe { CStr::from_ptr(event_info.info_p) }.to_string_lossy()
+    };
+    debug!("reply queue flow event {}: {info}", event.to_string_lossy());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_forget_requests_that_timed_out() {
+        let inbox = ReplyInbox::default();
+        inbox.expect("#GR0");
+
+        assert!(inbox.wait("#GR0", Duration::from_millis(10)).is_none());
+        assert!(inbox.pending.lock().unwrap().is_empty());
+    }
+}
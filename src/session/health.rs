@@ -0,0 +1,65 @@
+use std::time::SystemTime;
+
+/// Connection state of a session as observed from its session events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The session is connected to the broker.
+    Connected,
+    /// The session lost its connection and the C library is trying to reconnect.
+    Reconnecting,
+    /// The session is down and will not reconnect on its own.
+    Down,
+}
+
+/// Snapshot of the health of a session returned by [`Session::health`](super::Session::health).
+#[derive(Debug, Clone)]
+pub struct SessionHealth {
+    pub state: ConnectionState,
+    pub last_message_received: Option<SystemTime>,
+    /// Last time control traffic (such as keepalive responses) was observed from the broker.
+    /// This is sampled whenever the health is queried, so it is only as precise as the polling
+    /// interval.
+    pub last_keepalive: Option<SystemTime>,
+    pub reconnects: u64,
+    /// Host of the last connection event. With a host list it shows which host the session
+    /// failed over to.
+    pub host: Option<String>,
+}
+
+impl SessionHealth {
+    pub fn is_connected(&self) -> bool {
+        self.state == ConnectionState::Connected
+    }
+}
+
+/// Buffer and message memory figures returned by
+/// [`Session::buffer_usage`](super::Session::buffer_usage).
+#[derive(Debug, Clone, Copy)]
+pub struct BufferUsage {
+    /// Configured size of the session send buffer in bytes.
+    pub buffer_size: u64,
+    /// Bytes held by the message pool of the C library. The pool is shared by every session in
+    /// the process.
+    pub total_memory: u64,
+    /// Bytes of the message pool that are currently in use by messages.
+    pub allocated_memory: u64,
+}
+
+/// Connection parameters in effect after connecting, returned by
+/// [`Session::connection_info`](super::Session::connection_info).
+///
+/// The C library does not expose the negotiated TLS cipher.
+#[derive(Debug, Clone)]
+pub struct ConnectionInfo {
+    pub keep_alive_interval_ms: u64,
+    pub keep_alive_limit: u64,
+    /// 0 if compression is off.
+    pub compression_level: u8,
+    pub broker_version: Option<String>,
+    pub broker_platform: Option<String>,
+    pub router_name: Option<String>,
+    /// Whether the broker elides messages published with
+    /// [`eliding_eligible`](crate::message::OutboundMessageBuilder::eliding_eligible). The eliding
+    /// delay is configured on the client profile of the broker.
+    pub message_eliding: Option<bool>,
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+/// Counters of a session returned by [`Session::metrics`](super::Session::metrics).
+///
+/// The counters are monotonic over the lifetime of the session, so they can be exported as is to
+/// a metrics backend, labeled by `client_name`.
+///
+/// The crate does not register them with a metrics facade itself, a snapshot taken periodically
+/// is exported with whichever the application uses. For example with the `metrics` crate:
+///
+/// ```ignore
+/// let metrics = session.metrics();
+/// let labels = [("client_name", metrics.client_name.clone())];
+/// metrics::counter!("solace_published", &labels).absolute(metrics.published);
+/// metrics::counter!("solace_received", &labels).absolute(metrics.received);
+/// metrics::counter!("solace_reconnects", &labels).absolute(metrics.reconnects);
+/// metrics::gauge!("solace_throttled_seconds", &labels).set(metrics.throttled.as_secs_f64());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SessionMetrics {
+    pub session_id: u64,
+    pub client_name: String,
+    /// Messages successfully handed to the C library for sending.
+    pub published: u64,
+    /// Messages delivered to the `on_message` callback.
+    pub received: u64,
+    /// Guaranteed messages acknowledged by the broker.
+    pub acked: u64,
+    /// Guaranteed messages rejected by the broker.
+    pub rejected: u64,
+    /// `on_message` callbacks that exceeded the slow callback threshold.
+    pub slow_callbacks: u64,
+    /// Unacked guaranteed messages republished after a `RepublishUnackedMessages` event, see
+    /// [`SessionBuilder::republish_unacked`](super::SessionBuilder::republish_unacked).
+    pub republished: u64,
+    /// Guaranteed messages given up on after the ack timeout, see
+    /// [`SessionBuilder::ack_timeout`](super::SessionBuilder::ack_timeout).
+    pub ack_timeouts: u64,
+    /// Total time publishing was held back by the publish rate limit.
+    pub throttled: Duration,
+    pub reconnects: u64,
+}
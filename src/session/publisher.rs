@@ -0,0 +1,164 @@
+use std::mem::ManuallyDrop;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use solace_rs_sys as ffi;
+
+use super::batch::{send_batch, BatchResult};
+use super::state::SessionState;
+use super::{send_message, send_request, send_request_no_wait, send_tracked};
+use crate::message::{InboundMessage, OutboundMessage};
+use crate::util;
+use crate::SessionError;
+
+type Result<T> = std::result::Result<T, SessionError>;
+
+/// Handle to publish on a session from multiple threads, returned by
+/// [`Session::publisher_handle`](super::Session::publisher_handle).
+///
+/// Sending is thread safe in the C library, so the handle can be cloned and shared freely. Once
+/// the session is dropped, every send returns [`SessionError::SessionClosed`].
+///
+/// Dropping the session waits for the calls into the C library that handles are making. Waiting
+/// on the publish rate limit does not hold up the drop, but a blocking send does, and so does a
+/// [`PublisherHandle::request`] until its reply arrives or it times out.
+#[derive(Clone)]
+pub struct PublisherHandle {
+    // Only used while the session is open, which is checked through the state for every call.
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    state: Arc<SessionState>,
+}
+
+unsafe impl Send for PublisherHandle {}
+unsafe impl Sync for PublisherHandle {}
+
+impl PublisherHandle {
+    pub(crate) fn new(
+        session_ptr: ffi::solClient_opaqueSession_pt,
+        state: Arc<SessionState>,
+    ) -> Self {
+        Self { session_ptr, state }
+    }
+
+    pub fn publish(&self, message: &OutboundMessage) -> Result<()> {
+        // the session is kept open only while sending, which is checked again there
+        self.check_open()?;
+        send_message(self.session_ptr, &self.state, message)
+    }
+
+    /// See [`Session::publish_batch`](super::Session::publish_batch).
+    pub fn publish_batch(&self, messages: &[OutboundMessage]) -> BatchResult {
+        if let Err(e) = self.check_open() {
+            return BatchResult::failed(e, messages.len());
+        }
+        send_batch(self.session_ptr, &self.state, messages)
+    }
+
+    /// See [`Session::publish_raw`](super::Session::publish_raw).
+    ///
+    /// # Safety
+    /// `msg_ptr` must be a valid message that is not used by another thread during the call. The
+    /// caller keeps ownership of it.
+    pub unsafe fn publish_raw(&self, msg_ptr: ffi::solClient_opaqueMsg_pt) -> Result<()> {
+        self.check_open()?;
+        let message = ManuallyDrop::new(OutboundMessage::from_raw(msg_ptr));
+        send_message(self.session_ptr, &self.state, &message)
+    }
+
+    /// See [`Session::publish_tracked`](super::Session::publish_tracked).
+    pub fn publish_tracked(&self, message: &OutboundMessage) -> Result<PublishReceipt> {
+        self.check_open()?;
+        send_tracked(self.session_ptr, &self.state, message)
+    }
+
+    /// See [`Session::request`](super::Session::request).
+    ///
+    /// The session can not be destroyed while the request is in flight, dropping it waits for
+    /// the reply or for `timeout_ms` to expire.
+    pub fn request(
+        &self,
+        message: OutboundMessage,
+        timeout_ms: NonZeroU32,
+    ) -> Result<InboundMessage> {
+        // held while sending so that the session can not be destroyed underneath
+        let _open = self.state.open().ok_or(SessionError::SessionClosed)?;
+        send_request(self.session_ptr, &message, timeout_ms)
+    }
+
+    /// See [`Session::request_with_deadline`](super::Session::request_with_deadline).
+    ///
+    /// Like [`Self::request`], dropping the session waits for the reply or for the deadline.
+    pub fn request_with_deadline(
+        &self,
+        message: OutboundMessage,
+        deadline: Instant,
+    ) -> Result<InboundMessage> {
+        let timeout_ms = util::remaining_ms(deadline).ok_or(SessionError::RequestTimeout)?;
+        self.request(message, timeout_ms)
+    }
+
+    /// See [`Session::request_no_wait`](super::Session::request_no_wait).
+    pub fn request_no_wait(&self, message: OutboundMessage) -> Result<String> {
+        let _open = self.state.open().ok_or(SessionError::SessionClosed)?;
+        send_request_no_wait(self.session_ptr, &message)
+    }
+
+    pub fn session_id(&self) -> u64 {
+        self.state.id()
+    }
+
+    // skips encoding and rate limiting for a session that is already dropped
+    fn check_open(&self) -> Result<()> {
+        self.state
+            .open()
+            .map(|_| ())
+            .ok_or(SessionError::SessionClosed)
+    }
+}
+
+/// Outcome of a guaranteed message published with
+/// [`Session::publish_tracked`](super::Session::publish_tracked).
+///
+/// Dropping the receipt stops tracking the message.
+pub struct PublishReceipt {
+    state: Arc<SessionState>,
+    tag: usize,
+}
+
+impl PublishReceipt {
+    pub(crate) fn new(state: Arc<SessionState>, tag: usize) -> Self {
+        Self { state, tag }
+    }
+
+    /// Blocks until the broker acknowledges or rejects the message or the timeout expires.
+    ///
+    /// A rejection, for example because the queue quota is exceeded, returns
+    /// [`SessionError::MessageRejected`] with the broker response and subcode. On timeout a late
+    /// outcome is ignored. If the session has an
+    /// [`ack_timeout`](super::SessionBuilder::ack_timeout) that expires first,
+    /// [`SessionError::AckTimedOut`] is returned.
+    pub fn wait(self, timeout: Duration) -> Result<()> {
+        match self.state.confirmations.wait(self.tag, timeout) {
+            Some(Ok(())) => Ok(()),
+            Some(Err(e)) if e.timed_out => Err(SessionError::AckTimedOut),
+            Some(Err(e)) => Err(SessionError::MessageRejected(
+                e.response_code,
+                e.info,
+                e.subcode,
+            )),
+            None => Err(SessionError::PublishConfirmTimeout),
+        }
+    }
+
+    /// Like [`PublishReceipt::wait`], waiting until the deadline.
+    pub fn wait_with_deadline(self, deadline: Instant) -> Result<()> {
+        self.wait(util::remaining(deadline))
+    }
+}
+
+impl Drop for PublishReceipt {
+    fn drop(&mut self) {
+        self.state.confirmations.cancel(self.tag);
+    }
+}
@@ -0,0 +1,175 @@
+use std::num::{NonZeroU32, NonZeroU64};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Publish rate limit of a session, set with
+/// [`SessionBuilder::publish_rate_limit`](super::SessionBuilder::publish_rate_limit).
+///
+/// Both limits are token buckets allowing bursts of up to one second worth of messages or bytes.
+/// Publishing blocks the calling thread until the message fits in the limits. The time spent
+/// waiting is reported in [`SessionMetrics::throttled`](super::SessionMetrics::throttled).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RateLimit {
+    messages_per_sec: Option<NonZeroU32>,
+    bytes_per_sec: Option<NonZeroU64>,
+}
+
+impl RateLimit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn messages_per_sec(mut self, messages_per_sec: NonZeroU32) -> Self {
+        self.messages_per_sec = Some(messages_per_sec);
+        self
+    }
+
+    /// Limits the payload bytes published per second. Headers and properties are not counted.
+    pub fn bytes_per_sec(mut self, bytes_per_sec: NonZeroU64) -> Self {
+        self.bytes_per_sec = Some(bytes_per_sec);
+        self
+    }
+}
+
+pub(crate) struct RateLimiter {
+    // the payload size is only looked up when it is limited
+    limits_bytes: bool,
+    buckets: Mutex<Buckets>,
+    throttled_ns: AtomicU64,
+}
+
+struct Buckets {
+    messages: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        let now = Instant::now();
+        Self {
+            limits_bytes: limit.bytes_per_sec.is_some(),
+            buckets: Mutex::new(Buckets {
+                messages: limit
+                    .messages_per_sec
+                    .map(|rate| TokenBucket::new(rate.get() as f64, now)),
+                bytes: limit
+                    .bytes_per_sec
+                    .map(|rate| TokenBucket::new(rate.get() as f64, now)),
+            }),
+            throttled_ns: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn limits_bytes(&self) -> bool {
+        self.limits_bytes
+    }
+
+    /// Blocks until a message with a payload of `bytes` can be published.
+    pub(crate) fn acquire(&self, bytes: usize) {
+        let wait = self.reserve(bytes, Instant::now());
+        if wait.is_zero() {
+            return;
+        }
+
+        // the tokens are already taken, so concurrent publishers queue up behind this one
+        thread::sleep(wait);
+        self.throttled_ns
+            .fetch_add(wait.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    fn reserve(&self, bytes: usize, now: Instant) -> Duration {
+        let mut buckets = self.buckets.lock().unwrap();
+        let messages = buckets
+            .messages
+            .as_mut()
+            .map_or(Duration::ZERO, |b| b.reserve(1.0, now));
+        let bytes = buckets
+            .bytes
+            .as_mut()
+            .map_or(Duration::ZERO, |b| b.reserve(bytes as f64, now));
+
+        messages.max(bytes)
+    }
+
+    pub(crate) fn throttled(&self) -> Duration {
+        Duration::from_nanos(self.throttled_ns.load(Ordering::Relaxed))
+    }
+}
+
+struct TokenBucket {
+    rate: f64,
+    // goes negative when publishers reserve more than available
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, now: Instant) -> Self {
+        Self {
+            rate,
+            tokens: rate,
+            last_refill: now,
+        }
+    }
+
+    /// Takes `cost` tokens and returns how long to wait until they are available.
+    fn reserve(&mut self, cost: f64, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate).min(self.rate);
+        self.last_refill = now;
+
+        self.tokens -= cost;
+        if self.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+
+        Duration::from_secs_f64(-self.tokens / self.rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_allow_a_burst_of_one_second() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, now);
+
+        for _ in 0..10 {
+            assert_eq!(Duration::ZERO, bucket.reserve(1.0, now));
+        }
+        assert_eq!(Duration::from_millis(100), bucket.reserve(1.0, now));
+        assert_eq!(Duration::from_millis(200), bucket.reserve(1.0, now));
+    }
+
+    #[test]
+    fn it_should_refill_over_time() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, now);
+        bucket.reserve(10.0, now);
+
+        assert_eq!(
+            Duration::ZERO,
+            bucket.reserve(5.0, now + Duration::from_millis(500))
+        );
+        // never refills past the burst size
+        bucket.reserve(0.0, now + Duration::from_secs(60));
+        assert_eq!(10.0, bucket.tokens);
+    }
+
+    #[test]
+    fn it_should_wait_for_the_slowest_limit() {
+        let limiter = RateLimiter::new(
+            RateLimit::new()
+                .messages_per_sec(NonZeroU32::new(100).unwrap())
+                .bytes_per_sec(NonZeroU64::new(1000).unwrap()),
+        );
+        let now = Instant::now();
+
+        assert_eq!(Duration::ZERO, limiter.reserve(1000, now));
+        assert_eq!(Duration::from_millis(500), limiter.reserve(500, now));
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use super::{ConnectionState, Session, SessionBuilderError, SessionEvent};
+use crate::message::InboundMessage;
+use crate::SessionError;
+
+#[derive(Error, Debug)]
+pub enum ReconnectError {
+    #[error("failed to rebuild session after {0} attempts")]
+    RetriesExhausted(u32, #[source] SessionBuilderError),
+    #[error("failed to build session")]
+    BuildFailure(#[from] SessionBuilderError),
+    #[error("failed to reapply subscriptions")]
+    SubscriptionFailure(#[from] SessionError),
+}
+
+type Result<T> = std::result::Result<T, ReconnectError>;
+
+/// Exponential backoff with jitter used between reconnect attempts.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    multiplier: f64,
+    jitter: f64,
+    max_attempts: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: None,
+        }
+    }
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            ..Default::default()
+        }
+    }
+
+    /// Factor the delay grows by after every failed attempt. Values below 1 are treated as 1.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Fraction of the delay that is randomized, between 0 and 1. A jitter of 0.2 spreads a
+    /// 1s delay between 0.8s and 1.2s.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Maximum number of attempts before giving up. Unlimited by default.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Delay before the given attempt (starting at 0) without jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.min(i32::MAX as u32) as i32);
+        let delay = self.initial.as_secs_f64() * factor;
+
+        if !delay.is_finite() || delay >= self.max.as_secs_f64() {
+            return self.max;
+        }
+
+        Duration::from_secs_f64(delay)
+    }
+
+    /// Applies the jitter to a delay. `random` is expected to be uniformly distributed in [0, 1).
+    fn jittered(&self, delay: Duration, random: f64) -> Duration {
+        let spread = delay.as_secs_f64() * self.jitter;
+        let delay = delay.as_secs_f64() - spread + 2.0 * spread * random;
+        Duration::from_secs_f64(delay.max(0.0)).min(self.max)
+    }
+}
+
+/// Lifecycle notifications emitted by [`ReconnectingSession`].
+#[derive(Debug)]
+pub enum ReconnectEvent {
+    /// The session went down and the C library gave up reconnecting.
+    Down,
+    /// A new session is about to be built after waiting for `delay`.
+    Attempt { attempt: u32, delay: Duration },
+    /// Building the session failed.
+    AttemptFailed {
+        attempt: u32,
+        error: SessionBuilderError,
+    },
+    /// A new session is connected and all tracked subscriptions were reapplied.
+    Reconnected { attempts: u32 },
+    /// `max_attempts` was reached without being able to connect.
+    GaveUp { attempts: u32 },
+}
+
+type OnReconnectEvent<'session> = Box<dyn FnMut(&ReconnectEvent) + Send + 'session>;
+
+/// Rebuilds a session once it is down for good.
+///
+/// The C library only supports reconnecting with a fixed retry wait. Once its reconnect retries
+/// are exhausted, the session emits a `DownError` event and can not be used anymore.
+/// `ReconnectingSession` rebuilds the session with the provided factory, waiting with an
+/// exponential backoff between attempts, and reapplies the subscriptions that were made through
+/// it.
+///
+/// The factory is called for every attempt, so the callbacks need to be created anew each time,
+/// for example by cloning a channel sender into them.
+///
+/// # Polling
+///
+/// Nothing is rebuilt in the background: the session is only rebuilt when the caller calls
+/// [`Self::ensure_connected`], which blocks until a new session is connected. The session is
+/// borrowed by the caller and its factory is not `Send`, so it can not be replaced from the
+/// context thread or a worker thread. Until the next call, [`Self::session`] returns the session
+/// that is down and every send on it fails.
+///
+/// Call `ensure_connected` before every use of the session, or from the loop that waits for the
+/// `DownError` event:
+///
+/// ```no_run
+/// # use solace_rs::session::{Backoff, ReconnectingSession, SessionEvent};
+/// # use solace_rs::{Context, SolaceLogLevel};
+/// # use std::sync::mpsc;
+/// let context = Context::new(SolaceLogLevel::Warning).unwrap();
+/// let (events, down) = mpsc::channel();
+/// let factory = || {
+///     let events = events.clone();
+///     context
+///         .session_builder()
+///         .host_name("tcp://localhost:55554")
+///         .vpn_name("default")
+///         .username("default")
+///         .password("")
+///         .on_message(|_| {})
+///         .on_event(move |event| {
+///             if event == SessionEvent::DownError {
+///                 let _ = events.send(());
+///             }
+///         })
+///         .build()
+/// };
+///
+/// let mut session = ReconnectingSession::new(factory, Backoff::default()).unwrap();
+/// session.subscribe("try-me").unwrap();
+/// while down.recv().is_ok() {
+///     session.ensure_connected().unwrap();
+/// }
+/// ```
+pub struct ReconnectingSession<'session, M, E, F>
+where
+    M: FnMut(InboundMessage) + Send + 'session,
+    E: FnMut(SessionEvent) + Send + 'session,
+    F: FnMut() -> std::result::Result<Session<'session, M, E>, SessionBuilderError>,
+{
+    factory: F,
+    session: Session<'session, M, E>,
+    subscriptions: Vec<String>,
+    backoff: Backoff,
+    on_event: Option<OnReconnectEvent<'session>>,
+    rng_state: u64,
+}
+
+impl<'session, M, E, F> ReconnectingSession<'session, M, E, F>
+where
+    M: FnMut(InboundMessage) + Send + 'session,
+    E: FnMut(SessionEvent) + Send + 'session,
+    F: FnMut() -> std::result::Result<Session<'session, M, E>, SessionBuilderError>,
+{
+    /// Builds the initial session with the factory.
+    pub fn new(mut factory: F, backoff: Backoff) -> Result<Self> {
+        let session = factory()?;
+
+        Ok(Self {
+            factory,
+            session,
+            subscriptions: Vec::new(),
+            backoff,
+            on_event: None,
+            // any non zero seed works for xorshift
+            rng_state: RandomState::new().build_hasher().finish() | 1,
+        })
+    }
+
+    pub fn on_reconnect_event<T>(mut self, on_event: T) -> Self
+    where
+        T: FnMut(&ReconnectEvent) + Send + 'session,
+    {
+        self.on_event = Some(Box::new(on_event));
+        self
+    }
+
+    /// Current session. Note that it can be down, it is only rebuilt by
+    /// [`Self::ensure_connected`].
+    pub fn session(&self) -> &Session<'session, M, E> {
+        &self.session
+    }
+
+    /// Subscribes to the topic and keeps track of it so that it is reapplied on new sessions.
+    pub fn subscribe<T: Into<String>>(&mut self, topic: T) -> Result<()> {
+        let topic = topic.into();
+        self.session.subscribe(topic.as_str())?;
+        if !self.subscriptions.contains(&topic) {
+            self.subscriptions.push(topic);
+        }
+        Ok(())
+    }
+
+    pub fn unsubscribe<T: Into<String>>(&mut self, topic: T) -> Result<()> {
+        let topic = topic.into();
+        self.subscriptions.retain(|t| *t != topic);
+        self.session.unsubscribe(topic)?;
+        Ok(())
+    }
+
+    /// Returns the current session if it is usable, otherwise blocks while rebuilding it.
+    pub fn ensure_connected(&mut self) -> Result<&Session<'session, M, E>> {
+        if self.session.health().state != ConnectionState::Down {
+            return Ok(&self.session);
+        }
+
+        self.emit(ReconnectEvent::Down);
+
+        let mut attempt = 0;
+        loop {
+            let random = self.random();
+            let delay = self.backoff.jittered(self.backoff.delay(attempt), random);
+            self.emit(ReconnectEvent::Attempt { attempt, delay });
+            thread::sleep(delay);
+
+            attempt += 1;
+            match (self.factory)() {
+                Ok(session) => {
+                    // the old session is dropped here, before subscriptions are reapplied
+                    self.session = session;
+                    break;
+                }
+                Err(error) => {
+                    let gave_up = self.backoff.max_attempts.is_some_and(|max| attempt >= max);
+                    if gave_up {
+                        self.emit(ReconnectEvent::GaveUp { attempts: attempt });
+                        return Err(ReconnectError::RetriesExhausted(attempt, error));
+                    }
+                    self.emit(ReconnectEvent::AttemptFailed {
+                        attempt: attempt - 1,
+                        error,
+                    });
+                }
+            }
+        }
+
+        for topic in &self.subscriptions {
+            self.session.subscribe(topic.as_str())?;
+        }
+
+        self.emit(ReconnectEvent::Reconnected { attempts: attempt });
+
+        Ok(&self.session)
+    }
+
+    fn emit(&mut self, event: ReconnectEvent) {
+        if let Some(on_event) = self.on_event.as_mut() {
+            on_event(&event);
+        }
+    }
+
+    // xorshift64, good enough to spread out reconnect attempts
+    fn random(&mut self) -> f64 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 7;
+        self.rng_state ^= self.rng_state << 17;
+        (self.rng_state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_grow_delay_exponentially() {
+        let backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(10));
+
+        assert_eq!(Duration::from_millis(100), backoff.delay(0));
+        assert_eq!(Duration::from_millis(200), backoff.delay(1));
+        assert_eq!(Duration::from_millis(800), backoff.delay(3));
+    }
+
+    #[test]
+    fn it_should_cap_delay_at_max() {
+        let backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(1));
+
+        assert_eq!(Duration::from_secs(1), backoff.delay(4));
+        assert_eq!(Duration::from_secs(1), backoff.delay(u32::MAX));
+    }
+
+    #[test]
+    fn it_should_keep_jitter_within_bounds() {
+        let backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(10)).jitter(0.5);
+        let delay = Duration::from_secs(1);
+
+        assert_eq!(Duration::from_millis(500), backoff.jittered(delay, 0.0));
+        assert_eq!(Duration::from_secs(1), backoff.jittered(delay, 0.5));
+        assert!(backoff.jittered(delay, 0.999) < Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn it_should_not_jitter_past_max() {
+        let backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(1)).jitter(1.0);
+
+        assert_eq!(
+            Duration::from_secs(1),
+            backoff.jittered(Duration::from_secs(1), 0.99)
+        );
+    }
+}
@@ -0,0 +1,881 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock, RwLock, RwLockReadGuard};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant, SystemTime};
+
+use solace_rs_sys as ffi;
+use tracing::warn;
+
+use super::callback::AckTimeoutCallback;
+use super::event::parse_host;
+use super::rate_limit::{RateLimit, RateLimiter};
+use super::{ConnectionState, SessionEvent, SessionHealth, SessionMetrics};
+use crate::context::{CallbackKind, ContextCounters};
+use crate::message::{OutboundMessage, PayloadCodec};
+use crate::util::get_last_error_info;
+use crate::SolClientSubCode;
+
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Options of a [`SessionState`] taken from the session builder. Unset options keep the
+/// behaviour of a plain session.
+#[derive(Default)]
+pub(crate) struct SessionStateOptions {
+    pub(crate) windowed_acks: bool,
+    pub(crate) rate_limit: Option<RateLimit>,
+    pub(crate) codec: Option<Arc<dyn PayloadCodec>>,
+    pub(crate) republish_unacked: bool,
+    pub(crate) managed_resubscribe: bool,
+    pub(crate) ack_timeout: Option<Duration>,
+    pub(crate) on_ack_timeout: Option<AckTimeoutCallback>,
+    pub(crate) context_counters: Arc<ContextCounters>,
+}
+
+/// Bookkeeping shared between a [`Session`](super::Session) and the event callback running on
+/// the context thread.
+pub(crate) struct SessionState {
+    // process unique id used to correlate log lines of the same session
+    id: u64,
+    client_name: OnceLock<String>,
+
+    // correlation tags of the guaranteed messages sent but not yet acknowledged or rejected, in
+    // the order they were sent, with the time they were sent at
+    unacked: Mutex<VecDeque<(usize, Instant)>>,
+    window_open: Condvar,
+    // a windowed ack acknowledges the message it is reported for and every message sent before
+    windowed_acks: bool,
+    // copies of the unacked messages by correlation tag, kept when they are republished
+    // automatically. Never locked while holding `unacked`, publishers and the context thread
+    // update both
+    retained: Option<Mutex<HashMap<usize, OutboundMessage>>>,
+    // unacked messages older than this are given up on
+    ack_timeout: Option<Duration>,
+    // told how many messages were given up on
+    on_ack_timeout: Option<Mutex<AckTimeoutCallback>>,
+
+    health: Mutex<HealthState>,
+    // millis since UNIX_EPOCH, kept outside of the mutex since it is updated for every message
+    last_message_received_ms: AtomicU64,
+
+    published: AtomicU64,
+    received: AtomicU64,
+    acked: AtomicU64,
+    rejected: AtomicU64,
+    slow_callbacks: AtomicU64,
+    republished: AtomicU64,
+    ack_timeouts: AtomicU64,
+    // shared with the other sessions of the context
+    context_counters: Arc<ContextCounters>,
+
+    rate_limiter: Option<RateLimiter>,
+    codec: Option<Arc<dyn PayloadCodec>>,
+
+    // topics subscribed to through the session
+    subscriptions: Mutex<HashSet<String>>,
+    // the tracked subscriptions are reapplied by the session after a reconnect
+    managed_resubscribe: bool,
+
+    pub(crate) confirmations: Confirmations,
+
+    // set once the session is destroyed, handles hold the read lock while using the session
+    closed: RwLock<bool>,
+    // set before `closed` is written, callbacks check it instead of `closed` since the write
+    // waits for handles that may be blocked on the context thread, for example in a request
+    closing: AtomicBool,
+
+    // number of user callbacks currently running, the session waits for them before it is
+    // destroyed so that they never outlive the closures they run
+    callbacks_running: Mutex<usize>,
+    callbacks_done: Condvar,
+    context_thread: OnceLock<ThreadId>,
+}
+
+struct HealthState {
+    state: ConnectionState,
+    reconnects: u64,
+    rx_ctl_msgs: u64,
+    last_keepalive: Option<SystemTime>,
+    host: Option<String>,
+}
+
+impl HealthState {
+    fn set_host(&mut self, info: &ffi::solClient_session_eventCallbackInfo) {
+        if info.info_p.is_null() {
+            return;
+        }
+        let info = unsafe { CStr::from_ptr(info.info_p) }.to_string_lossy();
+        if let Some(host) = parse_host(&info) {
+            self.host = Some(host.to_owned());
+        }
+    }
+}
+
+impl SessionState {
+    pub(crate) fn new(options: SessionStateOptions) -> Self {
+        let SessionStateOptions {
+            windowed_acks,
+            rate_limit,
+            codec,
+            republish_unacked,
+            managed_resubscribe,
+            ack_timeout,
+            on_ack_timeout,
+            context_counters,
+        } = options;
+
+        Self {
+            id: NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed),
+            client_name: OnceLock::new(),
+            unacked: Mutex::new(VecDeque::new()),
+            window_open: Condvar::new(),
+            windowed_acks,
+            retained: republish_unacked.then(|| Mutex::new(HashMap::new())),
+            ack_timeout,
+            on_ack_timeout: on_ack_timeout.map(Mutex::new),
+            health: Mutex::new(HealthState {
+                state: ConnectionState::Connected,
+                reconnects: 0,
+                rx_ctl_msgs: 0,
+                last_keepalive: None,
+                host: None,
+            }),
+            last_message_received_ms: AtomicU64::new(0),
+            published: AtomicU64::new(0),
+            received: AtomicU64::new(0),
+            acked: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            slow_callbacks: AtomicU64::new(0),
+            republished: AtomicU64::new(0),
+            ack_timeouts: AtomicU64::new(0),
+            context_counters,
+            rate_limiter: rate_limit.map(RateLimiter::new),
+            codec,
+            subscriptions: Mutex::new(HashSet::new()),
+            managed_resubscribe,
+            confirmations: Confirmations::default(),
+            closed: RwLock::new(false),
+            closing: AtomicBool::new(false),
+            callbacks_running: Mutex::new(0),
+            callbacks_done: Condvar::new(),
+            context_thread: OnceLock::new(),
+        }
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Client name captured at connect time. Empty until the session is connected.
+    pub(crate) fn client_name(&self) -> &str {
+        self.client_name
+            .get()
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set_client_name(&self, client_name: String) {
+        let _ = self.client_name.set(client_name);
+    }
+
+    /// Returns a guard that keeps the session from being destroyed, or `None` if it already was.
+    pub(crate) fn open(&self) -> Option<RwLockReadGuard<'_, bool>> {
+        if self.closing.load(Ordering::Acquire) {
+            return None;
+        }
+        let closed = self.closed.read().unwrap();
+        (!*closed).then_some(closed)
+    }
+
+    /// Marks the session as closed, waiting for the handles currently using it.
+    pub(crate) fn close(&self) {
+        {
+            let _running = self.callbacks_running.lock().unwrap();
+            self.closing.store(true, Ordering::Release);
+        }
+        *self.closed.write().unwrap() = true;
+    }
+
+    /// Marks a callback as running, returns `None` once the session is closed in which case the
+    /// callback must not be dispatched.
+    pub(crate) fn enter_callback(&self, kind: CallbackKind) -> Option<CallbackGuard<'_>> {
+        let _ = self.context_thread.set(thread::current().id());
+
+        // the closing flag is set and checked while holding the counter so that
+        // `wait_for_callbacks` either sees this callback or this callback sees the session closed.
+        // Waiting on `closed` here could deadlock with a handle that waits on this thread
+        let mut running = self.callbacks_running.lock().unwrap();
+        if self.closing.load(Ordering::Acquire) {
+            return None;
+        }
+        *running += 1;
+
+        Some(CallbackGuard {
+            state: self,
+            kind,
+            start: Instant::now(),
+        })
+    }
+
+    /// Blocks until running callbacks return. Must be called after [`Self::close`].
+    pub(crate) fn wait_for_callbacks(&self) {
+        // dropping the session from one of its callbacks would wait on itself
+        if self.context_thread.get() == Some(&thread::current().id()) {
+            warn!(
+                session_id = self.id,
+                client_name = self.client_name(),
+                "session dropped from the context thread, not waiting for callbacks"
+            );
+            return;
+        }
+
+        let running = self.callbacks_running.lock().unwrap();
+        let _running = self
+            .callbacks_done
+            .wait_while(running, |running| *running > 0)
+            .unwrap();
+    }
+
+    pub(crate) fn unacked(&self) -> u64 {
+        self.unacked.lock().unwrap().len() as u64
+    }
+
+    pub(crate) fn guaranteed_message_sent(&self, tag: usize) {
+        self.unacked
+            .lock()
+            .unwrap()
+            .push_back((tag, Instant::now()));
+    }
+
+    /// Removes a single message, either rejected by the broker or failed to send.
+    pub(crate) fn guaranteed_message_settled(&self, tag: usize) {
+        let mut unacked = self.unacked.lock().unwrap();
+        if let Some(i) = unacked.iter().position(|(t, _)| *t == tag) {
+            unacked.remove(i);
+        }
+        drop(unacked);
+
+        if let Some(retained) = &self.retained {
+            retained.lock().unwrap().remove(&tag);
+        }
+        self.window_open.notify_all();
+    }
+
+    pub(crate) fn retains_unacked(&self) -> bool {
+        self.retained.is_some()
+    }
+
+    /// Keeps a copy of a guaranteed message until it is acknowledged, does nothing unless
+    /// republishing is enabled.
+    pub(crate) fn retain(&self, tag: usize, message: OutboundMessage) {
+        if let Some(retained) = &self.retained {
+            retained.lock().unwrap().insert(tag, message);
+        }
+    }
+
+    /// Takes the copies of the unacked messages, in the order they were sent. Returns `None`
+    /// unless republishing is enabled.
+    pub(crate) fn take_retained(&self) -> Option<Vec<(usize, OutboundMessage)>> {
+        let retained = self.retained.as_ref()?;
+        let sent_order: Vec<_> = self
+            .unacked
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(t, _)| *t)
+            .collect();
+
+        let mut retained = retained.lock().unwrap();
+        let messages = sent_order
+            .into_iter()
+            .filter_map(|tag| retained.remove(&tag).map(|message| (tag, message)))
+            .collect();
+        retained.clear();
+        Some(messages)
+    }
+
+    pub(crate) fn messages_republished(&self, count: u64) {
+        self.republished.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Fails the confirmation of a message that could not be republished.
+    pub(crate) fn republish_failed(&self, tag: usize, info: String) {
+        self.guaranteed_message_settled(tag);
+        let error = ConfirmError {
+            response_code: 0,
+            info,
+            subcode: get_last_error_info(),
+            timed_out: false,
+        };
+        self.confirmations.resolve(tag, Err(error));
+    }
+
+    /// Removes the messages acknowledged by an ack for `tag` and returns their tags. Acks for
+    /// messages that are not tracked, for example after the unacked messages were handed back,
+    /// acknowledge nothing.
+    fn guaranteed_messages_acked(&self, tag: usize) -> Vec<usize> {
+        let mut unacked = self.unacked.lock().unwrap();
+        let acked = match unacked.iter().position(|(t, _)| *t == tag) {
+            Some(i) if self.windowed_acks => unacked.drain(..=i).map(|(t, _)| t).collect(),
+            Some(i) => unacked.remove(i).into_iter().map(|(t, _)| t).collect(),
+            None => Vec::new(),
+        };
+        drop(unacked);
+
+        if let Some(retained) = &self.retained {
+            let mut retained = retained.lock().unwrap();
+            for tag in &acked {
+                retained.remove(tag);
+            }
+        }
+        self.window_open.notify_all();
+        acked
+    }
+
+    pub(crate) fn ack_timeout(&self) -> Option<Duration> {
+        self.ack_timeout
+    }
+
+    /// Gives up on the unacked messages sent more than the ack timeout before `now`. Their
+    /// confirmations fail with [`SessionError::AckTimedOut`](crate::SessionError::AckTimedOut)
+    /// and a late acknowledgement is ignored. Returns the number of messages that timed out.
+    pub(crate) fn expire_unacked(&self, now: Instant) -> usize {
+        let Some(ack_timeout) = self.ack_timeout else {
+            return 0;
+        };
+
+        let mut unacked = self.unacked.lock().unwrap();
+        // sent in order, so the messages that timed out are at the front
+        let expired = unacked
+            .iter()
+            .take_while(|(_, sent)| now.saturating_duration_since(*sent) >= ack_timeout)
+            .count();
+        let expired: Vec<_> = unacked.drain(..expired).map(|(t, _)| t).collect();
+        drop(unacked);
+
+        if expired.is_empty() {
+            return 0;
+        }
+
+        if let Some(retained) = &self.retained {
+            let mut retained = retained.lock().unwrap();
+            for tag in &expired {
+                retained.remove(tag);
+            }
+        }
+        self.window_open.notify_all();
+
+        self.ack_timeouts
+            .fetch_add(expired.len() as u64, Ordering::Relaxed);
+        for tag in &expired {
+            self.confirmations
+                .resolve(*tag, Err(ConfirmError::ack_timed_out()));
+        }
+        warn!(
+            session_id = self.id,
+            client_name = self.client_name(),
+            "{} guaranteed messages were not acknowledged within {ack_timeout:?}",
+            expired.len()
+        );
+        if let Some(on_ack_timeout) = &self.on_ack_timeout {
+            (on_ack_timeout.lock().unwrap())(expired.len());
+        }
+
+        expired.len()
+    }
+
+    /// Blocks until the number of unacked messages drops below `window_size` or the timeout
+    /// expires. Returns `true` if there is room in the window.
+    pub(crate) fn wait_for_window(&self, window_size: u64, timeout: Duration) -> bool {
+        let unacked = self.unacked.lock().unwrap();
+        let (unacked, _) = self
+            .window_open
+            .wait_timeout_while(unacked, timeout, |unacked| {
+                unacked.len() as u64 >= window_size
+            })
+            .unwrap();
+
+        (unacked.len() as u64) < window_size
+    }
+
+    pub(crate) fn message_published(&self) {
+        self.published.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns `false` if the topic was already tracked.
+    pub(crate) fn add_subscription(&self, topic: &str) -> bool {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        if subscriptions.contains(topic) {
+            return false;
+        }
+        subscriptions.insert(topic.to_owned())
+    }
+
+    pub(crate) fn remove_subscription(&self, topic: &str) {
+        self.subscriptions.lock().unwrap().remove(topic);
+    }
+
+    pub(crate) fn is_subscribed(&self, topic: &str) -> bool {
+        self.subscriptions.lock().unwrap().contains(topic)
+    }
+
+    pub(crate) fn manages_resubscribe(&self) -> bool {
+        self.managed_resubscribe
+    }
+
+    pub(crate) fn subscriptions(&self) -> Vec<String> {
+        self.subscriptions.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub(crate) fn codec(&self) -> Option<&dyn PayloadCodec> {
+        self.codec.as_deref()
+    }
+
+    pub(crate) fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+
+    pub(crate) fn slow_callback(&self) {
+        self.slow_callbacks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_received(&self) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        self.last_message_received_ms.store(now, Ordering::Relaxed);
+    }
+
+    /// Builds a health snapshot. `rx_ctl_msgs` is the current count of control messages received
+    /// by the session, any increase since the last call is recorded as keepalive traffic.
+    pub(crate) fn health(&self, rx_ctl_msgs: Option<u64>) -> SessionHealth {
+        let mut health = self.health.lock().unwrap();
+
+        if let Some(rx_ctl_msgs) = rx_ctl_msgs {
+            if rx_ctl_msgs > health.rx_ctl_msgs {
+                health.rx_ctl_msgs = rx_ctl_msgs;
+                health.last_keepalive = Some(SystemTime::now());
+            }
+        }
+
+        let last_message_received = match self.last_message_received_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(SystemTime::UNIX_EPOCH + Duration::from_millis(ms)),
+        };
+
+        SessionHealth {
+            state: health.state,
+            last_message_received,
+            last_keepalive: health.last_keepalive,
+            reconnects: health.reconnects,
+            host: health.host.clone(),
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> SessionMetrics {
+        SessionMetrics {
+            session_id: self.id,
+            client_name: self.client_name().to_owned(),
+            published: self.published.load(Ordering::Relaxed),
+            received: self.received.load(Ordering::Relaxed),
+            acked: self.acked.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            slow_callbacks: self.slow_callbacks.load(Ordering::Relaxed),
+            republished: self.republished.load(Ordering::Relaxed),
+            ack_timeouts: self.ack_timeouts.load(Ordering::Relaxed),
+            throttled: self
+                .rate_limiter
+                .as_ref()
+                .map_or(Duration::ZERO, RateLimiter::throttled),
+            reconnects: self.health.lock().unwrap().reconnects,
+        }
+    }
+
+    pub(crate) fn on_event(
+        &self,
+        event: SessionEvent,
+        info: &ffi::solClient_session_eventCallbackInfo,
+    ) {
+        match event {
+            SessionEvent::UpNotice => {
+                let mut health = self.health.lock().unwrap();
+                health.state = ConnectionState::Connected;
+                health.set_host(info);
+            }
+            SessionEvent::ReconnectingNotice => {
+                let mut health = self.health.lock().unwrap();
+                health.state = ConnectionState::Reconnecting;
+                health.set_host(info);
+            }
+            SessionEvent::ReconnectedNotice => {
+                let mut health = self.health.lock().unwrap();
+                health.state = ConnectionState::Connected;
+                health.reconnects += 1;
+                health.set_host(info);
+            }
+            SessionEvent::ConnectFailedError => {
+                let mut health = self.health.lock().unwrap();
+                health.state = ConnectionState::Down;
+                health.set_host(info);
+            }
+            SessionEvent::SubscriptionOk => {
+                self.confirmations
+                    .resolve(info.correlation_p as usize, Ok(()));
+            }
+            SessionEvent::SubscriptionError => {
+                let error = ConfirmError::from(info);
+                let tag = info.correlation_p as usize;
+                match self.confirmations.resolve(tag, Err(error.clone())) {
+                    Some(topic) => warn!(
+                        session_id = self.id,
+                        client_name = self.client_name(),
+                        topic,
+                        "subscription rejected. response code: {} info: {}",
+                        error.response_code,
+                        error.info
+                    ),
+                    None => warn!(
+                        session_id = self.id,
+                        client_name = self.client_name(),
+                        "subscription rejected. response code: {} info: {}",
+                        error.response_code,
+                        error.info
+                    ),
+                }
+            }
+            // for acks and rejections the correlation pointer is the correlation tag of the
+            // message, which is only set for tracked publishes
+            SessionEvent::Acknowledgement => {
+                let acked = self.guaranteed_messages_acked(info.correlation_p as usize);
+                self.acked.fetch_add(acked.len() as u64, Ordering::Relaxed);
+                for tag in acked {
+                    self.confirmations.resolve(tag, Ok(()));
+                }
+            }
+            SessionEvent::RejectedMsgError => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                let error = ConfirmError::from(info);
+                let tag = info.correlation_p as usize;
+                self.guaranteed_message_settled(tag);
+                if self
+                    .confirmations
+                    .resolve(tag, Err(error.clone()))
+                    .is_none()
+                {
+                    warn!(
+                        session_id = self.id,
+                        client_name = self.client_name(),
+                        "message rejected. response code: {} info: {} {}",
+                        error.response_code,
+                        error.info,
+                        error.subcode
+                    );
+                }
+            }
+            // unacked messages are handed back to the application and no longer occupy the
+            // publisher window. When republishing is enabled, the event handler takes the
+            // retained copies before the reset and republishes them
+            SessionEvent::RepublishUnackedMessages => {
+                self.reset_unacked();
+            }
+            // unacked messages are lost with the session
+            SessionEvent::DownError => {
+                self.health.lock().unwrap().state = ConnectionState::Down;
+                self.reset_unacked();
+            }
+            _ => (),
+        }
+    }
+
+    fn reset_unacked(&self) {
+        if let Some(retained) = &self.retained {
+            retained.lock().unwrap().clear();
+        }
+        self.unacked.lock().unwrap().clear();
+        self.window_open.notify_all();
+    }
+}
+
+pub(crate) struct CallbackGuard<'a> {
+    state: &'a SessionState,
+    kind: CallbackKind,
+    start: Instant,
+}
+
+impl Drop for CallbackGuard<'_> {
+    fn drop(&mut self) {
+        self.state
+            .context_counters
+            .record(self.kind, self.start.elapsed());
+        *self.state.callbacks_running.lock().unwrap() -= 1;
+        self.state.callbacks_done.notify_all();
+    }
+}
+
+/// Failure reported by the broker for a request that asked for confirmation. Must be built on the
+/// context thread while handling the event, since the subcode is read from the last error info.
+#[derive(Debug, Clone)]
+pub(crate) struct ConfirmError {
+    pub(crate) response_code: u32,
+    pub(crate) info: String,
+    pub(crate) subcode: SolClientSubCode,
+    // no confirmation was received within the ack timeout
+    pub(crate) timed_out: bool,
+}
+
+impl ConfirmError {
+    fn ack_timed_out() -> Self {
+        Self {
+            response_code: 0,
+            info: String::new(),
+            subcode: SolClientSubCode {
+                subcode: 0,
+                error_string: String::new(),
+            },
+            timed_out: true,
+        }
+    }
+}
+
+impl From<&ffi::solClient_session_eventCallbackInfo> for ConfirmError {
+    fn from(value: &ffi::solClient_session_eventCallbackInfo) -> Self {
+        let info = if value.info_p.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(value.info_p) }
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        Self {
+            response_code: value.responseCode,
+            info,
+            subcode: get_last_error_info(),
+            timed_out: false,
+        }
+    }
+}
+
+type ConfirmResult = std::result::Result<(), ConfirmError>;
+
+/// Requests waiting for a confirmation event. Each request is identified by a tag that is passed
+/// to the C library as the correlation pointer and handed back in the event.
+pub(crate) struct Confirmations {
+    // 0 is never used as a tag since it would be indistinguishable from a null correlation
+    next_tag: AtomicUsize,
+    pending: Mutex<HashMap<usize, (String, Option<ConfirmResult>)>>,
+    resolved: Condvar,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self {
+            next_tag: AtomicUsize::new(1),
+            pending: Mutex::new(HashMap::new()),
+            resolved: Condvar::new(),
+        }
+    }
+}
+
+impl Confirmations {
+    /// Returns a new tag without tracking a request for it.
+    pub(crate) fn new_tag(&self) -> usize {
+        self.next_tag.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Registers a new request for `name` and returns the tag to correlate it with.
+    pub(crate) fn register(&self, name: String) -> usize {
+        let tag = self.new_tag();
+        self.pending.lock().unwrap().insert(tag, (name, None));
+        tag
+    }
+
+    pub(crate) fn cancel(&self, tag: usize) {
+        self.pending.lock().unwrap().remove(&tag);
+    }
+
+    /// Stores the result of the request with the given tag. Returns the name of the request if it
+    /// was pending.
+    fn resolve(&self, tag: usize, result: ConfirmResult) -> Option<String> {
+        let mut pending = self.pending.lock().unwrap();
+        let (name, slot) = pending.get_mut(&tag)?;
+        *slot = Some(result);
+        let name = name.clone();
+        self.resolved.notify_all();
+        Some(name)
+    }
+
+    /// Blocks until the request is resolved or the timeout expires. Returns `None` on timeout, in
+    /// which case a late confirmation is ignored.
+    pub(crate) fn wait(&self, tag: usize, timeout: Duration) -> Option<ConfirmResult> {
+        let pending = self.pending.lock().unwrap();
+        let (mut pending, _) = self
+            .resolved
+            .wait_timeout_while(pending, timeout, |pending| {
+                pending
+                    .get(&tag)
+                    .is_some_and(|(_, result)| result.is_none())
+            })
+            .unwrap();
+
+        pending.remove(&tag).and_then(|(_, result)| result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_ack_single_messages_per_message() {
+        let state = SessionState::new(SessionStateOptions::default());
+        for tag in 1..=3 {
+            state.guaranteed_message_sent(tag);
+        }
+
+        assert_eq!(vec![2], state.guaranteed_messages_acked(2));
+        assert_eq!(2, state.unacked());
+    }
+
+    #[test]
+    fn it_should_ack_every_earlier_message_when_windowed() {
+        let state = SessionState::new(SessionStateOptions {
+            windowed_acks: true,
+            ..Default::default()
+        });
+        for tag in 1..=4 {
+            state.guaranteed_message_sent(tag);
+        }
+
+        assert_eq!(vec![1, 2, 3], state.guaranteed_messages_acked(3));
+        assert_eq!(1, state.unacked());
+    }
+
+    #[test]
+    fn it_should_ignore_acks_for_untracked_messages() {
+        let state = SessionState::new(SessionStateOptions {
+            windowed_acks: true,
+            ..Default::default()
+        });
+        state.guaranteed_message_sent(1);
+
+        assert!(state.guaranteed_messages_acked(7).is_empty());
+        assert_eq!(1, state.unacked());
+    }
+
+    #[test]
+    fn it_should_settle_rejected_message_only() {
+        let state = SessionState::new(SessionStateOptions {
+            windowed_acks: true,
+            ..Default::default()
+        });
+        for tag in 1..=3 {
+            state.guaranteed_message_sent(tag);
+        }
+
+        state.guaranteed_message_settled(2);
+
+        assert_eq!(vec![1, 3], state.guaranteed_messages_acked(3));
+    }
+
+    #[test]
+    fn it_should_retain_unacked_messages_in_send_order() {
+        use crate::message::{DeliveryMode, DestinationType, MessageDestination};
+        use crate::message::{MessageRead, OutboundMessageBuilder};
+
+        let state = SessionState::new(SessionStateOptions {
+            windowed_acks: true,
+            republish_unacked: true,
+            ..Default::default()
+        });
+        for tag in [3, 1, 2] {
+            let message = OutboundMessageBuilder::new()
+                .delivery_mode(DeliveryMode::Persistent)
+                .destination(MessageDestination::new(DestinationType::Topic, "t").unwrap())
+                .payload(tag.to_string())
+                .build()
+                .unwrap();
+            state.retain(tag, message);
+            state.guaranteed_message_sent(tag);
+        }
+
+        state.guaranteed_messages_acked(3);
+        let retained = state.take_retained().unwrap();
+
+        let payloads: Vec<_> = retained
+            .iter()
+            .map(|(tag, message)| (*tag, message.get_payload().unwrap().unwrap().to_vec()))
+            .collect();
+        assert_eq!(vec![(1, b"1".to_vec()), (2, b"2".to_vec())], payloads);
+        assert!(state.take_retained().unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_should_expire_messages_after_the_ack_timeout() {
+        let ack_timeout = Duration::from_secs(5);
+        let notified = Arc::new(AtomicUsize::new(0));
+        let on_ack_timeout = Arc::clone(&notified);
+        let state = SessionState::new(SessionStateOptions {
+            ack_timeout: Some(ack_timeout),
+            on_ack_timeout: Some(Box::new(move |expired| {
+                on_ack_timeout.fetch_add(expired, Ordering::Relaxed);
+            })),
+            ..Default::default()
+        });
+        let tag = state.confirmations.register("message".to_owned());
+        state.guaranteed_message_sent(tag);
+        state.guaranteed_message_sent(state.confirmations.new_tag());
+
+        assert_eq!(0, state.expire_unacked(Instant::now()));
+        assert_eq!(2, state.expire_unacked(Instant::now() + ack_timeout));
+        assert_eq!(0, state.unacked());
+        assert_eq!(2, state.metrics().ack_timeouts);
+        assert_eq!(2, notified.load(Ordering::Relaxed));
+
+        let result = state.confirmations.wait(tag, Duration::ZERO);
+        assert!(matches!(result, Some(Err(e)) if e.timed_out));
+    }
+
+    #[test]
+    fn it_should_not_retain_messages_by_default() {
+        let state = SessionState::new(SessionStateOptions::default());
+        assert!(!state.retains_unacked());
+        assert!(state.take_retained().is_none());
+    }
+
+    #[test]
+    fn it_should_track_subscriptions_once() {
+        let state = SessionState::new(SessionStateOptions::default());
+
+        assert!(state.add_subscription("a/b"));
+        assert!(!state.add_subscription("a/b"));
+        assert!(state.is_subscribed("a/b"));
+        assert!(!state.is_subscribed("a/>"));
+
+        state.remove_subscription("a/b");
+        assert!(!state.is_subscribed("a/b"));
+    }
+    #[test]
+    fn it_should_not_block_callbacks_while_closing() {
+        let state = Arc::new(SessionState::new(SessionStateOptions::default()));
+        // a handle using the session, for example waiting on a reply
+        let open = state.open().unwrap();
+
+        let closer = thread::spawn({
+            let state = state.clone();
+            move || state.close()
+        });
+        while !state.closing.load(Ordering::Acquire) {
+            thread::yield_now();
+        }
+
+        assert!(state.enter_callback(CallbackKind::Message).is_none());
+        assert!(state.open().is_none());
+
+        drop(open);
+        closer.join().unwrap();
+    }
+}
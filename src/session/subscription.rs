@@ -0,0 +1,112 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::state::SessionState;
+use crate::util;
+use crate::SessionError;
+
+type Result<T> = std::result::Result<T, SessionError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SubscriptionOp {
+    Subscribe,
+    Unsubscribe,
+}
+
+/// Result of [`Session::subscribe`](super::Session::subscribe).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscribeOutcome {
+    Subscribed,
+    /// The session was already subscribed to the topic, nothing was sent to the broker.
+    AlreadySubscribed,
+}
+
+/// Pending subscription change, returned by
+/// [`Session::subscribe_nonblocking`](super::Session::subscribe_nonblocking) and
+/// [`Session::unsubscribe_nonblocking`](super::Session::unsubscribe_nonblocking).
+///
+/// Dropping the handle only stops tracking the result, the change is still applied by the broker.
+pub struct SubscriptionHandle {
+    state: Arc<SessionState>,
+    tag: usize,
+    topic: String,
+    op: SubscriptionOp,
+    // the subscription was added to the tracked ones by this handle and is removed if rejected
+    tracked: bool,
+}
+
+impl SubscriptionHandle {
+    pub(crate) fn new(
+        state: Arc<SessionState>,
+        tag: usize,
+        topic: String,
+        op: SubscriptionOp,
+        tracked: bool,
+    ) -> Self {
+        Self {
+            state,
+            tag,
+            topic,
+            op,
+            tracked,
+        }
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Blocks until the broker confirms or rejects the change or the timeout expires. On timeout
+    /// a late confirmation is ignored.
+    pub fn wait(mut self, timeout: Duration) -> Result<()> {
+        let result = self.state.confirmations.wait(self.tag, timeout);
+        let topic = std::mem::take(&mut self.topic);
+
+        if self.tracked && matches!(result, Some(Err(_))) {
+            self.state.remove_subscription(&topic);
+        }
+
+        match (result, self.op) {
+            (Some(Ok(())), _) => Ok(()),
+            (Some(Err(e)), SubscriptionOp::Subscribe) => Err(SessionError::SubscriptionRejected(
+                topic,
+                e.response_code,
+                e.info,
+                e.subcode,
+            )),
+            (Some(Err(e)), SubscriptionOp::Unsubscribe) => Err(
+                SessionError::UnsubscriptionRejected(topic, e.response_code, e.info, e.subcode),
+            ),
+            (None, SubscriptionOp::Subscribe) => {
+                Err(SessionError::SubscriptionConfirmTimeout(topic))
+            }
+            (None, SubscriptionOp::Unsubscribe) => {
+                Err(SessionError::UnsubscriptionConfirmTimeout(topic))
+            }
+        }
+    }
+
+    /// Like [`SubscriptionHandle::wait`], waiting until the deadline.
+    pub fn wait_with_deadline(self, deadline: Instant) -> Result<()> {
+        self.wait(util::remaining(deadline))
+    }
+
+    /// Waits for every handle within a single timeout. Returns the first failure, the remaining
+    /// handles are dropped without waiting for them.
+    pub fn wait_all<I>(handles: I, timeout: Duration) -> Result<()>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let deadline = Instant::now() + timeout;
+        for handle in handles {
+            handle.wait_with_deadline(deadline)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        self.state.confirmations.cancel(self.tag);
+    }
+}
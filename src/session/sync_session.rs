@@ -0,0 +1,136 @@
+use std::num::NonZeroU32;
+use std::time::Instant;
+
+use super::{
+    BatchResult, PublishReceipt, PublisherHandle, Session, SessionEvent, SessionHealth,
+    SubscribeOutcome,
+};
+use crate::message::{InboundMessage, OutboundMessage};
+use crate::SessionError;
+
+type Result<T> = std::result::Result<T, SessionError>;
+
+/// Session that can be shared between threads, returned by
+/// [`Session::into_sync`](super::Session::into_sync).
+///
+/// Sending, subscribing and requests are thread safe in the C library, while the callbacks are
+/// only ever called on the context thread. `SyncSession` only exposes the thread safe calls, so it
+/// is `Sync` without requiring the callbacks to be `Sync`.
+pub struct SyncSession<
+    'session,
+    M: FnMut(InboundMessage) + Send + 'session,
+    E: FnMut(SessionEvent) + Send + 'session,
+> {
+    session: Session<'session, M, E>,
+}
+
+// the callbacks are never reachable through a shared reference
+unsafe impl<M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send> Sync
+    for SyncSession<'_, M, E>
+{
+}
+
+impl<'session, M: FnMut(InboundMessage) + Send, E: FnMut(SessionEvent) + Send>
+    SyncSession<'session, M, E>
+{
+    pub(crate) fn new(session: Session<'session, M, E>) -> Self {
+        Self { session }
+    }
+
+    /// Returns the session to use the calls that are not thread safe.
+    pub fn into_inner(self) -> Session<'session, M, E> {
+        self.session
+    }
+
+    /// See [`Session::publish`].
+    pub fn publish(&self, message: &OutboundMessage) -> Result<()> {
+        self.session.publish(message)
+    }
+
+    /// See [`Session::publish_batch`].
+    pub fn publish_batch(&self, messages: &[OutboundMessage]) -> BatchResult {
+        self.session.publish_batch(messages)
+    }
+
+    /// See [`Session::publish_tracked`].
+    pub fn publish_tracked(&self, message: &OutboundMessage) -> Result<PublishReceipt> {
+        self.session.publish_tracked(message)
+    }
+
+    /// See [`Session::publisher_handle`].
+    pub fn publisher_handle(&self) -> PublisherHandle {
+        self.session.publisher_handle()
+    }
+
+    /// See [`Session::subscribe`].
+    pub fn subscribe<T>(&self, topic: T) -> Result<SubscribeOutcome>
+    where
+        T: Into<Vec<u8>>,
+    {
+        self.session.subscribe(topic)
+    }
+
+    /// See [`Session::unsubscribe`].
+    pub fn unsubscribe<T>(&self, topic: T) -> Result<()>
+    where
+        T: Into<Vec<u8>>,
+    {
+        self.session.unsubscribe(topic)
+    }
+
+    /// See [`Session::is_subscribed`].
+    pub fn is_subscribed<T: AsRef<str>>(&self, topic: T) -> bool {
+        self.session.is_subscribed(topic)
+    }
+
+    /// See [`Session::request`].
+    pub fn request(
+        &self,
+        message: OutboundMessage,
+        timeout_ms: NonZeroU32,
+    ) -> Result<InboundMessage> {
+        self.session.request(message, timeout_ms)
+    }
+
+    /// See [`Session::request_with_deadline`].
+    pub fn request_with_deadline(
+        &self,
+        message: OutboundMessage,
+        deadline: Instant,
+    ) -> Result<InboundMessage> {
+        self.session.request_with_deadline(message, deadline)
+    }
+
+    /// See [`Session::request_no_wait`].
+    pub fn request_no_wait(&self, message: OutboundMessage) -> Result<String> {
+        self.session.request_no_wait(message)
+    }
+
+    /// See [`Session::session_id`].
+    pub fn session_id(&self) -> u64 {
+        self.session.session_id()
+    }
+
+    /// See [`Session::health`].
+    pub fn health(&self) -> SessionHealth {
+        self.session.health()
+    }
+
+    /// See [`Session::is_connected`].
+    pub fn is_connected(&self) -> bool {
+        self.session.is_connected()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{OwnedEventCallback, OwnedMessageCallback};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn it_should_be_sync_without_sync_callbacks() {
+        assert_send_sync::<SyncSession<'static, OwnedMessageCallback, OwnedEventCallback>>();
+    }
+}
@@ -0,0 +1,233 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use solace_rs_sys as ffi;
+use tracing::{debug, warn};
+
+use super::set_oauth2_access_token;
+use super::state::SessionState;
+use crate::util::Redacted;
+use crate::SessionError;
+
+pub type TokenError = Box<dyn Error + Send + Sync>;
+
+// waited before fetching again after the provider or the session failed
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// OAuth access token returned by a [`TokenProvider`].
+#[derive(Clone)]
+pub struct Token {
+    access_token: String,
+    expires_in: Option<Duration>,
+}
+
+impl Token {
+    pub fn new<T: Into<String>>(access_token: T) -> Self {
+        Self {
+            access_token: access_token.into(),
+            expires_in: None,
+        }
+    }
+
+    /// Lifetime of the token from when it was fetched, usually the `expires_in` of the token
+    /// response. Tokens without a lifetime are only refreshed after a login failure.
+    pub fn expires_in(mut self, expires_in: Duration) -> Self {
+        self.expires_in = Some(expires_in);
+        self
+    }
+
+    pub fn access_token(&self) -> &str {
+        &self.access_token
+    }
+}
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("access_token", &Redacted)
+            .field("expires_in", &self.expires_in)
+            .finish()
+    }
+}
+
+/// Source of the OAuth access tokens of a session, see
+/// [`SessionBuilder::token_provider`](super::SessionBuilder::token_provider).
+///
+/// Implemented for closures returning a [`Token`].
+pub trait TokenProvider: Send + Sync {
+    /// Fetches a new token. Called on a background thread of the session, so it can block, for
+    /// example on a request to the authorization server.
+    fn fetch_token(&self) -> Result<Token, TokenError>;
+}
+
+impl<F> TokenProvider for F
+where
+    F: Fn() -> Result<Token, TokenError> + Send + Sync,
+{
+    fn fetch_token(&self) -> Result<Token, TokenError> {
+        self()
+    }
+}
+
+// tokens are refreshed once 80% of their lifetime has passed
+pub(crate) fn refresh_at(fetched_at: Instant, token: &Token) -> Option<Instant> {
+    token.expires_in.map(|d| fetched_at + d.mul_f64(0.8))
+}
+
+#[derive(Default)]
+struct Signal {
+    refresh_now: bool,
+    stopped: bool,
+}
+
+/// Wakes the refresh thread, shared with the event handler which requests a refresh when the
+/// broker refuses the token.
+#[derive(Default)]
+pub(crate) struct TokenRefresh {
+    signal: Mutex<Signal>,
+    wake: Condvar,
+}
+
+impl TokenRefresh {
+    pub(crate) fn refresh_now(&self) {
+        self.signal.lock().unwrap().refresh_now = true;
+        self.wake.notify_one();
+    }
+
+    pub(crate) fn stop(&self) {
+        self.signal.lock().unwrap().stopped = true;
+        self.wake.notify_one();
+    }
+
+    /// Returns `false` once stopped.
+    fn wait(&self, until: Option<Instant>) -> bool {
+        let mut signal = self.signal.lock().unwrap();
+        loop {
+            if signal.stopped {
+                return false;
+            }
+            if signal.refresh_now {
+                signal.refresh_now = false;
+                return true;
+            }
+
+            let now = Instant::now();
+            signal = match until {
+                Some(until) if until <= now => return true,
+                Some(until) => self.wake.wait_timeout(signal, until - now).unwrap().0,
+                None => self.wake.wait(signal).unwrap(),
+            };
+        }
+    }
+}
+
+struct Target {
+    // only used while the session is open, which is checked through the state
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    state: Arc<SessionState>,
+}
+
+unsafe impl Send for Target {}
+
+impl Target {
+    fn set(&self, token: &Token) -> Result<(), SessionError> {
+        let _open = self.state.open().ok_or(SessionError::SessionClosed)?;
+        set_oauth2_access_token(self.session_ptr, token.access_token())
+    }
+}
+
+/// Spawns the thread that refreshes the token of the session before it expires. The thread is
+/// not joined, since the provider may block; it exits once stopped or the session is closed.
+pub(crate) fn spawn_token_refresh(
+    provider: Arc<dyn TokenProvider>,
+    refresh: Arc<TokenRefresh>,
+    session_ptr: ffi::solClient_opaqueSession_pt,
+    state: Arc<SessionState>,
+    next: Option<Instant>,
+) -> io::Result<()> {
+    let target = Target { session_ptr, state };
+    thread::Builder::new()
+        .name("solace-token-refresh".to_owned())
+        .spawn(move || run(&*provider, &refresh, &target, next))?;
+
+    Ok(())
+}
+
+fn run(
+    provider: &dyn TokenProvider,
+    refresh: &TokenRefresh,
+    target: &Target,
+    next: Option<Instant>,
+) {
+    let mut next = next;
+    while refresh.wait(next) {
+        let fetched_at = Instant::now();
+        let result = match provider.fetch_token() {
+            Ok(token) => target.set(&token).map(|_| token).map_err(TokenError::from),
+            Err(e) => Err(e),
+        };
+
+        next = match result {
+            Ok(token) => {
+                debug!(
+                    session_id = target.state.id(),
+                    client_name = target.state.client_name(),
+                    "refreshed oauth access token"
+                );
+                refresh_at(fetched_at, &token)
+            }
+            Err(e) if target.state.open().is_none() => {
+                debug!(
+                    session_id = target.state.id(),
+                    "session closed, stopping the token refresh: {e}"
+                );
+                return;
+            }
+            Err(e) => {
+                warn!(
+                    session_id = target.state.id(),
+                    client_name = target.state.client_name(),
+                    "failed to refresh oauth access token, retrying in {RETRY_DELAY:?}: {e}"
+                );
+                Some(Instant::now() + RETRY_DELAY)
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_refresh_before_expiry() {
+        let now = Instant::now();
+        let token = Token::new("token").expires_in(Duration::from_secs(100));
+        assert_eq!(Some(now + Duration::from_secs(80)), refresh_at(now, &token));
+        assert_eq!(None, refresh_at(now, &Token::new("token")));
+    }
+
+    #[test]
+    fn it_should_wake_on_refresh_request() {
+        let refresh = TokenRefresh::default();
+        refresh.refresh_now();
+        assert!(refresh.wait(None));
+
+        let deadline = Instant::now() + Duration::from_millis(10);
+        assert!(refresh.wait(Some(deadline)));
+        assert!(Instant::now() >= deadline);
+
+        refresh.stop();
+        assert!(!refresh.wait(None));
+    }
+
+    #[test]
+    fn it_should_redact_the_token() {
+        let debug = format!("{:?}", Token::new("secret"));
+        assert!(!debug.contains("secret"), "{debug}");
+    }
+}
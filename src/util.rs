@@ -2,19 +2,35 @@ use ffi::solClient_getLastErrorInfo;
 use num_traits::FromPrimitive;
 
 use crate::message::InboundMessage;
+use crate::session::callback::{EventHandler, MessageHandler};
 use crate::session::SessionEvent;
 use crate::SolClientSubCode;
 use solace_rs_sys as ffi;
-use std::mem;
+use std::num::NonZeroU32;
+use std::time::{Duration, Instant};
+use std::{fmt, mem};
 
-pub fn on_message_trampoline<'s, F>(_closure: &'s F) -> ffi::solClient_session_rxMsgCallbackFunc_t
+/// Stands in for passwords and other secrets in `Debug` output.
+pub(crate) struct Redacted;
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+pub fn on_message_trampoline<'s, F>(
+    _handler: &'s MessageHandler<F>,
+) -> ffi::solClient_session_rxMsgCallbackFunc_t
 where
     F: FnMut(InboundMessage) + Send + 's,
 {
     Some(static_on_message::<F>)
 }
 
-pub fn on_event_trampoline<'s, F>(_closure: &'s F) -> ffi::solClient_session_eventCallbackFunc_t
+pub fn on_event_trampoline<'s, F>(
+    _handler: &'s EventHandler<F>,
+) -> ffi::solClient_session_eventCallbackFunc_t
 where
     F: FnMut(SessionEvent) + Send + 's,
 {
@@ -33,7 +49,8 @@ where
     F: FnMut(InboundMessage) + Send + 's,
 {
     // this function is glue code to allow users to pass in closures
-    // we duplicate the message pointer (which does not copy over the binary data)
+    // the message is not duplicated, ownership is taken by returning CALLBACK_TAKE_MSG and the
+    // InboundMessage frees it when dropped
     // also this function will only be called from the context thread, so it should be thread safe
     // as well
 
@@ -43,36 +60,36 @@ where
         return ffi::solClient_rxMsgCallback_returnCode_SOLCLIENT_CALLBACK_OK;
     };
 
-    let message = InboundMessage::from(msg_p);
-    let user_closure: &mut Box<F> = unsafe { mem::transmute(raw_user_closure) };
-    user_closure(message);
+    let handler: &mut MessageHandler<F> = unsafe { raw_user_closure.cast().as_mut() };
+    if !handler.handle(msg_p) {
+        return ffi::solClient_rxMsgCallback_returnCode_SOLCLIENT_CALLBACK_OK;
+    }
 
     ffi::solClient_rxMsgCallback_returnCode_SOLCLIENT_CALLBACK_TAKE_MSG
 }
 
 extern "C" fn static_on_event<'s, F>(
-    _opaque_session_p: ffi::solClient_opaqueSession_pt, // non-null
+    opaque_session_p: ffi::solClient_opaqueSession_pt, // non-null
     event_info_p: ffi::solClient_session_eventCallbackInfo_pt, //non-null
-    raw_user_closure: *mut ::std::os::raw::c_void,      // can be null
+    raw_user_closure: *mut ::std::os::raw::c_void,     // can be null
 ) where
     F: FnMut(SessionEvent) + Send + 's,
 {
     let non_null_raw_user_closure = std::ptr::NonNull::new(raw_user_closure);
 
-    let Some(raw_user_closure) = non_null_raw_user_closure else {
+    let Some(raw_handler) = non_null_raw_user_closure else {
         return;
     };
-    let raw_event = unsafe { (*event_info_p).sessionEvent };
+    let event_info = unsafe { &*event_info_p };
+    let raw_event = event_info.sessionEvent;
+    let handler: &mut EventHandler<F> = unsafe { raw_handler.cast().as_mut() };
 
     let Some(event) = SessionEvent::from_u32(raw_event) else {
-        // TODO
-        // log a warning
+        handler.unknown_event(raw_event);
         return;
     };
 
-    let user_closure: &mut Box<F> = unsafe { mem::transmute(raw_user_closure) };
-
-    user_closure(event);
+    handler.handle(opaque_session_p, event, event_info);
 }
 
 pub(crate) fn get_last_error_info() -> SolClientSubCode {
@@ -88,3 +105,30 @@ pub(crate) fn get_last_error_info() -> SolClientSubCode {
         }
     }
 }
+
+/// Time left until the deadline, zero once it passed.
+pub(crate) fn remaining(deadline: Instant) -> Duration {
+    deadline.saturating_duration_since(Instant::now())
+}
+
+/// Milliseconds left until the deadline rounded up, for the C calls that take a timeout in
+/// milliseconds. `None` once the deadline passed.
+pub(crate) fn remaining_ms(deadline: Instant) -> Option<NonZeroU32> {
+    let remaining = remaining(deadline);
+    let ms = remaining.as_millis() + u128::from(remaining.subsec_nanos() % 1_000_000 != 0);
+    NonZeroU32::new(ms.min(u32::MAX as u128) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_round_remaining_ms_up() {
+        let deadline = Instant::now() + Duration::from_micros(1_500_100);
+        let ms = remaining_ms(deadline).unwrap().get();
+        assert!((1_500..=1_501).contains(&ms));
+
+        assert!(remaining_ms(Instant::now() - Duration::from_millis(1)).is_none());
+    }
+}
@@ -0,0 +1,86 @@
+use std::ffi::{c_char, CStr};
+use std::fmt;
+use std::ptr;
+
+use solace_rs_sys as ffi;
+
+/// Version of the solclient library, see [`solclient_version`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolClientVersion {
+    /// Version reported by the linked library, e.g. `7.26.1.8`.
+    pub version: String,
+    /// Build date and time of the linked library.
+    pub date_time: String,
+    /// Variant of the linked library, e.g. `Linux26-x86_64_opt`.
+    pub variant: String,
+    /// Version of the tarball bundled at build time, `None` when built against
+    /// `SOLCLIENT_LIB_PATH`.
+    pub bundled: Option<&'static str>,
+}
+
+impl fmt::Display for SolClientVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "solclient {} {} ({})",
+            self.version, self.variant, self.date_time
+        )?;
+        if let Some(bundled) = self.bundled {
+            write!(f, " bundled {bundled}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Version of the linked solclient library. Can be called before any context is created.
+pub fn solclient_version() -> SolClientVersion {
+    let mut info: ffi::solClient_version_info_pt = ptr::null_mut();
+    // only fails for a null pointer
+    unsafe { ffi::solClient_version_get(&mut info) };
+
+    let (version, date_time, variant) = if info.is_null() {
+        Default::default()
+    } else {
+        let info = unsafe { &*info };
+        (
+            to_string(info.version_p),
+            to_string(info.dateTime_p),
+            to_string(info.variant_p),
+        )
+    };
+
+    SolClientVersion {
+        version,
+        date_time,
+        variant,
+        bundled: ffi::SOLCLIENT_BUNDLED_VERSION,
+    }
+}
+
+fn to_string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_display_bundled_version() {
+        let version = SolClientVersion {
+            version: "7.26.1.8".to_owned(),
+            date_time: "Jan 1 2024 00:00:00".to_owned(),
+            variant: "Linux26-x86_64_opt".to_owned(),
+            bundled: Some("7.26.1.8"),
+        };
+        assert_eq!(
+            version.to_string(),
+            "solclient 7.26.1.8 Linux26-x86_64_opt (Jan 1 2024 00:00:00) bundled 7.26.1.8"
+        );
+    }
+}
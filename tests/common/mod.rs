@@ -0,0 +1,123 @@
+//! Broker used by the integration tests.
+//!
+//! `SOLACE_HOST` and `SOLACE_PORT` point the tests at an existing broker. Otherwise a
+//! `solace/solace-pubsub-standard` container is started with docker, or reused if it already
+//! exists. The container is left running between test runs, remove it with
+//! `docker rm -f solace-rs-tests`.
+
+use std::env;
+use std::process::Command;
+use std::sync::{mpsc, OnceLock};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use solace_rs::{message::InboundMessage, session::SessionEvent, Context, SolaceLogLevel};
+
+const CONTAINER_NAME: &str = "solace-rs-tests";
+const IMAGE: &str = "solace/solace-pubsub-standard:latest";
+const DEFAULT_PORT: &str = "55555";
+
+// the broker takes a while to boot on a fresh container
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// How long to wait for a message that is expected to arrive.
+pub const RECV_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long to wait for a subscription to be confirmed by the broker.
+pub const CONFIRM_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long to wait before concluding that a message is not going to arrive.
+pub const QUIET_PERIOD: Duration = Duration::from_millis(200);
+
+static BROKER_URL: OnceLock<String> = OnceLock::new();
+
+/// Url of a broker that accepts connections, starting one if needed.
+pub fn broker_url() -> &'static str {
+    BROKER_URL.get_or_init(|| {
+        let url = match env::var("SOLACE_HOST") {
+            Ok(host) => {
+                let port = env::var("SOLACE_PORT").unwrap_or_else(|_| DEFAULT_PORT.to_owned());
+                format!("tcp://{}:{}", host, port)
+            }
+            Err(_) => format!("tcp://{}", start_container()),
+        };
+        wait_until_ready(&url);
+        url
+    })
+}
+
+/// Receives `count` messages, panicking if one does not arrive within [`RECV_TIMEOUT`].
+pub fn recv_n<T>(rx: &mpsc::Receiver<T>, count: usize) -> Vec<T> {
+    (0..count)
+        .map(|i| {
+            rx.recv_timeout(RECV_TIMEOUT)
+                .unwrap_or_else(|e| panic!("receiving message {} of {}: {}", i + 1, count, e))
+        })
+        .collect()
+}
+
+/// Starts the broker container and returns the address its messaging port is published on.
+fn start_container() -> String {
+    // starting an existing container is a no-op if it is already running
+    if docker(&["start", CONTAINER_NAME]).is_none() {
+        docker(&[
+            "run",
+            "-d",
+            "--name",
+            CONTAINER_NAME,
+            "--shm-size=1g",
+            "-p",
+            "127.0.0.1::55555",
+            "-e",
+            "username_admin_globalaccesslevel=admin",
+            "-e",
+            "username_admin_password=admin",
+            IMAGE,
+        ])
+        .expect("starting broker container");
+    }
+
+    let port = docker(&["port", CONTAINER_NAME, "55555/tcp"]).expect("getting broker port");
+
+    // one line per address family, e.g. 127.0.0.1:49153
+    port.lines()
+        .next()
+        .expect("broker port is published")
+        .trim()
+        .to_owned()
+}
+
+fn docker(args: &[&str]) -> Option<String> {
+    let output = Command::new("docker")
+        .args(args)
+        .output()
+        .expect("running docker, set SOLACE_HOST to use an existing broker instead");
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Connects until the broker accepts sessions. The port is published before the broker is up, so
+/// the container running is not enough.
+fn wait_until_ready(url: &str) {
+    let context = Context::new(SolaceLogLevel::Warning).expect("creating context");
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+
+    loop {
+        let session = context
+            .session_builder()
+            .host_name(url)
+            .vpn_name("default")
+            .username("default")
+            .password("")
+            .on_message(|_: InboundMessage| {})
+            .on_event(|_: SessionEvent| {})
+            .build();
+
+        match session {
+            Ok(_) => return,
+            Err(e) if Instant::now() > deadline => panic!("broker at {} is not ready: {}", url, e),
+            Err(_) => sleep(Duration::from_secs(1)),
+        }
+    }
+}
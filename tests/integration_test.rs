@@ -2,8 +2,7 @@ use std::{
     collections::{HashMap, HashSet},
     num::NonZeroU32,
     sync::{mpsc, Arc, Barrier, Mutex},
-    thread::{self, sleep},
-    time::Duration,
+    thread,
 };
 
 use solace_rs::{
@@ -11,20 +10,18 @@ use solace_rs::{
         DeliveryMode, DestinationType, InboundMessage, Message, MessageDestination,
         OutboundMessageBuilder,
     },
-    session::SessionEvent,
-    Context, SolaceLogLevel,
+    session::{BatchOutcome, ForwardOptions, SessionEvent},
+    Context, SessionError, SolaceLogLevel,
 };
 
-static SLEEP_TIME: std::time::Duration = Duration::from_millis(10);
+mod common;
 
-const DEFAULT_HOST: &str = "worker-lenovo-yoga";
-const DEFAULT_PORT: &str = "55555";
+use common::{broker_url, recv_n, CONFIRM_TIMEOUT, QUIET_PERIOD, RECV_TIMEOUT};
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
 fn subscribe_and_publish() {
-    let host = option_env!("SOLACE_HOST").unwrap_or(DEFAULT_HOST);
-    let port = option_env!("SOLACE_PORT").unwrap_or(DEFAULT_PORT);
+    let host_name = broker_url();
 
     let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
     let (tx, rx) = mpsc::channel();
@@ -40,7 +37,7 @@ fn subscribe_and_publish() {
 
     let session = solace_context
         .session(
-            format!("tcp://{}:{}", host, port),
+            host_name,
             "default",
             "default",
             "",
@@ -48,10 +45,9 @@ fn subscribe_and_publish() {
             Some(|_: SessionEvent| {}),
         )
         .expect("creating session");
-    session.subscribe(topic).expect("subscribing to topic");
-
-    // need to wait before publishing so that the client is properly subscribed
-    sleep(SLEEP_TIME);
+    session
+        .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+        .expect("subscribing to topic");
 
     for msg in tx_msgs.clone() {
         let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
@@ -61,33 +57,21 @@ fn subscribe_and_publish() {
             .payload(msg)
             .build()
             .expect("building outbound msg");
-        session.publish(outbound_msg).expect("publishing message");
-    }
-    sleep(SLEEP_TIME);
-
-    let mut rx_msgs = vec![];
-
-    loop {
-        match rx.try_recv() {
-            Ok(msg) => {
-                let str = String::from_utf8_lossy(&msg).to_string();
-                rx_msgs.push(str);
-                if rx_msgs.len() == tx_msgs.len() {
-                    break;
-                }
-            }
-            _ => panic!(),
-        }
+        session.publish(&outbound_msg).expect("publishing message");
     }
 
+    let rx_msgs: Vec<_> = recv_n(&rx, tx_msgs.len())
+        .into_iter()
+        .map(|msg| String::from_utf8_lossy(&msg).to_string())
+        .collect();
+
     assert_eq!(tx_msgs, rx_msgs);
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
 fn multi_subscribe_and_publish() {
-    let host = option_env!("SOLACE_HOST").unwrap_or(DEFAULT_HOST);
-    let port = option_env!("SOLACE_PORT").unwrap_or(DEFAULT_PORT);
+    let host_name = broker_url();
     let msg_multiplier = 2;
 
     let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
@@ -98,7 +82,7 @@ fn multi_subscribe_and_publish() {
 
     let session0 = solace_context
         .session(
-            format!("tcp://{}:{}", host, port),
+            host_name,
             "default",
             "default",
             "",
@@ -111,11 +95,13 @@ fn multi_subscribe_and_publish() {
             Some(|_: SessionEvent| {}),
         )
         .expect("creating session");
-    session0.subscribe(topic).expect("subscribing to topic");
+    session0
+        .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+        .expect("subscribing to topic");
 
     let session1 = solace_context
         .session(
-            format!("tcp://{}:{}", host, port),
+            host_name,
             "default",
             "default",
             "",
@@ -128,10 +114,9 @@ fn multi_subscribe_and_publish() {
             Some(|_: SessionEvent| {}),
         )
         .expect("creating session");
-    session1.subscribe(topic).expect("subscribing to topic");
-
-    // need to wait before publishing so that the client is properly subscribed
-    sleep(SLEEP_TIME);
+    session1
+        .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+        .expect("subscribing to topic");
 
     for msg in tx_msgs.clone() {
         let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
@@ -141,24 +126,13 @@ fn multi_subscribe_and_publish() {
             .payload(msg)
             .build()
             .expect("building outbound msg");
-        session0.publish(outbound_msg).expect("publishing message");
+        session0.publish(&outbound_msg).expect("publishing message");
     }
 
-    sleep(SLEEP_TIME);
-
-    let mut rx_msgs = vec![];
-    loop {
-        match rx.try_recv() {
-            Ok(msg) => {
-                let str = String::from_utf8_lossy(&msg).to_string();
-                rx_msgs.push(str);
-                if rx_msgs.len() == tx_msgs.len() * msg_multiplier {
-                    break;
-                }
-            }
-            _ => panic!(),
-        }
-    }
+    let rx_msgs: Vec<_> = recv_n(&rx, tx_msgs.len() * msg_multiplier)
+        .into_iter()
+        .map(|msg| String::from_utf8_lossy(&msg).to_string())
+        .collect();
 
     let mut rx_msg_map = HashMap::new();
     for msg in rx_msgs {
@@ -180,10 +154,9 @@ fn multi_subscribe_and_publish() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
 fn unsubscribe_and_publish() {
-    let host = option_env!("SOLACE_HOST").unwrap_or(DEFAULT_HOST);
-    let port = option_env!("SOLACE_PORT").unwrap_or(DEFAULT_PORT);
+    let host_name = broker_url();
 
     let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
     let (tx, rx) = mpsc::channel();
@@ -199,7 +172,7 @@ fn unsubscribe_and_publish() {
 
     let session = solace_context
         .session(
-            format!("tcp://{}:{}", host, port),
+            host_name,
             "default",
             "default",
             "",
@@ -207,9 +180,9 @@ fn unsubscribe_and_publish() {
             Some(|_: SessionEvent| {}),
         )
         .expect("creating session");
-    session.subscribe(topic).expect("subscribing to topic");
-
-    sleep(SLEEP_TIME);
+    session
+        .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+        .expect("subscribing to topic");
 
     for msg in tx_msgs.clone() {
         let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
@@ -219,30 +192,18 @@ fn unsubscribe_and_publish() {
             .payload(msg)
             .build()
             .expect("building outbound msg");
-        session.publish(outbound_msg).expect("publishing message");
+        session.publish(&outbound_msg).expect("publishing message");
     }
 
-    session.unsubscribe(topic).expect("unsubscribing to topic");
-
-    sleep(SLEEP_TIME);
-
-    let mut rx_msgs = vec![];
-
-    loop {
-        match rx.try_recv() {
-            Ok(msg) => {
-                let str = String::from_utf8_lossy(&msg).to_string();
-                rx_msgs.push(str);
-                if rx_msgs.len() == tx_msgs.len() {
-                    break;
-                }
-            }
-            _ => panic!(),
-        }
-    }
+    let rx_msgs: Vec<_> = recv_n(&rx, tx_msgs.len())
+        .into_iter()
+        .map(|msg| String::from_utf8_lossy(&msg).to_string())
+        .collect();
 
     assert_eq!(tx_msgs, rx_msgs);
 
+    session.unsubscribe(topic).expect("unsubscribing to topic");
+
     for msg in tx_msgs.clone() {
         let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
         let outbound_msg = OutboundMessageBuilder::new()
@@ -251,21 +212,16 @@ fn unsubscribe_and_publish() {
             .payload(msg)
             .build()
             .expect("building outbound msg");
-        session.publish(outbound_msg).expect("publishing message");
+        session.publish(&outbound_msg).expect("publishing message");
     }
 
-    sleep(SLEEP_TIME);
-
-    if rx.try_recv().is_ok() {
-        panic!()
-    }
+    assert!(rx.recv_timeout(QUIET_PERIOD).is_err());
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
 fn multi_thread_publisher() {
-    let host = option_env!("SOLACE_HOST").unwrap_or(DEFAULT_HOST);
-    let port = option_env!("SOLACE_PORT").unwrap_or(DEFAULT_PORT);
+    let host_name = broker_url();
 
     let msg_multiplier = 3;
 
@@ -284,7 +240,7 @@ fn multi_thread_publisher() {
     let session = Arc::new(Mutex::new(
         solace_context
             .session(
-                format!("tcp://{}:{}", host, port),
+                host_name,
                 "default",
                 "default",
                 "",
@@ -297,12 +253,9 @@ fn multi_thread_publisher() {
     session
         .lock()
         .unwrap()
-        .subscribe(topic)
+        .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
         .expect("multi_thread_publisher");
 
-    // need to wait before publishing so that the client is properly subscribed
-    sleep(SLEEP_TIME);
-
     let mut handles = vec![];
 
     for _ in 0..msg_multiplier {
@@ -319,7 +272,7 @@ fn multi_thread_publisher() {
                     .build()
                     .expect("building outbound msg");
                 session_clone_lock
-                    .publish(outbound_msg)
+                    .publish(&outbound_msg)
                     .expect("publishing message");
             }
             drop(session_clone_lock);
@@ -331,18 +284,11 @@ fn multi_thread_publisher() {
         handle.join().unwrap();
     }
 
-    sleep(SLEEP_TIME);
-    drop(session);
-    drop(solace_context);
-
-    let mut rx_msgs = vec![];
-
-    while let Ok(msg) = rx.recv() {
-        let str = String::from_utf8_lossy(&msg).to_string();
-        rx_msgs.push(str);
-    }
-
-    assert!(rx_msgs.len() == tx_msgs.len() * msg_multiplier);
+    let rx_msgs: Vec<_> = recv_n(&rx, tx_msgs.len() * msg_multiplier)
+        .into_iter()
+        .map(|msg| String::from_utf8_lossy(&msg).to_string())
+        .collect();
+    assert!(rx.recv_timeout(QUIET_PERIOD).is_err());
 
     let mut rx_msg_map = HashMap::new();
     for msg in rx_msgs {
@@ -364,10 +310,147 @@ fn multi_thread_publisher() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
+fn session_outlives_dropped_context() {
+    let host_name = broker_url();
+
+    let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
+    let session = solace_context
+        .session(
+            host_name,
+            "default",
+            "default",
+            "",
+            Some(|_| {}),
+            Some(|_| {}),
+        )
+        .expect("creating session");
+
+    let client_name = session.client_name().unwrap();
+    assert_eq!(vec![client_name], solace_context.live_sessions());
+
+    let context = solace_context.clone();
+    drop(solace_context);
+    drop(context);
+
+    let dest = MessageDestination::new(DestinationType::Topic, "session_outlives_dropped_context")
+        .unwrap();
+    let outbound_msg = OutboundMessageBuilder::new()
+        .destination(dest)
+        .delivery_mode(DeliveryMode::Direct)
+        .payload("hello")
+        .build()
+        .expect("building outbound msg");
+    session.publish(&outbound_msg).expect("publishing message");
+}
+
+#[test]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
+fn context_stats_count_message_callbacks() {
+    let host_name = broker_url();
+
+    let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
+    let (tx, rx) = mpsc::channel();
+    let topic = "context_stats_count_message_callbacks";
+
+    let session = solace_context
+        .session(
+            host_name,
+            "default",
+            "default",
+            "",
+            Some(move |message: InboundMessage| {
+                let _ = tx.send(message);
+            }),
+            Some(|_| {}),
+        )
+        .expect("creating session");
+
+    session
+        .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+        .expect("subscribing to topic");
+
+    let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
+    let outbound_msg = OutboundMessageBuilder::new()
+        .destination(dest)
+        .delivery_mode(DeliveryMode::Direct)
+        .payload("hello")
+        .build()
+        .expect("building outbound msg");
+    session.publish(&outbound_msg).expect("publishing message");
+    rx.recv_timeout(CONFIRM_TIMEOUT).expect("receiving message");
+
+    let stats = solace_context.stats();
+    assert_eq!(1, stats.sessions);
+    assert_eq!(1, stats.rx_messages);
+
+    // a callback is counted once it returns, which dropping the session waits for
+    drop(session);
+    let stats = solace_context.stats();
+    assert_eq!(0, stats.sessions);
+    assert_eq!(1, stats.message_callbacks);
+    assert!(stats.event_callbacks > 0);
+}
+
+#[test]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
+fn publish_batch_reports_every_message() {
+    let host_name = broker_url();
+
+    let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
+    let (tx, rx) = mpsc::channel();
+    let topic = "publish_batch_reports_every_message";
+
+    let session = solace_context
+        .session(
+            host_name,
+            "default",
+            "default",
+            "",
+            Some(move |message: InboundMessage| {
+                let _ = tx.send(message);
+            }),
+            Some(|_| {}),
+        )
+        .expect("creating session");
+
+    session
+        .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+        .expect("subscribing to topic");
+
+    let messages: Vec<_> = ["helo", "hello2", "hello4"]
+        .into_iter()
+        .map(|payload| {
+            let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
+            OutboundMessageBuilder::new()
+                .destination(dest)
+                .delivery_mode(DeliveryMode::Direct)
+                .payload(payload)
+                .build()
+                .expect("building outbound msg")
+        })
+        .collect();
+
+    let result = session.publish_batch(&messages);
+    assert!(result.is_complete(), "{result:?}");
+    assert_eq!(3, result.accepted());
+
+    recv_n(&rx, 3);
+
+    let handle = session.publisher_handle();
+    drop(session);
+    let result = handle.publish_batch(&messages);
+    assert_eq!(0..3, result.unsent());
+    assert!(matches!(
+        result.outcomes()[0],
+        BatchOutcome::Failed(SessionError::SessionClosed)
+    ));
+}
+
+#[test]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
 fn no_local_session() {
-    let host = option_env!("SOLACE_HOST").unwrap_or(DEFAULT_HOST);
-    let port = option_env!("SOLACE_PORT").unwrap_or(DEFAULT_PORT);
+    let host_name = broker_url();
 
     let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
     let (tx, rx) = mpsc::channel();
@@ -380,7 +463,7 @@ fn no_local_session() {
 
     let session = solace_context
         .session_builder()
-        .host_name(format!("tcp://{}:{}", host, port))
+        .host_name(host_name)
         .vpn_name("default")
         .username("default")
         .password("")
@@ -390,10 +473,9 @@ fn no_local_session() {
         .build()
         .expect("creating session");
 
-    session.subscribe(topic).expect("subscribing to topic");
-
-    // need to wait before publishing so that the client is properly subscribed
-    sleep(SLEEP_TIME);
+    session
+        .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+        .expect("subscribing to topic");
 
     for msg in tx_msgs.clone() {
         let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
@@ -403,18 +485,16 @@ fn no_local_session() {
             .payload(msg)
             .build()
             .expect("building outbound msg");
-        session.publish(outbound_msg).expect("publishing message");
+        session.publish(&outbound_msg).expect("publishing message");
     }
-    sleep(SLEEP_TIME * 2);
 
-    assert!(rx.try_recv().is_err());
+    assert!(rx.recv_timeout(QUIET_PERIOD).is_err());
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
 fn auto_generate_tx_rx_session_fields() {
-    let host = option_env!("SOLACE_HOST").unwrap_or(DEFAULT_HOST);
-    let port = option_env!("SOLACE_PORT").unwrap_or(DEFAULT_PORT);
+    let host_name = broker_url();
 
     let (tx, rx) = mpsc::channel();
 
@@ -428,7 +508,7 @@ fn auto_generate_tx_rx_session_fields() {
 
     let session = solace_context
         .session_builder()
-        .host_name(format!("tcp://{}:{}", host, port))
+        .host_name(host_name)
         .vpn_name("default")
         .username("default")
         .password("")
@@ -444,10 +524,9 @@ fn auto_generate_tx_rx_session_fields() {
         .build()
         .expect("creating session");
 
-    session.subscribe(topic).expect("subscribing to topic");
-
-    // need to wait before publishing so that the client is properly subscribed
-    sleep(SLEEP_TIME);
+    session
+        .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+        .expect("subscribing to topic");
 
     for msg in tx_msgs.clone().into_iter().cycle().take(send_count) {
         let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
@@ -457,17 +536,13 @@ fn auto_generate_tx_rx_session_fields() {
             .payload(msg)
             .build()
             .expect("building outbound msg");
-        session.publish(outbound_msg).expect("publishing message");
+        session.publish(&outbound_msg).expect("publishing message");
     }
-    sleep(SLEEP_TIME);
-    let _ = session.disconnect();
-
-    drop(solace_context);
 
     let mut iter = tx_msgs.clone().into_iter().cycle();
 
     let mut rx_count = 0;
-    while let Ok(msg) = rx.recv() {
+    for msg in recv_n(&rx, send_count) {
         assert!(msg.get_payload().unwrap().unwrap() == iter.next().unwrap().as_bytes());
         assert!(msg.get_receive_timestamp().is_ok_and(|v| v.is_some()));
         assert!(msg.get_sender_id().is_ok_and(|v| v.is_some()));
@@ -481,10 +556,9 @@ fn auto_generate_tx_rx_session_fields() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
 fn request_and_reply() {
-    let host = option_env!("SOLACE_HOST").unwrap_or(DEFAULT_HOST);
-    let port = option_env!("SOLACE_PORT").unwrap_or(DEFAULT_PORT);
+    let host_name = broker_url();
     let topic = "request_and_reply";
 
     let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
@@ -497,7 +571,7 @@ fn request_and_reply() {
         let req = s.spawn(move || {
             let session = context
                 .session(
-                    format!("tcp://{}:{}", host, port),
+                    host_name,
                     "default",
                     "default",
                     "",
@@ -505,8 +579,8 @@ fn request_and_reply() {
                     Some(|_| {}),
                 )
                 .unwrap();
+            // the responder is subscribed once it reaches the barrier
             barrier.wait();
-            sleep(SLEEP_TIME);
 
             let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
 
@@ -527,7 +601,7 @@ fn request_and_reply() {
             let (tx, rx) = mpsc::channel();
             let session = context
                 .session(
-                    format!("tcp://{}:{}", host, port),
+                    host_name,
                     "default",
                     "default",
                     "",
@@ -537,11 +611,11 @@ fn request_and_reply() {
                     Some(|_| {}),
                 )
                 .unwrap();
-            session.subscribe(topic).unwrap();
+            session.subscribe_confirmed(topic, CONFIRM_TIMEOUT).unwrap();
 
             g_barrier.wait();
 
-            let msg = rx.recv().unwrap();
+            let msg = rx.recv_timeout(RECV_TIMEOUT).unwrap();
 
             let reply_msg = OutboundMessageBuilder::new()
                 .destination(msg.get_reply_to().unwrap().unwrap())
@@ -551,9 +625,280 @@ fn request_and_reply() {
                 .correlation_id(msg.get_correlation_id().unwrap().unwrap())
                 .build()
                 .expect("could not build message");
-            let _ = session.publish(reply_msg);
+            let _ = session.publish(&reply_msg);
         });
         assert!(res.join().is_ok());
         assert!(req.join().is_ok());
     });
 }
+
+#[test]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
+fn request_no_wait_delivers_reply_to_callback() {
+    let host_name = broker_url();
+    let topic = "request_no_wait_delivers_reply_to_callback";
+
+    let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
+    let g_barrier = Arc::new(Barrier::new(2));
+
+    thread::scope(|s| {
+        let context = solace_context.clone();
+        let barrier = g_barrier.clone();
+        // requester
+        let req = s.spawn(move || {
+            let (tx, rx) = mpsc::channel();
+            let session = context
+                .session(
+                    host_name,
+                    "default",
+                    "default",
+                    "",
+                    Some(move |message: InboundMessage| {
+                        let _ = tx.send(message);
+                    }),
+                    Some(|_| {}),
+                )
+                .unwrap();
+            // the responder is subscribed once it reaches the barrier
+            barrier.wait();
+
+            let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
+
+            let request = OutboundMessageBuilder::new()
+                .destination(dest)
+                .delivery_mode(DeliveryMode::Direct)
+                .payload("ping".to_string())
+                .build()
+                .expect("could not build message");
+            let correlation_id = session.request_no_wait(request).unwrap();
+
+            let reply = rx.recv_timeout(RECV_TIMEOUT).unwrap();
+            assert!(reply.get_payload().unwrap().unwrap() == b"pong");
+            assert!(reply.get_correlation_id().unwrap() == Some(correlation_id.as_str()));
+        });
+
+        let context = solace_context.clone();
+        let res = s.spawn(move || {
+            let (tx, rx) = mpsc::channel();
+            let session = context
+                .session(
+                    host_name,
+                    "default",
+                    "default",
+                    "",
+                    Some(move |message: InboundMessage| {
+                        let _ = tx.send(message);
+                    }),
+                    Some(|_| {}),
+                )
+                .unwrap();
+            session.subscribe_confirmed(topic, CONFIRM_TIMEOUT).unwrap();
+
+            g_barrier.wait();
+
+            let msg = rx.recv_timeout(RECV_TIMEOUT).unwrap();
+
+            let reply_msg = OutboundMessageBuilder::new()
+                .destination(msg.get_reply_to().unwrap().unwrap())
+                .delivery_mode(DeliveryMode::Direct)
+                .payload("pong".to_string())
+                .is_reply(true)
+                .correlation_id(msg.get_correlation_id().unwrap().unwrap())
+                .build()
+                .expect("could not build message");
+            let _ = session.publish(&reply_msg);
+        });
+        assert!(res.join().is_ok());
+        assert!(req.join().is_ok());
+    });
+}
+
+#[test]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
+fn guaranteed_request_and_reply() {
+    let host_name = broker_url();
+    let topic = "guaranteed_request_and_reply";
+
+    let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let responder = solace_context
+        .session(
+            host_name,
+            "default",
+            "default",
+            "",
+            Some(move |message: InboundMessage| {
+                let _ = tx.send(message);
+            }),
+            Some(|_| {}),
+        )
+        .unwrap();
+    responder
+        .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+        .unwrap();
+    let publisher = responder.publisher_handle();
+
+    let session = solace_context
+        .session(
+            host_name,
+            "default",
+            "default",
+            "",
+            Some(|_| {}),
+            Some(|_| {}),
+        )
+        .unwrap();
+    let requester = session.guaranteed_requester().unwrap();
+    assert_eq!(DestinationType::QueueTemp, requester.reply_to().dest_type);
+
+    thread::scope(|s| {
+        s.spawn(move || {
+            let msg = rx.recv_timeout(RECV_TIMEOUT).unwrap();
+            let reply_msg = OutboundMessageBuilder::new()
+                .destination(msg.get_reply_to().unwrap().unwrap())
+                .delivery_mode(DeliveryMode::Persistent)
+                .payload("pong".to_string())
+                .is_reply(true)
+                .correlation_id(msg.get_correlation_id().unwrap().unwrap())
+                .build()
+                .expect("could not build message");
+            publisher.publish(&reply_msg).unwrap();
+        });
+
+        let request = OutboundMessageBuilder::new()
+            .destination(MessageDestination::new(DestinationType::Topic, topic).unwrap())
+            .delivery_mode(DeliveryMode::Direct)
+            .payload("ping".to_string())
+            .build()
+            .expect("could not build message");
+        let reply = requester.request(request, RECV_TIMEOUT).unwrap();
+        assert_eq!(b"pong", reply.get_payload().unwrap().unwrap());
+    });
+}
+
+#[test]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
+fn semp_request_over_message_bus() {
+    let host_name = broker_url();
+
+    let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
+    let session = solace_context
+        .session(
+            host_name,
+            "default",
+            "default",
+            "",
+            Some(|_| {}),
+            Some(|_| {}),
+        )
+        .unwrap();
+
+    let reply = session
+        .semp_request(
+            "<rpc><show><message-vpn><vpn-name>default</vpn-name></message-vpn></show></rpc>",
+            NonZeroU32::new(5_000).unwrap(),
+        )
+        .unwrap();
+    assert!(reply.contains("<rpc-reply"));
+}
+
+#[test]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
+fn owned_session_moved_to_thread() {
+    let host_name = broker_url();
+
+    let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
+    let (tx, rx) = mpsc::channel();
+    let topic = "owned_session_moved_to_thread";
+
+    let session = solace_context
+        .session_builder()
+        .host_name(host_name)
+        .vpn_name("default")
+        .username("default")
+        .password("")
+        .on_message(move |message: InboundMessage| {
+            let Ok(Some(payload)) = message.get_payload() else {
+                return;
+            };
+            let _ = tx.send(payload.to_owned());
+        })
+        .on_event(|_: SessionEvent| {})
+        .build_owned()
+        .expect("creating session");
+
+    let session = Box::new(session);
+
+    thread::spawn(move || {
+        session
+            .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+            .expect("subscribing to topic");
+
+        let dest = MessageDestination::new(DestinationType::Topic, topic).unwrap();
+        let outbound_msg = OutboundMessageBuilder::new()
+            .destination(dest)
+            .delivery_mode(DeliveryMode::Direct)
+            .payload("hello")
+            .build()
+            .expect("building outbound msg");
+        session.publish(&outbound_msg).expect("publishing message");
+    })
+    .join()
+    .unwrap();
+
+    let msg = rx.recv_timeout(RECV_TIMEOUT).unwrap();
+    assert_eq!(b"hello".to_vec(), msg);
+}
+
+#[test]
+#[cfg_attr(not(feature = "broker-tests"), ignore)]
+fn forward_received_message() {
+    let host_name = broker_url();
+
+    let solace_context = Context::new(SolaceLogLevel::Warning).unwrap();
+    let (tx, rx) = mpsc::channel();
+    let (in_topic, out_topic) = ("forward_in", "forward_out");
+
+    let session = solace_context
+        .session(
+            host_name,
+            "default",
+            "default",
+            "",
+            Some(move |message: InboundMessage| {
+                let _ = tx.send(message);
+            }),
+            Some(|_: SessionEvent| {}),
+        )
+        .expect("creating session");
+    for topic in [in_topic, out_topic] {
+        session
+            .subscribe_confirmed(topic, CONFIRM_TIMEOUT)
+            .expect("subscribing to topic");
+    }
+
+    let dest = MessageDestination::new(DestinationType::Topic, in_topic).unwrap();
+    let outbound_msg = OutboundMessageBuilder::new()
+        .destination(dest)
+        .delivery_mode(DeliveryMode::Direct)
+        .payload("forwarded")
+        .correlation_id("forward_id")
+        .build()
+        .expect("building outbound msg");
+    session.publish(&outbound_msg).expect("publishing message");
+
+    let received = rx.recv_timeout(RECV_TIMEOUT).expect("receiving message");
+    let dest = MessageDestination::new(DestinationType::Topic, out_topic).unwrap();
+    session
+        .forward(&received, dest, ForwardOptions::new())
+        .expect("forwarding message");
+
+    let forwarded = rx
+        .recv_timeout(RECV_TIMEOUT)
+        .expect("receiving forwarded message");
+    let destination = forwarded.get_destination().unwrap().unwrap();
+    assert_eq!(out_topic, destination.dest.to_string_lossy());
+    assert_eq!(Some(&b"forwarded"[..]), forwarded.get_payload().unwrap());
+    assert_eq!(Some("forward_id"), forwarded.get_correlation_id().unwrap());
+}